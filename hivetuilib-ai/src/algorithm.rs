@@ -1,8 +1,12 @@
 use std::{cmp::Ordering, convert::TryFrom, fmt::Debug, marker::PhantomData, ops::ControlFlow};
 
 use hivetuilib::{
-    engine::{logging::EventLog, CloneError, Engine, EventListener},
-    GameData, RevEffect,
+    engine::{
+        logging::EventLog,
+        runner::{Player, PlayerAction},
+        CloneError, Engine, EventListener, LoggingEngine, PendingDecision,
+    },
+    DecisionObject, GameData, RevEffect,
 };
 
 use crate::{
@@ -34,12 +38,13 @@ pub trait RateAndMap<T: GameData> {
     ) -> RatingType;
 }
 
-// TODO: lift last part (i.e. only decide on subdecision)?
-/// To apply the min-max algorithm, the engine must be in pending decision state
-/// and the decision must be a top-level decision.
+/// To apply the min-max algorithm, the engine must be in pending decision state.
+/// Within a decision chain, only completions of the already selected options are rated.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum InvalidEngineState {
     PendingEffect,
+    /// Not returned anymore, since the algorithm can be applied within a decision chain.
+    #[deprecated(note = "the algorithm can be applied to follow-up decisions")]
     FollowUp,
     Finished,
 }
@@ -48,6 +53,7 @@ impl From<CloneError> for InvalidEngineState {
     fn from(e: CloneError) -> Self {
         match e {
             CloneError::PendingEffect => InvalidEngineState::PendingEffect,
+            #[allow(deprecated)]
             CloneError::FollowUp => InvalidEngineState::FollowUp,
         }
     }
@@ -56,6 +62,8 @@ impl From<CloneError> for InvalidEngineState {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MinMaxError {
     PendingEffect,
+    /// Not returned anymore, since the algorithm can be applied within a decision chain.
+    #[deprecated(note = "the algorithm can be applied to follow-up decisions")]
     FollowUp,
     Finished,
     Cancelled,
@@ -65,6 +73,7 @@ impl MinMaxError {
     pub fn into_engine_state_error(self) -> Option<InvalidEngineState> {
         match self {
             MinMaxError::PendingEffect => Some(InvalidEngineState::PendingEffect),
+            #[allow(deprecated)]
             MinMaxError::FollowUp => Some(InvalidEngineState::FollowUp),
             MinMaxError::Finished => Some(InvalidEngineState::Finished),
             MinMaxError::Cancelled => None,
@@ -76,24 +85,26 @@ impl From<CloneError> for MinMaxError {
     fn from(e: CloneError) -> Self {
         match e {
             CloneError::PendingEffect => MinMaxError::PendingEffect,
+            #[allow(deprecated)]
             CloneError::FollowUp => MinMaxError::FollowUp,
         }
     }
 }
 
-pub fn add_context_to_ratings<T, L>(
-    engine: &Engine<T, L>,
+pub fn add_context_to_ratings<T, L, D>(
+    engine: &Engine<T, L, D>,
     ratings: Vec<(RatingType, Box<[usize]>)>,
 ) -> Result<Vec<MoveRating<T>>, InvalidEngineState>
 where
     T: GameData + Clone + Debug,
     L: EventListener<T>,
+    D: DecisionObject<T> + ?Sized,
     T::EffectType: RevEffect<T>,
 {
     if engine.is_finished() {
         return Err(InvalidEngineState::Finished);
     }
-    let mut engine = engine.try_clone_with_listener(EventLog::new())?;
+    let mut engine = clone_for_search(engine)?;
     let mut stepper = EngineStepper::new(&mut engine);
 
     let result = ratings
@@ -107,6 +118,22 @@ where
     Ok(result)
 }
 
+/// Clones the engine for searching, which requires a logging engine with plain decisions.
+pub(crate) fn clone_for_search<T, L, D>(
+    engine: &Engine<T, L, D>,
+) -> Result<LoggingEngine<T>, CloneError>
+where
+    T: GameData + Clone,
+    L: EventListener<T>,
+    D: DecisionObject<T> + ?Sized,
+{
+    let engine = engine.try_clone_with_listener(EventLog::new())?;
+    // the clone is never in pending effect state
+    Ok(engine
+        .try_convert()
+        .unwrap_or_else(|_| panic!("{}", INTERNAL_ERROR)))
+}
+
 pub struct PruningInput {
     pub total_depth: usize,
     pub current_depth: usize,
@@ -156,10 +183,11 @@ where
         &self.rate_and_map
     }
 
-    pub fn apply<L>(&self, engine: &mut Engine<T, L>)
+    pub fn apply<L, D>(&self, engine: &mut Engine<T, L, D>)
     where
         T: Clone,
         L: EventListener<T>,
+        D: DecisionObject<T> + ?Sized,
     {
         let (_, index_list, _) = self.run(engine).expect("Invalid engine state!");
         for &i in index_list.iter() {
@@ -170,23 +198,25 @@ where
         }
     }
 
-    pub fn run<L>(&self, engine: &Engine<T, L>) -> Result<MoveRating<T>, InvalidEngineState>
+    pub fn run<L, D>(&self, engine: &Engine<T, L, D>) -> Result<MoveRating<T>, InvalidEngineState>
     where
         T: Clone,
         L: EventListener<T>,
+        D: DecisionObject<T> + ?Sized,
     {
         self.run_with_cancellation(engine, || false)
             .map_err(|e| e.into_engine_state_error().unwrap())
     }
 
-    pub fn run_with_cancellation<L, F>(
+    pub fn run_with_cancellation<L, D, F>(
         &self,
-        engine: &Engine<T, L>,
+        engine: &Engine<T, L, D>,
         should_cancel: F,
     ) -> Result<MoveRating<T>, MinMaxError>
     where
         T: GameData + Clone,
         L: EventListener<T>,
+        D: DecisionObject<T> + ?Sized,
         F: Fn() -> bool,
     {
         let ratings = self.run_all_ratings_with_cancellation(engine, should_cancel)?;
@@ -199,33 +229,35 @@ where
         Ok(result)
     }
 
-    pub fn run_all_ratings<L>(
+    pub fn run_all_ratings<L, D>(
         &self,
-        engine: &Engine<T, L>,
+        engine: &Engine<T, L, D>,
     ) -> Result<Vec<MoveRating<T>>, InvalidEngineState>
     where
         T: GameData + Clone,
         L: EventListener<T>,
+        D: DecisionObject<T> + ?Sized,
     {
         self.run_all_ratings_with_cancellation(engine, || false)
             .map_err(|e| e.into_engine_state_error().unwrap())
     }
 
-    pub fn run_all_ratings_with_cancellation<L, F>(
+    pub fn run_all_ratings_with_cancellation<L, D, F>(
         &self,
-        engine: &Engine<T, L>,
+        engine: &Engine<T, L, D>,
         should_cancel: F,
     ) -> Result<Vec<MoveRating<T>>, MinMaxError>
     where
         T: GameData + Clone,
         L: EventListener<T>,
+        D: DecisionObject<T> + ?Sized,
         F: Fn() -> bool,
     {
         if engine.is_finished() {
             return Err(MinMaxError::Finished);
         }
         // let now = Instant::now();
-        let mut engine = engine.try_clone_with_listener(EventLog::new())?;
+        let mut engine = clone_for_search(engine)?;
 
         self.params.integrity_check();
         let mut stepper = EngineStepper::new(&mut engine);
@@ -608,9 +640,32 @@ where
     }
 }
 
+/// The best option is calculated separately for each decision of a chain.
+impl<T, R, L, D> Player<T, L, D> for MinMaxAlgorithm<T, R>
+where
+    T: GameData + Clone + Debug,
+    T::EffectType: RevEffect<T>,
+    R: RateAndMap<T>,
+    L: EventListener<T>,
+    D: DecisionObject<T> + ?Sized,
+{
+    fn decide(&mut self, decision: &PendingDecision<T, L, D>) -> Result<PlayerAction, String> {
+        let (_, index_list, _) = self.run(decision.engine()).map_err(|e| format!("{e:?}"))?;
+        Ok(PlayerAction::Select(index_list[0]))
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use hivetuilib::engine::{Engine, GameState};
+    use std::thread;
+
+    use hivetuilib::{
+        engine::{
+            runner::{GameRunner, Player, ScriptedPlayer},
+            Engine, EventListener, GameState,
+        },
+        DecisionObject,
+    };
 
     use crate::{
         engine_stepper::EngineStepper,
@@ -830,4 +885,76 @@ mod test {
             Ok((-4, Box::from([1]), ZeroOneContext::Flat))
         );
     }
+
+    #[test]
+    fn run_in_chain_test() {
+        let sliding = SlidingParams::with_defaults(2, 1, 4, 4, 4, 2, 4, 1);
+        let params = Params::new(2, sliding.clone(), 1);
+        let mut alg = MinMaxAlgorithm::new(params, RateAndMapZeroOne);
+        alg.params.first_cut_delay_depth = 1;
+        let data = ZeroOneGame::new(true, 8);
+        let mut engine = Engine::new_logging(2, data);
+        match engine.pull() {
+            GameState::PendingDecision(dec) => dec.select_option(1),
+            _ => unreachable!(),
+        }
+        assert_eq!(
+            alg.run(&engine),
+            Ok((0, Box::from([0]), ZeroOneContext::OneAnd))
+        );
+        alg.apply(&mut engine);
+        match engine.pull() {
+            GameState::PendingEffect(eff) => eff.all_effects(),
+            _ => unreachable!(),
+        }
+        assert_eq!(engine.data().num_ones, 1);
+        assert_eq!(engine.data().num_zeros, 1);
+        assert!(engine.pending_chain().is_empty());
+    }
+
+    #[test]
+    fn player_test() {
+        let sliding = SlidingParams::with_defaults(2, 1, 4, 4, 4, 2, 4, 1);
+        let params = Params::new(2, sliding.clone(), 1);
+        let mut alg = MinMaxAlgorithm::new(params, RateAndMapZeroOne);
+        alg.params.first_cut_delay_depth = 1;
+        let engine = Engine::new_logging(2, ZeroOneGame::new(true, 6));
+        let players: Vec<Box<dyn Player<_, _>>> = vec![
+            Box::new(alg),
+            Box::new(ScriptedPlayer::selecting([1, 1, 1, 1])),
+        ];
+        let mut runner = GameRunner::new(engine, players);
+        assert_eq!(runner.run(), Ok(None));
+        let data = runner.engine().data();
+        assert_eq!(data.num_zeros + data.num_ones, 6);
+    }
+
+    fn play_against_script<L, D>(engine: Engine<ZeroOneGame, L, D>) -> ZeroOneGame
+    where
+        L: EventListener<ZeroOneGame>,
+        D: DecisionObject<ZeroOneGame> + ?Sized,
+    {
+        let sliding = SlidingParams::with_defaults(2, 1, 4, 4, 4, 2, 4, 1);
+        let params = Params::new(2, sliding, 1);
+        let mut alg = MinMaxAlgorithm::new(params, RateAndMapZeroOne);
+        alg.params.first_cut_delay_depth = 1;
+        let players: Vec<Box<dyn Player<_, _, _>>> = vec![
+            Box::new(alg),
+            Box::new(ScriptedPlayer::selecting([1, 1, 1, 1])),
+        ];
+        let mut runner = GameRunner::new(engine, players);
+        assert_eq!(runner.run(), Ok(None));
+        runner.engine().data().clone()
+    }
+
+    #[test]
+    fn send_engine_test() {
+        let engine = Engine::new_send(2, ZeroOneGame::new(true, 6));
+        let data = thread::spawn(move || play_against_script(engine))
+            .join()
+            .unwrap();
+        let expected = play_against_script(Engine::new_logging(2, ZeroOneGame::new(true, 6)));
+        assert_eq!(data.num_zeros, expected.num_zeros);
+        assert_eq!(data.num_ones, expected.num_ones);
+    }
 }
@@ -14,6 +14,8 @@ where
 {
     engine: &'a mut Engine<T, EventLog<T>>,
     decision_context: Vec<(T::Context, usize)>,
+    /// options already selected in the decision chain of the root position
+    root_chain: Vec<usize>,
 }
 
 impl<T: GameData + Debug> Debug for EngineStepper<'_, T>
@@ -30,9 +32,11 @@ where
     T::EffectType: RevEffect<T>,
 {
     pub fn new(engine: &'a mut Engine<T, EventLog<T>>) -> Self {
+        let root_chain = engine.pending_chain();
         Self {
             engine,
             decision_context: Vec::new(),
+            root_chain,
         }
     }
 
@@ -74,7 +78,17 @@ where
         if !self.engine.undo_last_decision() {
             panic!("{}", INTERNAL_ERROR)
         }
-        self.decision_context.pop().expect(INTERNAL_ERROR)
+        let result = self.decision_context.pop().expect(INTERNAL_ERROR);
+        if self.decision_context.is_empty() {
+            // undoing retracts the whole chain, so restore the root position
+            for &index in &self.root_chain {
+                match self.engine.pull() {
+                    GameState::PendingDecision(dec) => dec.select_option(index),
+                    _ => panic!("{}", INTERNAL_ERROR),
+                }
+            }
+        }
+        result
     }
 
     pub fn is_finished(&self) -> bool {
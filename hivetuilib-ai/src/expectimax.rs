@@ -0,0 +1,326 @@
+use std::{fmt::Debug, marker::PhantomData};
+
+use hivetuilib::{
+    engine::{
+        logging::EventLog,
+        runner::{Player, PlayerAction},
+        Engine, EventListener, GameState, PendingDecision,
+    },
+    DecisionObject, GameData, RevEffect,
+};
+
+use crate::{algorithm::clone_for_search, InvalidEngineState, RateAndMap, INTERNAL_ERROR};
+
+/// Exhaustive expectimax search, which supports chance decisions (see
+/// `Decision::chance_weights`). The value of a chance decision is the weighted mean
+/// of its options, other decisions are maximized for the searching player and
+/// minimized for the opponents.
+///
+/// Only `RateAndMap::rate_game_state` is used, which rates the leafs of the search tree.
+pub struct Expectimax<T: GameData + Debug, R: RateAndMap<T>>
+where
+    T::EffectType: RevEffect<T>,
+{
+    depth: usize,
+    rate_and_map: R,
+    _t: PhantomData<T>,
+}
+
+impl<T: GameData + Debug, R: RateAndMap<T>> Expectimax<T, R>
+where
+    T::EffectType: RevEffect<T>,
+{
+    /// The depth is the number of decision chains that are searched, including chance decisions.
+    pub fn new(depth: usize, rate_and_map: R) -> Self {
+        assert!(depth > 0, "Search depth must be positive!");
+        Self {
+            depth,
+            rate_and_map,
+            _t: PhantomData,
+        }
+    }
+
+    pub fn rate_and_map(&self) -> &R {
+        &self.rate_and_map
+    }
+
+    /// Returns the expected value of each option of the pending decision,
+    /// from the perspective of its player.
+    pub fn run_all_ratings<L, D>(
+        &self,
+        engine: &Engine<T, L, D>,
+    ) -> Result<Vec<f64>, InvalidEngineState>
+    where
+        T: Clone,
+        L: EventListener<T>,
+        D: DecisionObject<T> + ?Sized,
+    {
+        if engine.is_finished() {
+            return Err(InvalidEngineState::Finished);
+        }
+        let mut engine = clone_for_search(engine)?;
+        let (option_count, player) = match engine.pull() {
+            GameState::PendingDecision(dec) => (dec.option_count(), dec.player()),
+            _ => panic!("{}", INTERNAL_ERROR),
+        };
+        let chain = engine.pending_chain();
+        Ok((0..option_count)
+            .map(|index| self.option_value(&mut engine, &chain, index, self.depth, player))
+            .collect())
+    }
+
+    /// Returns the best option of the pending decision and its expected value.
+    pub fn run<L, D>(&self, engine: &Engine<T, L, D>) -> Result<(f64, usize), InvalidEngineState>
+    where
+        T: Clone,
+        L: EventListener<T>,
+        D: DecisionObject<T> + ?Sized,
+    {
+        let ratings = self.run_all_ratings(engine)?;
+        let (index, &value) = ratings
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .expect(INTERNAL_ERROR);
+        Ok((value, index))
+    }
+
+    fn value(&self, engine: &mut Engine<T, EventLog<T>>, depth: usize, player: usize) -> f64 {
+        if let GameState::PendingEffect(effect) = engine.pull() {
+            effect.all_effects();
+        }
+        let (option_count, weights, dec_player) = match engine.pull() {
+            GameState::PendingDecision(dec) if dec.level_in_chain() > 0 || depth > 0 => {
+                (dec.option_count(), dec.chance_weights(), dec.player())
+            }
+            _ => {
+                return f64::from(
+                    self.rate_and_map
+                        .rate_game_state(engine.data(), &[], player),
+                );
+            }
+        };
+        let chain = engine.pending_chain();
+        match weights {
+            Some(weights) => {
+                let total: u32 = weights.iter().sum();
+                let sum: f64 = (0..option_count)
+                    .filter(|&index| weights[index] > 0)
+                    .map(|index| {
+                        f64::from(weights[index])
+                            * self.option_value(engine, &chain, index, depth, player)
+                    })
+                    .sum();
+                sum / f64::from(total)
+            }
+            None => {
+                let values =
+                    (0..option_count).map(|i| self.option_value(engine, &chain, i, depth, player));
+                if dec_player == player {
+                    values.fold(f64::NEG_INFINITY, f64::max)
+                } else {
+                    values.fold(f64::INFINITY, f64::min)
+                }
+            }
+        }
+    }
+
+    /// Selects the option, computes its value and restores the pending decision chain.
+    fn option_value(
+        &self,
+        engine: &mut Engine<T, EventLog<T>>,
+        chain: &[usize],
+        index: usize,
+        depth: usize,
+        player: usize,
+    ) -> f64 {
+        match engine.pull() {
+            GameState::PendingDecision(dec) => dec.select_option(index),
+            _ => panic!("{}", INTERNAL_ERROR),
+        }
+        match engine.pull() {
+            GameState::PendingDecision(_) => {
+                let value = self.value(engine, depth, player);
+                match engine.pull() {
+                    GameState::PendingDecision(dec) => {
+                        let retracted = dec
+                            .into_follow_up_decision()
+                            .is_some_and(|follow_up| follow_up.retract_n(1));
+                        assert!(retracted, "{}", INTERNAL_ERROR);
+                    }
+                    _ => panic!("{}", INTERNAL_ERROR),
+                }
+                value
+            }
+            _ => {
+                let value = self.value(engine, depth - 1, player);
+                assert!(engine.undo_last_decision(), "{}", INTERNAL_ERROR);
+                for &i in chain {
+                    match engine.pull() {
+                        GameState::PendingDecision(dec) => dec.select_option(i),
+                        _ => panic!("{}", INTERNAL_ERROR),
+                    }
+                }
+                value
+            }
+        }
+    }
+}
+
+/// Selects the option with the highest expected value, separately for each decision of a chain.
+impl<T, R, L, D> Player<T, L, D> for Expectimax<T, R>
+where
+    T: GameData + Clone + Debug,
+    T::EffectType: RevEffect<T>,
+    R: RateAndMap<T>,
+    L: EventListener<T>,
+    D: DecisionObject<T> + ?Sized,
+{
+    fn decide(&mut self, decision: &PendingDecision<T, L, D>) -> Result<PlayerAction, String> {
+        let (_, index) = self.run(decision.engine()).map_err(|e| format!("{e:?}"))?;
+        Ok(PlayerAction::Select(index))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use hivetuilib::{
+        chance_decision::ChanceDecision,
+        engine::{
+            runner::{GameRunner, Player, ScriptedPlayer},
+            Engine, GameState,
+        },
+        plain_decision::PlainDecision,
+        Decision, GameData, RevEffect,
+    };
+
+    use crate::{
+        rater::{DecisionType, Rater},
+        test::{RateAndMapZeroOne, ZeroOneGame},
+        RateAndMap, RatingType,
+    };
+
+    use super::Expectimax;
+
+    /// In each round, the player either takes two points or gambles for five points.
+    #[derive(Debug, Clone)]
+    struct CoinGame {
+        points: [i32; 2],
+        /// weights of losing and winning a gamble
+        weights: [u32; 2],
+        round: usize,
+        rounds: usize,
+    }
+
+    impl CoinGame {
+        fn new(weights: [u32; 2], rounds: usize) -> Self {
+            Self {
+                points: [0; 2],
+                weights,
+                round: 0,
+                rounds,
+            }
+        }
+
+        fn player(&self) -> usize {
+            self.round % 2
+        }
+    }
+
+    fn add_points(dec: &mut PlainDecision<CoinGame>, points: i32) {
+        dec.add_rev_effect(
+            move |data: &mut CoinGame| {
+                let player = data.player();
+                data.points[player] += points;
+                data.round += 1;
+                None
+            },
+            move |data: &mut CoinGame| {
+                data.round -= 1;
+                let player = data.player();
+                data.points[player] -= points;
+            },
+        );
+    }
+
+    impl GameData for CoinGame {
+        type Context = ();
+        type EffectType = dyn RevEffect<Self>;
+
+        fn next_decision(&self) -> Option<Box<dyn Decision<Self>>> {
+            if self.round >= self.rounds {
+                return None;
+            }
+            let mut dec = PlainDecision::new(self.player());
+            add_points(&mut dec, 2);
+            let weights = self.weights.to_vec();
+            dec.add_follow_up(move |_| {
+                let mut coin = PlainDecision::new(2);
+                add_points(&mut coin, 0);
+                add_points(&mut coin, 5);
+                ChanceDecision::new(coin, weights.clone())
+            });
+            Some(Box::new(dec))
+        }
+    }
+
+    struct RatePoints;
+
+    impl RateAndMap<CoinGame> for RatePoints {
+        fn apply_type_mapping(&self, _context: &()) -> DecisionType {
+            DecisionType::BottomLevel
+        }
+
+        fn rate_moves(&self, _: &mut Rater, _: &[()], _: &CoinGame, _: &[((), usize)]) {
+            unreachable!()
+        }
+
+        fn rate_game_state(&self, data: &CoinGame, _: &[((), usize)], player: usize) -> RatingType {
+            100 * (data.points[player] - data.points[1 - player])
+        }
+    }
+
+    #[test]
+    fn expectimax_test() {
+        let alg = Expectimax::new(1, RatePoints);
+        let engine = Engine::new(2, CoinGame::new([1, 1], 1));
+        assert_eq!(alg.run_all_ratings(&engine).unwrap(), [200.0, 250.0]);
+        assert_eq!(alg.run(&engine).unwrap(), (250.0, 1));
+
+        let engine = Engine::new(2, CoinGame::new([2, 1], 1));
+        let ratings = alg.run_all_ratings(&engine).unwrap();
+        assert_eq!(ratings[0], 200.0);
+        assert!((ratings[1] - 500.0 / 3.0).abs() < 1e-9);
+        assert_eq!(alg.run(&engine).unwrap().1, 0);
+
+        // the opponent gambles in the second round
+        let alg = Expectimax::new(2, RatePoints);
+        let engine = Engine::new(2, CoinGame::new([1, 1], 2));
+        assert_eq!(alg.run_all_ratings(&engine).unwrap(), [-50.0, 0.0]);
+    }
+
+    #[test]
+    fn expectimax_in_chain_test() {
+        let alg = Expectimax::new(1, RateAndMapZeroOne);
+        let mut engine = Engine::new_logging(2, ZeroOneGame::new(true, 2));
+        match engine.pull() {
+            GameState::PendingDecision(dec) => dec.select_option(0),
+            _ => panic!(),
+        }
+        assert_eq!(alg.run_all_ratings(&engine).unwrap(), [4.0, 0.0]);
+        assert_eq!(engine.pending_chain(), [0]);
+    }
+
+    #[test]
+    fn player_test() {
+        let players: Vec<Box<dyn Player<CoinGame>>> = vec![
+            Box::new(Expectimax::new(2, RatePoints)),
+            Box::new(ScriptedPlayer::selecting([0])),
+        ];
+        // the coin is tossed by the chance player
+        let mut runner = GameRunner::new(Engine::new(2, CoinGame::new([1, 1], 2)), players)
+            .with_chance_player(Box::new(ScriptedPlayer::selecting([1])));
+        runner.run().unwrap();
+        assert_eq!(runner.engine().data().points, [5, 2]);
+    }
+}
@@ -6,12 +6,15 @@ pub type RatingType = i32;
 
 mod algorithm;
 mod engine_stepper;
+mod expectimax;
 mod params;
 mod search_tree_state;
 
 pub mod rater;
+pub mod remote_client;
 
 pub use algorithm::*;
+pub use expectimax::*;
 pub use params::*;
 
 #[cfg(test)]
@@ -20,7 +23,10 @@ pub(crate) mod test {
         rater::{DecisionType, Rater},
         RateAndMap, RatingType,
     };
-    use hivetuilib::{plain_decision::PlainDecision, GameData, RevEffect};
+    use hivetuilib::{
+        plain_decision::PlainDecision, DecisionObject, GameData, RevEffect, SendDecision,
+        SendGameData,
+    };
 
     pub(crate) fn type_mapping(context: &ZeroOneContext) -> DecisionType {
         match context {
@@ -76,6 +82,61 @@ pub(crate) mod test {
         ZeroAnd,
         OneAnd,
     }
+    /// Adds the options of a decision of the lowest level, depending on its context.
+    fn add_options<D>(dec: &mut PlainDecision<ZeroOneGame, D>)
+    where
+        D: DecisionObject<ZeroOneGame> + ?Sized,
+    {
+        let apply_zero = |data: &mut ZeroOneGame| {
+            data.num_zeros += 1;
+            None
+        };
+        let undo_zero = |data: &mut ZeroOneGame| {
+            data.num_zeros -= 1;
+        };
+        let apply_one = |data: &mut ZeroOneGame| {
+            data.num_ones += 1;
+            None
+        };
+        let undo_one = |data: &mut ZeroOneGame| {
+            data.num_ones -= 1;
+        };
+        let apply_update = |data: &mut ZeroOneGame| {
+            data.update();
+            None
+        };
+        let update = |data: &mut ZeroOneGame| {
+            data.update();
+        };
+
+        match dec.context_ref() {
+            ZeroOneContext::Flat => {
+                dec.add_rev_effect(chain(apply_zero, apply_update), chain(undo_zero, update));
+                dec.add_rev_effect(chain(apply_one, apply_update), chain(undo_one, update));
+            }
+            ZeroOneContext::ZeroAnd => {
+                dec.add_rev_effect(
+                    chain(chain(apply_zero, apply_zero), apply_update),
+                    chain(chain(undo_zero, undo_zero), update),
+                );
+                dec.add_rev_effect(
+                    chain(chain(apply_zero, apply_one), apply_update),
+                    chain(chain(undo_one, undo_zero), update),
+                );
+            }
+            ZeroOneContext::OneAnd => {
+                dec.add_rev_effect(
+                    chain(chain(apply_one, apply_zero), apply_update),
+                    chain(chain(undo_one, undo_zero), update),
+                );
+                dec.add_rev_effect(
+                    chain(chain(apply_one, apply_one), apply_update),
+                    chain(chain(undo_one, undo_one), update),
+                );
+            }
+            ZeroOneContext::Base => unreachable!(),
+        }
+    }
 
     impl GameData for ZeroOneGame {
         type Context = ZeroOneContext;
@@ -87,59 +148,48 @@ pub(crate) mod test {
             }
 
             let player = self.player;
-            let apply_zero = |data: &mut ZeroOneGame| {
-                data.num_zeros += 1;
-                None
-            };
-            let undo_zero = |data: &mut ZeroOneGame| {
-                data.num_zeros -= 1;
-            };
-            let apply_one = |data: &mut ZeroOneGame| {
-                data.num_ones += 1;
-                None
-            };
-            let undo_one = |data: &mut ZeroOneGame| {
-                data.num_ones -= 1;
-            };
-            let apply_update = |data: &mut ZeroOneGame| {
-                data.update();
-                None
-            };
-            let update = |data: &mut ZeroOneGame| {
-                data.update();
+            let follow_up = move |context| {
+                move |_: &ZeroOneGame| {
+                    let mut dec = PlainDecision::with_context(player, context);
+                    add_options(&mut dec);
+                    dec
+                }
             };
-
             if self.use_high_level {
                 let mut dec = PlainDecision::with_context(player, ZeroOneContext::Base);
-                dec.add_follow_up(move |_| {
-                    let mut zero_dec = PlainDecision::with_context(player, ZeroOneContext::ZeroAnd);
-                    zero_dec.add_rev_effect(
-                        chain(chain(apply_zero, apply_zero), apply_update),
-                        chain(chain(undo_zero, undo_zero), update),
-                    );
-                    zero_dec.add_rev_effect(
-                        chain(chain(apply_zero, apply_one), apply_update),
-                        chain(chain(undo_one, undo_zero), update),
-                    );
-                    zero_dec
-                });
-                dec.add_follow_up(move |_| {
-                    let mut one_dec = PlainDecision::with_context(player, ZeroOneContext::OneAnd);
-                    one_dec.add_rev_effect(
-                        chain(chain(apply_one, apply_zero), apply_update),
-                        chain(chain(undo_one, undo_zero), update),
-                    );
-                    one_dec.add_rev_effect(
-                        chain(chain(apply_one, apply_one), apply_update),
-                        chain(chain(undo_one, undo_one), update),
-                    );
-                    one_dec
-                });
+                dec.add_follow_up(follow_up(ZeroOneContext::ZeroAnd));
+                dec.add_follow_up(follow_up(ZeroOneContext::OneAnd));
                 Some(Box::new(dec))
             } else {
                 let mut dec = PlainDecision::with_context(player, ZeroOneContext::Flat);
-                dec.add_rev_effect(chain(apply_zero, apply_update), chain(undo_zero, update));
-                dec.add_rev_effect(chain(apply_one, apply_update), chain(undo_one, update));
+                add_options(&mut dec);
+                Some(Box::new(dec))
+            }
+        }
+    }
+
+    impl SendGameData for ZeroOneGame {
+        fn next_send_decision(&self) -> Option<Box<dyn SendDecision<Self>>> {
+            if self.num_zeros + self.num_ones >= self.finished_at {
+                return None;
+            }
+
+            let player = self.player;
+            let follow_up = move |context| {
+                move |_: &ZeroOneGame| {
+                    let mut dec = PlainDecision::send_with_context(player, context);
+                    add_options(&mut dec);
+                    dec
+                }
+            };
+            if self.use_high_level {
+                let mut dec = PlainDecision::send_with_context(player, ZeroOneContext::Base);
+                dec.add_follow_up(follow_up(ZeroOneContext::ZeroAnd));
+                dec.add_follow_up(follow_up(ZeroOneContext::OneAnd));
+                Some(Box::new(dec))
+            } else {
+                let mut dec = PlainDecision::send_with_context(player, ZeroOneContext::Flat);
+                add_options(&mut dec);
                 Some(Box::new(dec))
             }
         }
@@ -363,12 +363,15 @@ where
 {
     let index = usize::try_from(index).unwrap();
     let (result, _) = translate_impl(engine, &type_mapping, index);
-    if let GameState::PendingDecision(dec) = engine.pull() {
-        if let Some(fu) = dec.into_follow_up_decision() {
-            fu.retract_all();
-        }
+    let result = result.expect(INTERNAL_ERROR);
+    // only retract the options selected here, the engine might start within a decision chain
+    if result.len() > 1 {
+        let fu = pull_decision(engine, INTERNAL_ERROR)
+            .into_follow_up_decision()
+            .expect(INTERNAL_ERROR);
+        assert!(fu.retract_n(result.len() - 1), "{}", INTERNAL_ERROR);
     }
-    result.expect(INTERNAL_ERROR).into_iter().rev().collect()
+    result.into_iter().rev().collect()
 }
 
 pub fn translate_impl<T: GameData, L: EventListener<T>, F>(
@@ -0,0 +1,112 @@
+use std::{
+    fmt::Debug,
+    io::{BufRead, Write},
+};
+
+use hivetuilib::{
+    engine::{
+        io::CompatibilityPolicy,
+        remote::{run_client, RemoteError},
+        runner::PlayerAction,
+    },
+    GameData, RevEffect,
+};
+
+use crate::{MinMaxAlgorithm, RateAndMap};
+
+/// Reference client for the remote player protocol, which answers each request
+/// with the best option according to the min-max algorithm.
+///
+/// Pass `stdin().lock()` and `stdout()` to run the client as a separate process.
+/// A failure of the algorithm is returned as `RemoteError::DecisionFailed`.
+pub fn run_min_max_client<T, R, RI, W, H, F>(
+    algorithm: &MinMaxAlgorithm<T, R>,
+    reader: RI,
+    writer: W,
+    expected_header: H,
+    version: [u32; 2],
+    compatibility_policy: CompatibilityPolicy,
+    parse_initial_state: F,
+) -> Result<(), RemoteError>
+where
+    T: GameData + Clone + Debug,
+    T::EffectType: RevEffect<T>,
+    R: RateAndMap<T>,
+    RI: BufRead,
+    W: Write,
+    H: AsRef<str>,
+    F: Fn(&[(String, String)]) -> Result<T, String>,
+{
+    run_client(
+        reader,
+        writer,
+        expected_header,
+        version,
+        compatibility_policy,
+        parse_initial_state,
+        |engine| {
+            let (_, index_list, _) = algorithm
+                .run(engine)
+                .map_err(|e| format!("min-max algorithm failed: {e:?}"))?;
+            let index = index_list
+                .first()
+                .ok_or("min-max algorithm found no option")?;
+            Ok(PlayerAction::Select(*index))
+        },
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use hivetuilib::engine::{
+        io::CompatibilityPolicy,
+        remote::{write_request, RemoteRequest},
+        Engine, GameState,
+    };
+
+    use crate::{
+        test::{RateAndMapZeroOne, ZeroOneGame},
+        MinMaxAlgorithm, Params, SlidingParams,
+    };
+
+    use super::run_min_max_client;
+
+    #[test]
+    fn client_test() {
+        let sliding = SlidingParams::with_defaults(2, 1, 4, 4, 4, 2, 4, 1);
+        let mut params = Params::new(2, sliding, 1);
+        params.first_cut_delay_depth = 1;
+        let alg = MinMaxAlgorithm::new(params, RateAndMapZeroOne);
+
+        let mut engine = Engine::new_logging(2, ZeroOneGame::new(true, 8));
+        match engine.pull() {
+            GameState::PendingDecision(dec) => dec.select_option(1),
+            _ => unreachable!(),
+        }
+        let request = RemoteRequest {
+            initial_state: vec![("length".to_string(), "8".to_string())],
+            num_players: 2,
+            log: engine.serialized_log(),
+            option_count: 2,
+            player: 0,
+            level_in_chain: 1,
+        };
+        let mut input = Vec::new();
+        write_request(&mut input, "ZeroOne", [1, 0], &request).unwrap();
+        input.extend_from_slice(b"finished\n");
+
+        let mut output = Vec::new();
+        run_min_max_client(
+            &alg,
+            input.as_slice(),
+            &mut output,
+            "ZeroOne",
+            [1, 0],
+            CompatibilityPolicy::MinorEqual,
+            |state| Ok(ZeroOneGame::new(true, state[0].1.parse().unwrap())),
+        )
+        .unwrap();
+        let (_, index_list, _) = alg.run(&engine).unwrap();
+        assert_eq!(output, format!("select {}\n", index_list[0]).as_bytes());
+    }
+}
@@ -0,0 +1,71 @@
+use std::marker::PhantomData;
+
+use crate::{Decision, GameData, OptionId, Outcome};
+
+/// A chance decision (e.g. a dice roll) with weighted options. The options
+/// are provided by the wrapped decision, the player is usually irrelevant.
+#[derive(Debug, Clone)]
+pub struct ChanceDecision<T: GameData, D: Decision<T>> {
+    decision: D,
+    weights: Vec<u32>,
+    _t: PhantomData<fn(&T)>,
+}
+
+impl<T: GameData, D: Decision<T>> ChanceDecision<T, D> {
+    pub fn new(decision: D, weights: Vec<u32>) -> Self {
+        assert_eq!(
+            decision.option_count(),
+            weights.len(),
+            "One weight per option required!"
+        );
+        assert!(
+            weights.iter().any(|&w| w > 0),
+            "At least one option with positive weight required!"
+        );
+        Self {
+            decision,
+            weights,
+            _t: PhantomData,
+        }
+    }
+
+    /// Chance decision where all options have the same probability.
+    pub fn uniform(decision: D) -> Self {
+        let weights = vec![1; decision.option_count()];
+        Self::new(decision, weights)
+    }
+
+    pub fn weights(&self) -> &[u32] {
+        &self.weights
+    }
+}
+
+impl<T: GameData, D: Decision<T>> Decision<T> for ChanceDecision<T, D> {
+    fn select_option(&self, data: &T, index: usize) -> Outcome<T> {
+        self.decision.select_option(data, index)
+    }
+
+    fn option_count(&self) -> usize {
+        self.decision.option_count()
+    }
+
+    fn player(&self) -> usize {
+        self.decision.player()
+    }
+
+    fn context(&self, data: &T) -> T::Context {
+        self.decision.context(data)
+    }
+
+    fn chance_weights(&self) -> Option<Vec<u32>> {
+        Some(self.weights.clone())
+    }
+
+    fn option_id(&self, data: &T, index: usize) -> Option<OptionId> {
+        self.decision.option_id(data, index)
+    }
+
+    fn option_index(&self, data: &T, id: OptionId) -> Option<usize> {
+        self.decision.option_index(data, id)
+    }
+}
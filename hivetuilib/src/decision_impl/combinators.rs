@@ -0,0 +1,455 @@
+use std::{marker::PhantomData, mem};
+
+use crate::{Decision, GameData, IndexableContext, OptionContext, OptionId, Outcome};
+
+type ContextElement<T> = <<T as GameData>::Context as IndexableContext>::ContextElement;
+
+/// Adapters to combine and restrict decisions, e.g. merging the options to place a piece
+/// with the options to move a piece.
+///
+/// The adapters that change the options require an `OptionContext` and keep its
+/// elements consistent with the options.
+pub trait DecisionExt<T: GameData>: Decision<T> {
+    /// Appends the options of the other decisions, which must belong to the same player.
+    /// Apart from the elements, the context is the context of this decision.
+    fn concat<I>(self: Box<Self>, others: I) -> Concat<T, Self>
+    where
+        I: IntoIterator<Item = Box<Self>>,
+        T::Context: OptionContext,
+    {
+        let mut decisions = vec![self];
+        decisions.extend(others);
+        Concat::new(decisions)
+    }
+
+    /// Restricts the decision to the options whose context element satisfies the predicate.
+    ///
+    /// The predicate is only evaluated for the given data, since the number of options
+    /// can't depend on the data (see `Decision::option_count`). The filter is thus only
+    /// valid for this data: with other data, e.g. after an effect was applied, the
+    /// remaining options are not filtered again.
+    fn filter<P>(self: Box<Self>, data: &T, predicate: P) -> Filter<T, Self>
+    where
+        P: Fn(&ContextElement<T>) -> bool,
+        T::Context: OptionContext,
+    {
+        let mut context = self.context(data);
+        let indices = context
+            .elements_mut()
+            .iter()
+            .enumerate()
+            .filter(|(_, element)| predicate(element))
+            .map(|(index, _)| index)
+            .collect();
+        Filter {
+            decision: self,
+            indices,
+            _t: PhantomData,
+        }
+    }
+
+    /// Maps each element of the context.
+    fn map_context<F>(self: Box<Self>, mapping: F) -> MapContext<T, Self, F>
+    where
+        F: Fn(&T, ContextElement<T>) -> ContextElement<T>,
+        T::Context: OptionContext,
+    {
+        MapContext {
+            decision: self,
+            mapping,
+            _t: PhantomData,
+        }
+    }
+
+    /// Nests the decision that is created by the function for the selected option,
+    /// see `FollowUp`.
+    fn follow_up<E, F>(self: Box<Self>, decision_fn: F) -> FollowUp<T, Self>
+    where
+        F: Fn(&T, usize) -> E + Clone + 'static,
+        E: Decision<T> + 'static,
+    {
+        let mut result = FollowUp::new(self);
+        result.add_follow_ups(decision_fn);
+        result
+    }
+}
+
+impl<T: GameData, D: Decision<T> + ?Sized> DecisionExt<T> for D {}
+
+/// The options of several decisions of the same player, see `DecisionExt::concat`.
+pub struct Concat<T: GameData, D: Decision<T> + ?Sized> {
+    decisions: Vec<Box<D>>,
+    _t: PhantomData<fn(&T)>,
+}
+
+impl<T: GameData, D: Decision<T> + ?Sized> Concat<T, D> {
+    pub fn new(decisions: Vec<Box<D>>) -> Self {
+        assert!(!decisions.is_empty(), "At least one decision required!");
+        assert!(
+            decisions
+                .iter()
+                .all(|d| d.player() == decisions[0].player()),
+            "All decisions must belong to the same player!"
+        );
+        Self {
+            decisions,
+            _t: PhantomData,
+        }
+    }
+
+    /// The decision of the option and the index within the decision.
+    fn locate(&self, index: usize) -> (&D, usize) {
+        let mut remaining = index;
+        for decision in self.decisions.iter() {
+            if remaining < decision.option_count() {
+                return (decision, remaining);
+            }
+            remaining -= decision.option_count();
+        }
+        let option_count: usize = self.decisions.iter().map(|d| d.option_count()).sum();
+        panic!("Invalid option: {index}. Only {option_count} options available.")
+    }
+}
+
+impl<T: GameData, D: Decision<T> + ?Sized> Decision<T> for Concat<T, D>
+where
+    T::Context: OptionContext,
+{
+    fn select_option(&self, data: &T, index: usize) -> Outcome<T> {
+        let (decision, index) = self.locate(index);
+        decision.select_option(data, index)
+    }
+
+    fn option_count(&self) -> usize {
+        self.decisions.iter().map(|d| d.option_count()).sum()
+    }
+
+    fn player(&self) -> usize {
+        self.decisions[0].player()
+    }
+
+    fn context(&self, data: &T) -> T::Context {
+        let mut context = self.decisions[0].context(data);
+        for decision in &self.decisions[1..] {
+            let mut other = decision.context(data);
+            context.elements_mut().append(other.elements_mut());
+        }
+        context
+    }
+
+    /// The concatenated weights if all decisions are chance decisions.
+    fn chance_weights(&self) -> Option<Vec<u32>> {
+        let weights = self
+            .decisions
+            .iter()
+            .map(|d| d.chance_weights())
+            .collect::<Option<Vec<_>>>()?;
+        Some(weights.concat())
+    }
+
+    fn option_id(&self, data: &T, index: usize) -> Option<OptionId> {
+        let (decision, index) = self.locate(index);
+        decision.option_id(data, index)
+    }
+}
+
+/// A subset of the options of a decision, see `DecisionExt::filter`.
+pub struct Filter<T: GameData, D: Decision<T> + ?Sized> {
+    decision: Box<D>,
+    /// indices of the remaining options in ascending order
+    indices: Vec<usize>,
+    _t: PhantomData<fn(&T)>,
+}
+
+impl<T: GameData, D: Decision<T> + ?Sized> Decision<T> for Filter<T, D>
+where
+    T::Context: OptionContext,
+{
+    fn select_option(&self, data: &T, index: usize) -> Outcome<T> {
+        let inner = self.indices.get(index).unwrap_or_else(|| {
+            panic!(
+                "Invalid option: {}. Only {} options available.",
+                index,
+                self.option_count()
+            )
+        });
+        self.decision.select_option(data, *inner)
+    }
+
+    fn option_count(&self) -> usize {
+        self.indices.len()
+    }
+
+    fn player(&self) -> usize {
+        self.decision.player()
+    }
+
+    fn context(&self, data: &T) -> T::Context {
+        let mut context = self.decision.context(data);
+        let elements = mem::take(context.elements_mut());
+        let mut indices = self.indices.iter().peekable();
+        context.elements_mut().extend(
+            elements
+                .into_iter()
+                .enumerate()
+                .filter(|(index, _)| indices.next_if(|&&i| i == *index).is_some())
+                .map(|(_, element)| element),
+        );
+        context
+    }
+
+    fn chance_weights(&self) -> Option<Vec<u32>> {
+        let weights = self.decision.chance_weights()?;
+        Some(self.indices.iter().map(|&index| weights[index]).collect())
+    }
+
+    fn option_id(&self, data: &T, index: usize) -> Option<OptionId> {
+        self.decision.option_id(data, self.indices[index])
+    }
+}
+
+/// A decision with mapped context elements, see `DecisionExt::map_context`.
+pub struct MapContext<T: GameData, D: Decision<T> + ?Sized, F> {
+    decision: Box<D>,
+    mapping: F,
+    _t: PhantomData<fn(&T)>,
+}
+
+impl<T: GameData, D: Decision<T> + ?Sized, F> Decision<T> for MapContext<T, D, F>
+where
+    T::Context: OptionContext,
+    F: Fn(&T, ContextElement<T>) -> ContextElement<T>,
+{
+    fn select_option(&self, data: &T, index: usize) -> Outcome<T> {
+        self.decision.select_option(data, index)
+    }
+
+    fn option_count(&self) -> usize {
+        self.decision.option_count()
+    }
+
+    fn player(&self) -> usize {
+        self.decision.player()
+    }
+
+    fn context(&self, data: &T) -> T::Context {
+        let mut context = self.decision.context(data);
+        let elements = mem::take(context.elements_mut());
+        context.elements_mut().extend(
+            elements
+                .into_iter()
+                .map(|element| (self.mapping)(data, element)),
+        );
+        context
+    }
+
+    fn chance_weights(&self) -> Option<Vec<u32>> {
+        self.decision.chance_weights()
+    }
+
+    fn option_id(&self, data: &T, index: usize) -> Option<OptionId> {
+        self.decision.option_id(data, index)
+    }
+}
+
+type FollowUpFn<T> = Box<dyn Fn(&T) -> Box<dyn Decision<T>>>;
+
+/// Nests decisions within the options of another decision, e.g. selecting the target
+/// field after selecting a piece. Options without a nested decision keep their outcome.
+pub struct FollowUp<T: GameData, D: Decision<T> + ?Sized> {
+    decision: Box<D>,
+    follow_ups: Vec<Option<FollowUpFn<T>>>,
+}
+
+impl<T: GameData, D: Decision<T> + ?Sized> FollowUp<T, D> {
+    pub fn new(decision: Box<D>) -> Self {
+        let follow_ups = (0..decision.option_count()).map(|_| None).collect();
+        Self {
+            decision,
+            follow_ups,
+        }
+    }
+
+    /// Replaces the outcome of the option with the decision created by the function.
+    pub fn add_follow_up<E, F>(&mut self, index: usize, decision_fn: F) -> &mut Self
+    where
+        F: Fn(&T) -> E + 'static,
+        E: Decision<T> + 'static,
+    {
+        self.follow_ups[index] = Some(Box::new(move |data| Box::new(decision_fn(data))));
+        self
+    }
+
+    /// Replaces the outcome of every option with the decision created by the function,
+    /// which receives the index of the option.
+    pub fn add_follow_ups<E, F>(&mut self, decision_fn: F) -> &mut Self
+    where
+        F: Fn(&T, usize) -> E + Clone + 'static,
+        E: Decision<T> + 'static,
+    {
+        for index in 0..self.follow_ups.len() {
+            let decision_fn = decision_fn.clone();
+            self.add_follow_up(index, move |data| decision_fn(data, index));
+        }
+        self
+    }
+}
+
+impl<T: GameData, D: Decision<T> + ?Sized> Decision<T> for FollowUp<T, D> {
+    fn select_option(&self, data: &T, index: usize) -> Outcome<T> {
+        match &self.follow_ups[index] {
+            Some(decision_fn) => Outcome::FollowUp(decision_fn(data)),
+            None => self.decision.select_option(data, index),
+        }
+    }
+
+    fn option_count(&self) -> usize {
+        self.decision.option_count()
+    }
+
+    fn player(&self) -> usize {
+        self.decision.player()
+    }
+
+    fn context(&self, data: &T) -> T::Context {
+        self.decision.context(data)
+    }
+
+    fn chance_weights(&self) -> Option<Vec<u32>> {
+        self.decision.chance_weights()
+    }
+
+    fn option_id(&self, data: &T, index: usize) -> Option<OptionId> {
+        self.decision.option_id(data, index)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        chance_decision::ChanceDecision,
+        engine::{Engine, GameState},
+        vec_decision::VecDecision,
+        Decision, Effect, GameData, IdentifiableOption, OptionId,
+    };
+
+    use super::{DecisionExt, FollowUp};
+
+    #[derive(Debug, Default)]
+    struct Collect {
+        values: Vec<u32>,
+    }
+
+    /// Decision that pushes one of the values, which are also the context elements.
+    fn pushing(values: &[u32]) -> VecDecision<Collect, u32> {
+        let mut dec = VecDecision::new(0);
+        for &value in values {
+            dec.add_effect(
+                move |data: &mut Collect| {
+                    data.values.push(value);
+                    None
+                },
+                value,
+            );
+        }
+        dec
+    }
+
+    impl IdentifiableOption for u32 {
+        fn option_id(&self) -> OptionId {
+            OptionId(u64::from(*self))
+        }
+    }
+
+    impl GameData for Collect {
+        type Context = Vec<u32>;
+        type EffectType = dyn Effect<Self>;
+
+        fn next_decision(&self) -> Option<Box<dyn Decision<Self>>> {
+            match self.values.len() {
+                0 => {
+                    let place: Box<dyn Decision<Self>> = Box::new(pushing(&[1, 2, 3]));
+                    let concat = place.concat([Box::new(pushing(&[10, 20])) as Box<_>]);
+                    let filtered = Box::new(concat).filter(self, |&value| value != 2);
+                    Some(Box::new(
+                        Box::new(filtered).map_context(|_, value| 100 * value),
+                    ))
+                }
+                1 => {
+                    let mut dec = FollowUp::new(Box::new(pushing(&[4, 5])));
+                    dec.add_follow_up(1, |_| pushing(&[6, 7]));
+                    Some(Box::new(dec))
+                }
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn combinator_test() {
+        let mut engine = Engine::new(1, Collect::default());
+        match engine.pull() {
+            GameState::PendingDecision(dec) => {
+                assert_eq!(dec.option_count(), 4);
+                assert_eq!(dec.context(), [100, 300, 1000, 2000]);
+                dec.apply_option(2);
+            }
+            _ => panic!("expected pending decision"),
+        }
+        assert_eq!(engine.data().values, [10]);
+
+        match engine.pull() {
+            GameState::PendingDecision(dec) => {
+                assert_eq!(dec.context(), [4, 5]);
+                dec.select_option(1);
+            }
+            _ => panic!("expected pending decision"),
+        }
+        match engine.pull() {
+            GameState::PendingDecision(dec) => {
+                assert_eq!(dec.level_in_chain(), 1);
+                assert_eq!(dec.context(), [6, 7]);
+                dec.apply_option(1);
+            }
+            _ => panic!("expected pending decision"),
+        }
+        assert_eq!(engine.data().values, [10, 7]);
+        assert!(engine.is_finished());
+    }
+
+    #[test]
+    #[should_panic(expected = "same player")]
+    fn concat_players_test() {
+        let place: Box<dyn Decision<Collect>> = Box::new(pushing(&[1, 2]));
+        let other: VecDecision<Collect, u32> = VecDecision::new(1);
+        place.concat([Box::new(other) as Box<_>]);
+    }
+
+    #[test]
+    fn option_id_and_weights_test() {
+        let data = Collect::default();
+        let chance = |values: &[u32], weights: Vec<u32>| {
+            let mut dec = pushing(values);
+            dec.identify_options();
+            Box::new(ChanceDecision::new(dec, weights)) as Box<dyn Decision<Collect>>
+        };
+        let concat = chance(&[1, 2, 3], vec![1, 2, 3]).concat([chance(&[10, 20], vec![4, 5])]);
+        assert_eq!(concat.chance_weights(), Some(vec![1, 2, 3, 4, 5]));
+        assert_eq!(concat.option_id(&data, 3), Some(OptionId(10)));
+        assert_eq!(concat.option_index(&data, OptionId(3)), Some(2));
+
+        let filtered = Box::new(concat).filter(&data, |&value| value % 2 == 0);
+        assert_eq!(filtered.context(&data), [2, 10, 20]);
+        assert_eq!(filtered.chance_weights(), Some(vec![2, 4, 5]));
+        assert_eq!(filtered.option_id(&data, 1), Some(OptionId(10)));
+        assert_eq!(filtered.option_index(&data, OptionId(20)), Some(2));
+        assert_eq!(filtered.option_index(&data, OptionId(1)), None);
+
+        // weights require chance decisions only, while ids are provided per option
+        let place: Box<dyn Decision<Collect>> = Box::new(pushing(&[1]));
+        let mixed = place.concat([chance(&[2], vec![1])]);
+        assert_eq!(mixed.chance_weights(), None);
+        assert_eq!(mixed.option_id(&data, 0), None);
+        assert_eq!(mixed.option_id(&data, 1), Some(OptionId(2)));
+    }
+}
@@ -1,19 +1,22 @@
 use std::marker::PhantomData;
 
 use crate::{
-    new_effect, new_rev_effect, vec_context::VecContext, Decision, Effect, GameData, Outcome,
-    RevEffect,
+    new_effect, new_rev_effect, vec_context::VecContext, Decision, DecisionObject, Effect,
+    GameData, IdentifiableOption, OptionId, Outcome, RevEffect, SendDecision, SendGameData,
+    SendOutcome,
 };
 
-pub trait MapToOutcome<T: GameData, C: Clone, I: Clone> {
-    fn apply_mapping(&self, data: &T, inner: &I, context: &C) -> Outcome<T>;
+/// Maps a context element to the outcome of the option, where `D` is the type
+/// of the follow-up decisions (see `DecisionObject`).
+pub trait MapToOutcome<T: GameData, C: Clone, I: Clone, D: ?Sized = dyn Decision<T>> {
+    fn apply_mapping(&self, data: &T, inner: &I, context: &C) -> Outcome<T, D>;
 }
 
-impl<F, T: GameData, C: Clone, I: Clone> MapToOutcome<T, C, I> for F
+impl<F, T: GameData, C: Clone, I: Clone, D: ?Sized> MapToOutcome<T, C, I, D> for F
 where
-    F: Fn(&T, &I, &C) -> Outcome<T>,
+    F: Fn(&T, &I, &C) -> Outcome<T, D>,
 {
-    fn apply_mapping(&self, data: &T, inner: &I, context: &C) -> Outcome<T> {
+    fn apply_mapping(&self, data: &T, inner: &I, context: &C) -> Outcome<T, D> {
         self(data, inner, context)
     }
 }
@@ -23,13 +26,13 @@ pub struct MapToEffect<F> {
     mapping: F,
 }
 
-impl<F, A, T, C: Clone, I: Clone> MapToOutcome<T, C, I> for MapToEffect<F>
+impl<F, A, T, C: Clone, I: Clone, D: ?Sized> MapToOutcome<T, C, I, D> for MapToEffect<F>
 where
     T: GameData<EffectType = dyn Effect<T>>,
     F: Fn(&I, &C) -> A,
     A: Fn(&mut T) -> Option<Box<dyn Effect<T>>> + Send + 'static,
 {
-    fn apply_mapping(&self, _data: &T, inner: &I, context: &C) -> Outcome<T> {
+    fn apply_mapping(&self, _data: &T, inner: &I, context: &C) -> Outcome<T, D> {
         Outcome::Effect(new_effect((self.mapping)(inner, context)))
     }
 }
@@ -39,14 +42,14 @@ pub struct MapToRevEffect<F> {
     mapping: F,
 }
 
-impl<F, A, U, T, C: Clone, I: Clone> MapToOutcome<T, C, I> for MapToRevEffect<F>
+impl<F, A, U, T, C: Clone, I: Clone, D: ?Sized> MapToOutcome<T, C, I, D> for MapToRevEffect<F>
 where
     T: GameData<EffectType = dyn RevEffect<T>>,
     F: Fn(&I, &C) -> (A, U),
     A: Fn(&mut T) -> Option<Box<dyn RevEffect<T>>> + Send + 'static,
     U: Fn(&mut T) + Send + 'static,
 {
-    fn apply_mapping(&self, _data: &T, inner: &I, context: &C) -> Outcome<T> {
+    fn apply_mapping(&self, _data: &T, inner: &I, context: &C) -> Outcome<T, D> {
         let (apply, undo) = (self.mapping)(inner, context);
         Outcome::Effect(new_rev_effect(apply, undo))
     }
@@ -67,45 +70,68 @@ where
     }
 }
 
+impl<F, D, T: SendGameData, C: Clone, I: Clone> MapToOutcome<T, C, I, dyn SendDecision<T>>
+    for MapToFollowUp<F>
+where
+    F: Fn(&T, &I, &C) -> D,
+    D: SendDecision<T> + 'static,
+{
+    fn apply_mapping(&self, data: &T, inner: &I, context: &C) -> SendOutcome<T> {
+        Outcome::FollowUp(Box::new((self.mapping)(data, inner, context)))
+    }
+}
+
 // TODO: Debug?
 /// A powerful and performant representation of a decision.
 /// This decision type uses a mapping function and a list of context
 /// elements to calculate the outcome lazily.
 #[derive(Debug, Clone)]
-struct MappedDecisionImpl<F, T: GameData, C: Clone, I: Clone = ()>
+struct MappedDecisionImpl<F, T: GameData, C: Clone, I: Clone = (), D: ?Sized = dyn Decision<T>>
 where
-    F: MapToOutcome<T, C, I>,
+    F: MapToOutcome<T, C, I, D>,
     T::Context: From<VecContext<C, I>>,
 {
     mapping: F,
     context: VecContext<C, I>,
     player: usize,
-    _t: PhantomData<T>,
+    option_ids: Option<fn(&C) -> OptionId>,
+    _t: PhantomData<fn(&T)>,
+    _d: PhantomData<fn() -> Box<D>>,
 }
 
-impl<T: GameData, F, C: Clone, I: Clone> MappedDecisionImpl<F, T, C, I>
+impl<T: GameData, F, C: Clone, I: Clone, D: ?Sized> MappedDecisionImpl<F, T, C, I, D>
 where
-    F: MapToOutcome<T, C, I>,
+    F: MapToOutcome<T, C, I, D>,
     T::Context: From<VecContext<C, I>>,
 {
-    fn new(mapping: F, context: VecContext<C, I>, player: usize) -> Self {
+    fn new(mapping: F, builder: MappedDecision<T, C, I>) -> Self
+    where
+        C: 'static,
+        I: 'static,
+    {
         Self {
             mapping,
-            context,
-            player,
+            context: builder.context,
+            player: builder.player,
+            option_ids: builder.option_ids,
             _t: PhantomData,
+            _d: PhantomData,
         }
     }
 }
 
-impl<T: GameData, F, C: Clone, I: Clone> Decision<T> for MappedDecisionImpl<F, T, C, I>
+impl<T: GameData, F, C: Clone, I: Clone, D> Decision<T> for MappedDecisionImpl<F, T, C, I, D>
 where
-    F: MapToOutcome<T, C, I>,
+    F: MapToOutcome<T, C, I, D>,
     T::Context: From<VecContext<C, I>>,
+    D: DecisionObject<T> + ?Sized,
 {
     fn select_option(&self, data: &T, index: usize) -> Outcome<T> {
-        self.mapping
-            .apply_mapping(data, self.context.inner(), &self.context[index])
+        D::into_outcome(self.mapping.apply_mapping(
+            data,
+            self.context.inner(),
+            &self.context[index],
+        ))
     }
 
     fn option_count(&self) -> usize {
@@ -119,6 +145,25 @@ where
     fn context(&self, _data: &T) -> T::Context {
         T::Context::from(self.context.clone())
     }
+
+    fn option_id(&self, _data: &T, index: usize) -> Option<OptionId> {
+        self.option_ids
+            .map(|option_id| option_id(&self.context[index]))
+    }
+}
+
+impl<T: SendGameData, F, C, I> SendDecision<T>
+    for MappedDecisionImpl<F, T, C, I, dyn SendDecision<T>>
+where
+    F: MapToOutcome<T, C, I, dyn SendDecision<T>> + Send,
+    T::Context: From<VecContext<C, I>>,
+    C: Clone + Send,
+    I: Clone + Send,
+{
+    fn select_send_option(&self, data: &T, index: usize) -> SendOutcome<T> {
+        self.mapping
+            .apply_mapping(data, self.context.inner(), &self.context[index])
+    }
 }
 
 // ----- builder pattern -----
@@ -129,6 +174,7 @@ where
 {
     context: VecContext<C, I>,
     player: usize,
+    option_ids: Option<fn(&C) -> OptionId>,
     _t: PhantomData<T>,
 }
 
@@ -156,6 +202,7 @@ where
         Self {
             context: VecContext::with_inner(inner),
             player,
+            option_ids: None,
             _t: PhantomData,
         }
     }
@@ -165,11 +212,20 @@ where
         self
     }
 
+    /// Identifies the options by their context elements, see `Decision::option_id`.
+    pub fn identify_options(&mut self) -> &mut Self
+    where
+        C: IdentifiableOption,
+    {
+        self.option_ids = Some(C::option_id);
+        self
+    }
+
     pub fn spawn_by_outcome<F>(self, mapping: F) -> Box<dyn Decision<T>>
     where
         F: Fn(&T, &I, &C) -> Outcome<T> + 'static,
     {
-        Box::new(MappedDecisionImpl::new(mapping, self.context, self.player))
+        Box::new(MappedDecisionImpl::<_, T, C, I>::new(mapping, self))
     }
 
     pub fn spawn_by_follow_up<F, D>(self, mapping: F) -> Box<dyn Decision<T>>
@@ -177,10 +233,9 @@ where
         F: Fn(&T, &I, &C) -> D + 'static,
         D: Decision<T> + 'static,
     {
-        Box::new(MappedDecisionImpl::new(
+        Box::new(MappedDecisionImpl::<_, T, C, I>::new(
             MapToFollowUp { mapping },
-            self.context,
-            self.player,
+            self,
         ))
     }
 
@@ -211,10 +266,9 @@ where
         F: Fn(&I, &C) -> A + 'static,
         A: Fn(&mut T) -> Option<Box<dyn Effect<T>>> + Send + 'static,
     {
-        Box::new(MappedDecisionImpl::new(
+        Box::new(MappedDecisionImpl::<_, T, C, I>::new(
             MapToEffect { mapping },
-            self.context,
-            self.player,
+            self,
         ))
     }
 }
@@ -230,10 +284,77 @@ where
         A: Fn(&mut T) -> Option<Box<dyn RevEffect<T>>> + Send + 'static,
         U: Fn(&mut T) + Send + 'static,
     {
-        Box::new(MappedDecisionImpl::new(
+        Box::new(MappedDecisionImpl::<_, T, C, I>::new(
+            MapToRevEffect { mapping },
+            self,
+        ))
+    }
+}
+
+/// Builders of `SendDecision`s (see `SendGameData`), which require the mapping
+/// and the context to be `Send`.
+impl<T: SendGameData, C, I> MappedDecision<T, C, I>
+where
+    T::Context: From<VecContext<C, I>>,
+    C: Clone + Send + 'static,
+    I: Clone + Send + 'static,
+{
+    pub fn spawn_send_by_outcome<F>(self, mapping: F) -> Box<dyn SendDecision<T>>
+    where
+        F: Fn(&T, &I, &C) -> SendOutcome<T> + Send + 'static,
+    {
+        Box::new(MappedDecisionImpl::<_, T, C, I, dyn SendDecision<T>>::new(
+            mapping, self,
+        ))
+    }
+
+    pub fn spawn_send_by_follow_up<F, D>(self, mapping: F) -> Box<dyn SendDecision<T>>
+    where
+        F: Fn(&T, &I, &C) -> D + Send + 'static,
+        D: SendDecision<T> + 'static,
+    {
+        Box::new(MappedDecisionImpl::<_, T, C, I, dyn SendDecision<T>>::new(
+            MapToFollowUp { mapping },
+            self,
+        ))
+    }
+}
+
+impl<T, C, I> MappedDecision<T, C, I>
+where
+    T: SendGameData<EffectType = dyn Effect<T>>,
+    T::Context: From<VecContext<C, I>>,
+    C: Clone + Send + 'static,
+    I: Clone + Send + 'static,
+{
+    pub fn spawn_send_by_effect<F, A>(self, mapping: F) -> Box<dyn SendDecision<T>>
+    where
+        F: Fn(&I, &C) -> A + Send + 'static,
+        A: Fn(&mut T) -> Option<Box<dyn Effect<T>>> + Send + 'static,
+    {
+        Box::new(MappedDecisionImpl::<_, T, C, I, dyn SendDecision<T>>::new(
+            MapToEffect { mapping },
+            self,
+        ))
+    }
+}
+
+impl<T, C, I> MappedDecision<T, C, I>
+where
+    T: SendGameData<EffectType = dyn RevEffect<T>>,
+    T::Context: From<VecContext<C, I>>,
+    C: Clone + Send + 'static,
+    I: Clone + Send + 'static,
+{
+    pub fn spawn_send_by_rev_effect<F, A, U>(self, mapping: F) -> Box<dyn SendDecision<T>>
+    where
+        F: Fn(&I, &C) -> (A, U) + Send + 'static,
+        A: Fn(&mut T) -> Option<Box<dyn RevEffect<T>>> + Send + 'static,
+        U: Fn(&mut T) + Send + 'static,
+    {
+        Box::new(MappedDecisionImpl::<_, T, C, I, dyn SendDecision<T>>::new(
             MapToRevEffect { mapping },
-            self.context,
-            self.player,
+            self,
         ))
     }
 }
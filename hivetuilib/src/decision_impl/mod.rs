@@ -1,3 +1,5 @@
+pub mod chance_decision;
+pub mod combinators;
 pub mod lazy_decision;
 pub mod mapped_decision;
 pub mod plain_decision;
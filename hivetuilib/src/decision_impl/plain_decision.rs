@@ -1,19 +1,26 @@
 use std::fmt::{self, Debug};
 
-use crate::{new_effect, new_rev_effect, Decision, Effect, GameData, Outcome, RevEffect};
+use crate::{
+    new_effect, new_rev_effect, Decision, DecisionObject, Effect, GameData, IdentifiableOption,
+    IndexableContext, OptionId, Outcome, OutcomeFn, RevEffect, SendDecision, SendGameData,
+    SendOutcome, SendOutcomeFn,
+};
 
 /// A simple representation of a decision consisting of the player,
 /// a list of effects and a cloneable context.
-pub struct PlainDecision<T: GameData>
+///
+/// With `D = dyn SendDecision<T>`, the decision is a `SendDecision` (see `SendGameData`).
+pub struct PlainDecision<T: GameData, D: DecisionObject<T> + ?Sized = dyn Decision<T>>
 where
     T::Context: Clone,
 {
-    options: Vec<Box<dyn Fn(&T) -> Outcome<T>>>,
+    options: Vec<Box<D::OutcomeFn>>,
     context: T::Context,
     player: usize,
+    option_ids: Option<fn(&T::Context, usize) -> OptionId>,
 }
 
-impl<T: GameData> Debug for PlainDecision<T>
+impl<T: GameData, D: DecisionObject<T> + ?Sized> Debug for PlainDecision<T, D>
 where
     T::Context: Clone + Debug,
 {
@@ -43,14 +50,10 @@ where
     T::Context: Clone,
 {
     pub fn with_context(player: usize, context: T::Context) -> Self {
-        Self {
-            options: Vec::new(),
-            context,
-            player,
-        }
+        Self::create(player, context)
     }
 
-    pub fn add_option(&mut self, outcome_fn: Box<dyn Fn(&T) -> Outcome<T>>) -> &mut Self {
+    pub fn add_option(&mut self, outcome_fn: OutcomeFn<T>) -> &mut Self {
         self.options.push(outcome_fn);
         self
     }
@@ -65,6 +68,54 @@ where
             Outcome::FollowUp(Box::new(new_decision))
         }))
     }
+}
+
+impl<T: SendGameData> PlainDecision<T, dyn SendDecision<T>>
+where
+    T::Context: Clone + Default,
+{
+    pub fn new_send(player: usize) -> Self {
+        Self::send_with_context(player, Default::default())
+    }
+}
+
+impl<T: SendGameData> PlainDecision<T, dyn SendDecision<T>>
+where
+    T::Context: Clone,
+{
+    pub fn send_with_context(player: usize, context: T::Context) -> Self {
+        Self::create(player, context)
+    }
+
+    pub fn add_option(&mut self, outcome_fn: SendOutcomeFn<T>) -> &mut Self {
+        self.options.push(outcome_fn);
+        self
+    }
+
+    pub fn add_follow_up<D, F>(&mut self, decision_fn: F) -> &mut Self
+    where
+        F: Fn(&T) -> D + Send + 'static,
+        D: SendDecision<T> + 'static,
+    {
+        self.add_option(Box::new(move |data| {
+            let new_decision = decision_fn(data);
+            Outcome::FollowUp(Box::new(new_decision))
+        }))
+    }
+}
+
+impl<T: GameData, D: DecisionObject<T> + ?Sized> PlainDecision<T, D>
+where
+    T::Context: Clone,
+{
+    fn create(player: usize, context: T::Context) -> Self {
+        Self {
+            options: Vec::new(),
+            context,
+            player,
+            option_ids: None,
+        }
+    }
 
     pub fn len(&self) -> usize {
         self.options.len()
@@ -82,9 +133,19 @@ where
     pub fn context_mut(&mut self) -> &mut T::Context {
         &mut self.context
     }
+
+    /// Identifies the options by the elements of the context, see `Decision::option_id`.
+    pub fn identify_options(&mut self) -> &mut Self
+    where
+        T::Context: IndexableContext,
+        <T::Context as IndexableContext>::ContextElement: IdentifiableOption,
+    {
+        self.option_ids = Some(|context, index| context.select(index).option_id());
+        self
+    }
 }
 
-impl<T> PlainDecision<T>
+impl<T, D: DecisionObject<T> + ?Sized> PlainDecision<T, D>
 where
     T: GameData<EffectType = dyn Effect<T>>,
     T::Context: Clone,
@@ -93,13 +154,13 @@ where
     where
         A: Fn(&mut T) -> Option<Box<dyn Effect<T>>> + Clone + Send + 'static,
     {
-        self.add_option(Box::new(move |_| {
-            Outcome::Effect(new_effect(apply.clone()))
-        }))
+        self.options
+            .push(D::effect_fn(move |_| new_effect(apply.clone())));
+        self
     }
 }
 
-impl<T> PlainDecision<T>
+impl<T, D: DecisionObject<T> + ?Sized> PlainDecision<T, D>
 where
     T: GameData<EffectType = dyn RevEffect<T>>,
     T::Context: Clone,
@@ -109,26 +170,36 @@ where
         A: Fn(&mut T) -> Option<Box<dyn RevEffect<T>>> + Clone + Send + 'static,
         U: Fn(&mut T) + Clone + Send + 'static,
     {
-        self.add_option(Box::new(move |_| {
-            Outcome::Effect(new_rev_effect(apply.clone(), undo.clone()))
-        }))
+        self.options.push(D::effect_fn(move |_| {
+            new_rev_effect(apply.clone(), undo.clone())
+        }));
+        self
     }
 }
 
-impl<T: GameData> Decision<T> for PlainDecision<T>
+impl<T: GameData, D: DecisionObject<T> + ?Sized> PlainDecision<T, D>
 where
     T::Context: Clone,
 {
-    fn select_option(&self, data: &T, index: usize) -> Outcome<T> {
+    fn outcome(&self, data: &T, index: usize) -> Outcome<T, D> {
         let outcome_fn = self.options.get(index).unwrap_or_else(|| {
             panic!(
                 "Invalid option: {}. Only {} options available.",
                 index,
-                self.option_count()
+                self.len()
             )
         });
         outcome_fn(data)
     }
+}
+
+impl<T: GameData, D: DecisionObject<T> + ?Sized> Decision<T> for PlainDecision<T, D>
+where
+    T::Context: Clone,
+{
+    fn select_option(&self, data: &T, index: usize) -> Outcome<T> {
+        D::into_outcome(self.outcome(data, index))
+    }
 
     fn option_count(&self) -> usize {
         self.len()
@@ -141,4 +212,18 @@ where
     fn context(&self, _data: &T) -> T::Context {
         self.context.clone()
     }
+
+    fn option_id(&self, _data: &T, index: usize) -> Option<OptionId> {
+        self.option_ids
+            .map(|option_id| option_id(&self.context, index))
+    }
+}
+
+impl<T: SendGameData> SendDecision<T> for PlainDecision<T, dyn SendDecision<T>>
+where
+    T::Context: Clone + Send,
+{
+    fn select_send_option(&self, data: &T, index: usize) -> SendOutcome<T> {
+        self.outcome(data, index)
+    }
 }
@@ -1,5 +1,7 @@
 use std::{iter::FromIterator, ops::Deref, slice::Iter};
 
+use crate::{IndexableContext, OptionContext};
+
 // TODO: we should be able to lift the Clone bounds with GATs
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct VecContext<C: Clone, I: Clone = ()> {
@@ -102,3 +104,17 @@ impl<C: Clone, I: Clone> AsRef<[C]> for VecContext<C, I> {
         &self.data
     }
 }
+
+impl<C: Clone, I: Clone> IndexableContext for VecContext<C, I> {
+    type ContextElement = C;
+
+    fn select(&self, index: usize) -> C {
+        self.data[index].clone()
+    }
+}
+
+impl<C: Clone, I: Clone> OptionContext for VecContext<C, I> {
+    fn elements_mut(&mut self) -> &mut Vec<C> {
+        &mut self.data
+    }
+}
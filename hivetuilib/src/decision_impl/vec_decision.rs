@@ -1,24 +1,33 @@
 use std::fmt::{self, Debug};
 
 use crate::{
-    new_effect, new_rev_effect, vec_context::VecContext, Decision, Effect, GameData, Outcome,
-    RevEffect,
+    new_effect, new_rev_effect, vec_context::VecContext, Decision, DecisionObject, Effect,
+    GameData, IdentifiableOption, OptionId, Outcome, OutcomeFn, RevEffect, SendDecision,
+    SendGameData, SendOutcome, SendOutcomeFn,
 };
 
 /// Represents a decision with a player, a list of options
 /// and a corresponding `VecContext`.
-pub struct VecDecision<T: GameData, C: Clone, I: Clone = ()>
+///
+/// With `D = dyn SendDecision<T>`, the decision is a `SendDecision` (see `SendGameData`).
+pub struct VecDecision<T, C, I = (), D = dyn Decision<T>>
 where
+    T: GameData,
     T::Context: From<VecContext<C, I>>,
+    C: Clone,
+    I: Clone,
+    D: DecisionObject<T> + ?Sized,
 {
-    options: Vec<Box<dyn Fn(&T) -> Outcome<T>>>,
+    options: Vec<Box<D::OutcomeFn>>,
     context: VecContext<C, I>,
     player: usize,
+    option_ids: Option<fn(&C) -> OptionId>,
 }
 
-impl<T: GameData, C: Clone, I: Clone> Debug for VecDecision<T, C, I>
+impl<T: GameData, C: Clone, I: Clone, D> Debug for VecDecision<T, C, I, D>
 where
     T::Context: From<VecContext<C, I>>,
+    D: DecisionObject<T> + ?Sized,
     C: Debug,
     I: Debug,
 {
@@ -48,21 +57,11 @@ where
     T::Context: From<VecContext<C, I>>,
 {
     pub fn with_inner(player: usize, inner: I) -> Self {
-        Self {
-            options: Vec::new(),
-            context: VecContext::with_inner(inner),
-            player,
-        }
+        Self::create(player, inner)
     }
 
-    pub fn add_option(
-        &mut self,
-        outcome_fn: Box<dyn Fn(&T) -> Outcome<T>>,
-        context: C,
-    ) -> &mut Self {
-        self.options.push(outcome_fn);
-        self.context.push(context);
-        self
+    pub fn add_option(&mut self, outcome_fn: OutcomeFn<T>, context: C) -> &mut Self {
+        self.push_option(outcome_fn, context)
     }
 
     pub fn add_follow_up<D, F>(&mut self, decision_fn: F, context: C) -> &mut Self
@@ -78,6 +77,76 @@ where
             context,
         )
     }
+}
+
+impl<T: SendGameData, C: Clone, I: Clone> VecDecision<T, C, I, dyn SendDecision<T>>
+where
+    T::Context: From<VecContext<C, I>>,
+    I: Default,
+{
+    pub fn new_send(player: usize) -> Self {
+        Self::send_with_inner(player, Default::default())
+    }
+}
+
+impl<T: SendGameData, C: Clone, I: Clone> VecDecision<T, C, I, dyn SendDecision<T>>
+where
+    T::Context: From<VecContext<C, I>>,
+{
+    pub fn send_with_inner(player: usize, inner: I) -> Self {
+        Self::create(player, inner)
+    }
+
+    pub fn add_option(&mut self, outcome_fn: SendOutcomeFn<T>, context: C) -> &mut Self {
+        self.push_option(outcome_fn, context)
+    }
+
+    pub fn add_follow_up<D, F>(&mut self, decision_fn: F, context: C) -> &mut Self
+    where
+        F: Fn(&T) -> D + Send + 'static,
+        D: SendDecision<T> + 'static,
+    {
+        self.add_option(
+            Box::new(move |data| {
+                let new_decision = decision_fn(data);
+                Outcome::FollowUp(Box::new(new_decision))
+            }),
+            context,
+        )
+    }
+}
+
+impl<T: GameData, C: Clone, I: Clone, D> VecDecision<T, C, I, D>
+where
+    T::Context: From<VecContext<C, I>>,
+    D: DecisionObject<T> + ?Sized,
+{
+    fn create(player: usize, inner: I) -> Self {
+        Self {
+            options: Vec::new(),
+            context: VecContext::with_inner(inner),
+            player,
+            option_ids: None,
+        }
+    }
+
+    fn push_option(&mut self, outcome_fn: Box<D::OutcomeFn>, context: C) -> &mut Self {
+        self.options.push(outcome_fn);
+        self.context.push(context);
+        self
+    }
+
+    fn outcome(&self, data: &T, index: usize) -> Outcome<T, D> {
+        debug_assert!(self.options.len() == self.context.len());
+        let outcome_fn = self.options.get(index).unwrap_or_else(|| {
+            panic!(
+                "Invalid option: {}. Only {} options available.",
+                index,
+                self.len()
+            )
+        });
+        outcome_fn(data)
+    }
 
     pub fn len(&self) -> usize {
         self.options.len()
@@ -91,55 +160,56 @@ where
     pub fn context_ref(&self) -> &VecContext<C, I> {
         &self.context
     }
+
+    /// Identifies the options by their context elements, see `Decision::option_id`.
+    pub fn identify_options(&mut self) -> &mut Self
+    where
+        C: IdentifiableOption,
+    {
+        self.option_ids = Some(C::option_id);
+        self
+    }
 }
 
-impl<T, C: Clone, I: Clone> VecDecision<T, C, I>
+impl<T, C: Clone, I: Clone, D> VecDecision<T, C, I, D>
 where
     T: GameData<EffectType = dyn Effect<T>>,
     T::Context: From<VecContext<C, I>>,
+    D: DecisionObject<T> + ?Sized,
 {
     pub fn add_effect<A>(&mut self, apply: A, context: C) -> &mut Self
     where
         A: Fn(&mut T) -> Option<Box<dyn Effect<T>>> + Clone + Send + 'static,
     {
-        self.add_option(
-            Box::new(move |_| Outcome::Effect(new_effect(apply.clone()))),
-            context,
-        )
+        self.push_option(D::effect_fn(move |_| new_effect(apply.clone())), context)
     }
 }
 
-impl<T, C: Clone, I: Clone> VecDecision<T, C, I>
+impl<T, C: Clone, I: Clone, D> VecDecision<T, C, I, D>
 where
     T: GameData<EffectType = dyn RevEffect<T>>,
     T::Context: From<VecContext<C, I>>,
+    D: DecisionObject<T> + ?Sized,
 {
     pub fn add_rev_effect<A, U>(&mut self, apply: A, undo: U, context: C) -> &mut Self
     where
         A: Fn(&mut T) -> Option<Box<dyn RevEffect<T>>> + Clone + Send + 'static,
         U: Fn(&mut T) + Clone + Send + 'static,
     {
-        self.add_option(
-            Box::new(move |_| Outcome::Effect(new_rev_effect(apply.clone(), undo.clone()))),
+        self.push_option(
+            D::effect_fn(move |_| new_rev_effect(apply.clone(), undo.clone())),
             context,
         )
     }
 }
 
-impl<T: GameData, C: Clone, I: Clone> Decision<T> for VecDecision<T, C, I>
+impl<T: GameData, C: Clone, I: Clone, D> Decision<T> for VecDecision<T, C, I, D>
 where
     T::Context: From<VecContext<C, I>>,
+    D: DecisionObject<T> + ?Sized,
 {
     fn select_option(&self, data: &T, index: usize) -> Outcome<T> {
-        debug_assert!(self.options.len() == self.context.len());
-        let outcome_fn = self.options.get(index).unwrap_or_else(|| {
-            panic!(
-                "Invalid option: {}. Only {} options available.",
-                index,
-                self.option_count()
-            )
-        });
-        outcome_fn(data)
+        D::into_outcome(self.outcome(data, index))
     }
 
     fn option_count(&self) -> usize {
@@ -154,4 +224,20 @@ where
         debug_assert!(self.options.len() == self.context.len());
         T::Context::from(self.context.clone())
     }
+
+    fn option_id(&self, _data: &T, index: usize) -> Option<OptionId> {
+        self.option_ids
+            .map(|option_id| option_id(&self.context[index]))
+    }
+}
+
+impl<T: SendGameData, C, I> SendDecision<T> for VecDecision<T, C, I, dyn SendDecision<T>>
+where
+    T::Context: From<VecContext<C, I>>,
+    C: Clone + Send,
+    I: Clone + Send,
+{
+    fn select_send_option(&self, data: &T, index: usize) -> SendOutcome<T> {
+        self.outcome(data, index)
+    }
 }
@@ -1,8 +1,8 @@
 use std::fmt::{self, Debug};
 
-use crate::GameData;
+use crate::{GameData, GameResult};
 
-use super::{Engine, InternalState, PDecisionState, PEffectState, INTERNAL_ERROR};
+use super::{Engine, EngineError, InternalState, PDecisionState, PEffectState, INTERNAL_ERROR};
 
 /// Abstracted engine trait (with erased type parameter).
 pub trait AbstractEngine {
@@ -19,13 +19,15 @@ pub enum AbstractState<'a> {
 impl<T: GameData> AbstractEngine for Engine<T> {
     fn pull_abstract(&mut self) -> AbstractState<'_> {
         match &self.state {
-            InternalState::PEffect(_) => {
+            InternalState::PEffect(..) => {
                 AbstractState::PendingEffect(AbstractPendingEffect { state: self })
             }
             InternalState::PDecision(_, _) => {
                 AbstractState::PendingDecision(AbstractPendingDecision { state: self })
             }
-            InternalState::Finished => AbstractState::Finished(AbstractFinished {}),
+            InternalState::Finished => AbstractState::Finished(AbstractFinished {
+                result: self.result(),
+            }),
             InternalState::Invalid => panic!("Internal error - invalid state"),
         }
     }
@@ -63,6 +65,13 @@ impl<'a> AbstractPendingDecision<'a> {
         self.state.select_option(index)
     }
 
+    /// Same as `select_option`, but returns an error for an invalid index.
+    pub fn try_select_option(self, index: usize) -> Result<(), EngineError> {
+        self.state.check_option(index)?;
+        self.state.select_option(index);
+        Ok(())
+    }
+
     pub fn option_count(&self) -> usize {
         self.state.option_count()
     }
@@ -104,6 +113,13 @@ impl<'a> AbstractFollowUpDecision<'a> {
         self.state.select_option(index)
     }
 
+    /// Same as `select_option`, but returns an error for an invalid index.
+    pub fn try_select_option(self, index: usize) -> Result<(), EngineError> {
+        self.state.check_option(index)?;
+        self.state.select_option(index);
+        Ok(())
+    }
+
     pub fn option_count(&self) -> usize {
         self.state.option_count()
     }
@@ -125,6 +141,11 @@ impl<'a> AbstractFollowUpDecision<'a> {
         assert!(self.state.retract_n(1), "{}", INTERNAL_ERROR)
     }
 
+    /// Same as `retract`, but returns an error instead of panicking.
+    pub fn try_retract(self) -> Result<(), EngineError> {
+        self.state.try_retract_n(1)
+    }
+
     /// Retracts from n subdecisions and returns whether the retraction was successful.
     ///
     /// This is the case if and only if n <= #{pending decisions}.
@@ -141,5 +162,13 @@ impl<'a> AbstractFollowUpDecision<'a> {
 
 #[derive(Debug)]
 pub struct AbstractFinished {
-    // TODO additional information?
+    result: Option<GameResult>,
+}
+
+impl AbstractFinished {
+    /// Outcome of the game: the termination by a meta action or otherwise the result
+    /// reported by the game data (see `Engine::result`).
+    pub fn result(&self) -> Option<&GameResult> {
+        self.result.as_ref()
+    }
 }
@@ -0,0 +1,308 @@
+//! Random resolution of chance decisions, see `ChanceData`.
+
+use crate::{ChanceData, DecisionObject};
+
+use super::{
+    runner::{Player, PlayerAction},
+    Engine, EventListener, PDecisionState, PendingDecision,
+};
+
+/// Randomly chooses an option according to the weights. The choice is determined by the
+/// seed, the state hash, the number of completed decisions (see `Engine::decision_count`)
+/// and the options already selected in the pending decision chain.
+pub fn chance_index<T: ChanceData>(
+    data: &T,
+    decision_count: usize,
+    chain: &[usize],
+    weights: &[u32],
+) -> usize {
+    let total = weights.iter().map(|&w| u64::from(w)).sum::<u64>();
+    assert!(
+        total > 0,
+        "At least one option with positive weight required!"
+    );
+    let mut state = mix(data.chance_seed());
+    state = mix(state ^ data.state_hash());
+    state = mix(state ^ decision_count as u64);
+    for &index in chain {
+        state = mix(state ^ index as u64);
+    }
+    let mut value = state % total;
+    for (index, &weight) in weights.iter().enumerate() {
+        if value < u64::from(weight) {
+            return index;
+        }
+        value -= u64::from(weight);
+    }
+    unreachable!()
+}
+
+/// SplitMix64 finalizer.
+fn mix(input: u64) -> u64 {
+    let mut z = input.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+impl<T: ChanceData, L: EventListener<T>, D: DecisionObject<T> + ?Sized> Engine<T, L, D> {
+    /// Selects an option of a pending chance decision randomly and returns its index.
+    /// The selection is reported to the listener like any other decision.
+    ///
+    /// Returns `None` if no chance decision is pending or the game is paused.
+    pub fn resolve_chance(&mut self) -> Option<usize> {
+        if self.meta.is_paused() {
+            return None;
+        }
+        let weights = self.get_decision()?.chance_weights()?;
+        let index = chance_index(
+            &self.data,
+            self.decision_count,
+            &self.pending_chain(),
+            &weights,
+        );
+        self.select_option(index);
+        Some(index)
+    }
+}
+
+/// Player that resolves chance decisions ("nature"), see `GameRunner::with_chance_player`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChancePlayer;
+
+impl<T, L, D> Player<T, L, D> for ChancePlayer
+where
+    T: ChanceData,
+    L: EventListener<T>,
+    D: DecisionObject<T> + ?Sized,
+{
+    fn decide(&mut self, decision: &PendingDecision<T, L, D>) -> Result<PlayerAction, String> {
+        let weights = decision
+            .chance_weights()
+            .ok_or_else(|| "not a chance decision".to_string())?;
+        let engine = decision.engine();
+        Ok(PlayerAction::Select(chance_index(
+            engine.data(),
+            engine.decision_count(),
+            &engine.pending_chain(),
+            &weights,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        chance_decision::ChanceDecision,
+        engine::{
+            io::restore_game_state,
+            meta::MetaAction,
+            runner::{GameRunner, Player, PlayerAction},
+            Engine, EventListener, GameState, LoggingEngine, NotListening, PendingDecision,
+        },
+        plain_decision::PlainDecision,
+        ChanceData, Decision, GameData, RevEffect, StateHash,
+    };
+
+    use super::{chance_index, ChancePlayer};
+
+    /// The chance decisions don't belong to a player of the game.
+    const NATURE: usize = 1;
+
+    /// Player 0 either adds one or rolls a die with the values 1 to 3, where 2 is impossible.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct DiceGame {
+        seed: u64,
+        sum: u32,
+        history: Vec<u32>,
+        rolling: bool,
+    }
+
+    impl DiceGame {
+        fn new(seed: u64) -> Self {
+            Self {
+                seed,
+                sum: 0,
+                history: Vec::new(),
+                rolling: false,
+            }
+        }
+    }
+
+    impl StateHash for DiceGame {
+        fn state_hash(&self) -> u64 {
+            self.history
+                .iter()
+                .fold(u64::from(self.rolling), |hash, &val| 31 * hash + val as u64)
+        }
+    }
+
+    impl ChanceData for DiceGame {
+        fn chance_seed(&self) -> u64 {
+            self.seed
+        }
+    }
+
+    impl GameData for DiceGame {
+        type Context = ();
+        type EffectType = dyn RevEffect<Self>;
+
+        fn next_decision(&self) -> Option<Box<dyn Decision<Self>>> {
+            if self.sum >= 20 {
+                return None;
+            }
+            if self.rolling {
+                let mut dec = PlainDecision::new(NATURE);
+                for val in 1..=3 {
+                    dec.add_rev_effect(
+                        move |data: &mut DiceGame| {
+                            data.sum += val;
+                            data.history.push(val);
+                            data.rolling = false;
+                            None
+                        },
+                        move |data: &mut DiceGame| {
+                            data.sum -= val;
+                            data.history.pop();
+                            data.rolling = true;
+                        },
+                    );
+                }
+                return Some(Box::new(ChanceDecision::new(dec, vec![1, 0, 3])));
+            }
+            let mut dec = PlainDecision::new(0);
+            dec.add_rev_effect(
+                |data: &mut DiceGame| {
+                    data.rolling = true;
+                    None
+                },
+                |data: &mut DiceGame| data.rolling = false,
+            );
+            dec.add_rev_effect(
+                |data: &mut DiceGame| {
+                    data.sum += 1;
+                    data.history.push(1);
+                    None
+                },
+                |data: &mut DiceGame| {
+                    data.sum -= 1;
+                    data.history.pop();
+                },
+            );
+            Some(Box::new(dec))
+        }
+    }
+
+    /// Always rolls and resolves the chance decisions until the game is finished.
+    fn play_out<L: EventListener<DiceGame>>(engine: &mut Engine<DiceGame, L>) {
+        loop {
+            match engine.pull() {
+                GameState::PendingEffect(effect) => effect.all_effects(),
+                GameState::PendingDecision(dec) => {
+                    if dec.chance_weights().is_none() {
+                        dec.select_option(0);
+                    } else {
+                        engine.resolve_chance().unwrap();
+                    }
+                }
+                GameState::Finished(_) => break,
+            }
+        }
+    }
+
+    #[test]
+    fn chance_index_test() {
+        let data = DiceGame::new(42);
+        let index = chance_index(&data, 0, &[], &[1, 0, 3]);
+        assert_eq!(chance_index(&data, 0, &[], &[1, 0, 3]), index);
+        assert_eq!(chance_index(&data, 0, &[], &[0, 5, 0]), 1);
+        let mut counts = [0; 3];
+        for seed in 0..400 {
+            counts[chance_index(&DiceGame::new(seed), 0, &[], &[1, 0, 3])] += 1;
+        }
+        assert_eq!(counts[1], 0);
+        assert!(counts[0] > 50 && counts[0] < 150, "{counts:?}");
+
+        // a repeated state does not repeat the random choice of an earlier decision
+        let first = chance_index(&data, 0, &[], &[1, 1]);
+        assert!((1..20).any(|count| chance_index(&data, count, &[], &[1, 1]) != first));
+    }
+
+    #[test]
+    fn resolve_test() {
+        let mut engine = Engine::new(1, DiceGame::new(7));
+        assert_eq!(engine.resolve_chance(), None);
+        play_out(&mut engine);
+        let history = engine.data().history.clone();
+        assert!(!history.contains(&2));
+
+        let mut other = Engine::new(1, DiceGame::new(7));
+        play_out(&mut other);
+        assert_eq!(other.data().history, history);
+        let differs = (0..10).any(|seed| {
+            let mut other = Engine::new(1, DiceGame::new(seed));
+            play_out(&mut other);
+            other.data().history != history
+        });
+        assert!(differs);
+    }
+
+    #[test]
+    fn paused_test() {
+        let mut engine = Engine::new(1, DiceGame::new(7));
+        match engine.pull() {
+            GameState::PendingDecision(dec) => {
+                dec.apply_option(0);
+            }
+            _ => panic!("expected pending decision"),
+        }
+        engine.apply_meta_action(MetaAction::Pause(0)).unwrap();
+        assert_eq!(engine.resolve_chance(), None);
+        engine.apply_meta_action(MetaAction::Resume(0)).unwrap();
+        assert!(engine.resolve_chance().is_some());
+    }
+
+    #[test]
+    fn undo_and_replay_test() {
+        let mut engine = LoggingEngine::new_logging(1, DiceGame::new(3));
+        play_out(&mut engine);
+        let data = engine.data().clone();
+        let count = engine.decision_count();
+        assert!(engine.undo_last_decision());
+        assert!(engine.undo_last_decision());
+        assert_eq!(engine.decision_count(), count - 2);
+        assert_ne!(engine.data(), &data);
+        play_out(&mut engine);
+        assert_eq!(engine.data(), &data);
+
+        let restored =
+            restore_game_state(1, || Ok(DiceGame::new(3)), engine.serialized_log()).unwrap();
+        assert_eq!(restored.data(), &data);
+    }
+
+    fn always_roll(_: &PendingDecision<DiceGame>) -> Result<PlayerAction, String> {
+        Ok(PlayerAction::Select(0))
+    }
+
+    #[test]
+    fn chance_player_test() {
+        let mut expected = Engine::new(1, DiceGame::new(11));
+        play_out(&mut expected);
+
+        let players: Vec<Box<dyn Player<DiceGame, NotListening>>> = vec![Box::new(always_roll)];
+        let mut runner = GameRunner::new(Engine::new(1, DiceGame::new(11)), players)
+            .with_chance_player(Box::new(ChancePlayer));
+        runner.run().unwrap();
+        assert_eq!(runner.engine().data(), expected.data());
+
+        // the chance decision has no player without a chance player
+        let players: Vec<Box<dyn Player<DiceGame, NotListening>>> = vec![Box::new(always_roll)];
+        let mut runner = GameRunner::new(Engine::new(1, DiceGame::new(11)), players);
+        assert!(runner.step().unwrap());
+        assert!(runner.step().is_err());
+
+        let players: Vec<Box<dyn Player<DiceGame, NotListening>>> = vec![Box::new(ChancePlayer)];
+        let mut runner = GameRunner::new(Engine::new(1, DiceGame::new(11)), players);
+        assert!(runner.step().is_err());
+    }
+}
@@ -0,0 +1,400 @@
+//! Chess-style game clocks, driven by the events of the engine.
+//!
+//! The clock of a player runs from the moment the previous decision was completed until
+//! the effects of the player's decision are completed. If an option of another player
+//! is selected in between (e.g. a follow-up decision of an opponent), the time until
+//! then is charged to the previous player and the clock switches.
+
+use std::time::{Duration, Instant};
+
+use crate::{GameData, GameResult};
+
+use super::{io::SerializedClock, EventListener, INTERNAL_ERROR};
+
+/// Source of the current time, which allows to control the time in tests.
+pub trait TimeSource {
+    /// Time since an arbitrary, but fixed point.
+    fn now(&self) -> Duration;
+}
+
+/// Measures the time with `Instant`.
+#[derive(Debug, Clone, Copy)]
+pub struct RealTime {
+    start: Instant,
+}
+
+impl Default for RealTime {
+    fn default() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl TimeSource for RealTime {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeControl {
+    /// Initial time of each player.
+    pub base: Duration,
+    /// Time added after each move.
+    pub increment: Duration,
+    /// Maximum time of a single move.
+    pub move_limit: Option<Duration>,
+}
+
+impl TimeControl {
+    pub fn new(base: Duration, increment: Duration) -> Self {
+        Self {
+            base,
+            increment,
+            move_limit: None,
+        }
+    }
+
+    pub fn with_move_limit(mut self, move_limit: Duration) -> Self {
+        self.move_limit = Some(move_limit);
+        self
+    }
+}
+
+/// Tracks the remaining time of each player, where the player of a move is determined by
+/// `Decision::player()`. Must be added as listener to the engine, e.g. as second member of a
+/// tuple listener or as observer of a `ListenerChain`.
+///
+/// Undoing a decision restores the remaining times from before the decision and restarts
+/// the clock. A redone decision doesn't consume any time, since the redo stack might belong
+/// to another variation than the undone decision.
+#[derive(Debug, Clone)]
+pub struct GameClock<S: TimeSource = RealTime> {
+    control: TimeControl,
+    time: S,
+    remaining: Vec<Duration>,
+    /// remaining times and timeout after each completed decision, starting with the initial
+    /// (or restored) state
+    history: Vec<(Vec<Duration>, Option<usize>)>,
+    /// player whose clock is running, if already known
+    running: Option<usize>,
+    /// start of the current period
+    since: Duration,
+    timeout: Option<usize>,
+}
+
+impl GameClock {
+    pub fn new(num_players: usize, control: TimeControl) -> Self {
+        Self::with_time_source(num_players, control, RealTime::default())
+    }
+}
+
+impl<S: TimeSource> GameClock<S> {
+    pub fn with_time_source(num_players: usize, control: TimeControl, time: S) -> Self {
+        let since = time.now();
+        let remaining = vec![control.base; num_players];
+        Self {
+            control,
+            time,
+            history: vec![(remaining.clone(), None)],
+            remaining,
+            running: None,
+            since,
+            timeout: None,
+        }
+    }
+
+    pub fn control(&self) -> TimeControl {
+        self.control
+    }
+
+    /// Player whose clock is running. Before the first option of a decision is selected,
+    /// this is the player of the pending decision.
+    pub fn running_player(&self) -> Option<usize> {
+        self.running
+    }
+
+    /// Remaining time of the player, including the running time of the current move.
+    pub fn remaining(&self, player: usize) -> Duration {
+        let remaining = self.remaining[player];
+        if self.running == Some(player) {
+            remaining.saturating_sub(self.elapsed())
+        } else {
+            remaining
+        }
+    }
+
+    pub fn remaining_times(&self) -> Vec<Duration> {
+        (0..self.remaining.len())
+            .map(|player| self.remaining(player))
+            .collect()
+    }
+
+    /// The first player who exceeded the time, if any.
+    pub fn timeout(&self) -> Option<usize> {
+        self.timeout
+    }
+
+    /// Flags a timeout if the running player exceeded the time.
+    /// Returns the first player who exceeded the time, if any.
+    pub fn check_timeout(&mut self) -> Option<usize> {
+        if let Some(player) = self.running {
+            if self.is_exceeded(player, self.elapsed()) {
+                self.timeout.get_or_insert(player);
+            }
+        }
+        self.timeout
+    }
+
+    /// Remaining times in milliseconds, to be saved next to the log
+    /// (see `io::save_game_with_clock`).
+    pub fn serialized(&self) -> SerializedClock {
+        self.remaining_times()
+            .into_iter()
+            .map(|time| time.as_millis() as u64)
+            .collect()
+    }
+
+    /// Restores the remaining times, e.g. after loading a game. The clock is restarted
+    /// and decisions before the restored position can not be undone anymore.
+    ///
+    /// Panics if the number of times doesn't match the number of players, which is
+    /// already checked when the clock is parsed (see `io::parse_saved_game_with_clock`).
+    pub fn restore(&mut self, clock: &SerializedClock) {
+        assert_eq!(
+            clock.len(),
+            self.remaining.len(),
+            "Number of players does not match the clock"
+        );
+        self.remaining = clock.iter().map(|&ms| Duration::from_millis(ms)).collect();
+        self.history = vec![(self.remaining.clone(), self.timeout)];
+        self.restart();
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.time.now().saturating_sub(self.since)
+    }
+
+    fn is_exceeded(&self, player: usize, elapsed: Duration) -> bool {
+        elapsed > self.remaining[player] || self.control.move_limit.is_some_and(|l| elapsed > l)
+    }
+
+    /// Charges the elapsed time to the running player and starts a new period.
+    fn charge(&mut self) -> Option<usize> {
+        let now = self.time.now();
+        let elapsed = now.saturating_sub(self.since);
+        self.since = now;
+        let player = self.running?;
+        if self.is_exceeded(player, elapsed) {
+            self.timeout.get_or_insert(player);
+        }
+        self.remaining[player] = self.remaining[player].saturating_sub(elapsed);
+        Some(player)
+    }
+
+    fn restart(&mut self) {
+        self.running = None;
+        self.since = self.time.now();
+    }
+}
+
+impl<T: GameData, S: TimeSource> EventListener<T> for GameClock<S> {
+    fn effect_applied(&mut self, _effect: Box<T::EffectType>) {}
+
+    fn effect_observed(&mut self, _effect: &T::EffectType) {}
+
+    fn option_selected(&mut self, _index: usize, player: usize) {
+        match self.running {
+            Some(running) if running != player => {
+                self.charge();
+                self.running = Some(player);
+            }
+            Some(_) => {}
+            None => self.running = Some(player),
+        }
+    }
+
+    fn retracted_by_n(&mut self, _n: usize) {}
+
+    fn effects_completed(&mut self, _data: &T) {
+        if let Some(player) = self.charge() {
+            if self.timeout != Some(player) {
+                self.remaining[player] += self.control.increment;
+            }
+        }
+        self.running = None;
+        self.history.push((self.remaining.clone(), self.timeout));
+    }
+
+    fn decision_pending(
+        &mut self,
+        _data: &T,
+        player: usize,
+        _option_count: usize,
+        level_in_chain: usize,
+    ) {
+        if level_in_chain == 0 && self.running.is_none() {
+            self.running = Some(player);
+        }
+    }
+
+    fn decision_undone(&mut self, _data: &T) {
+        if self.history.len() > 1 {
+            self.history.pop();
+        }
+        let (remaining, timeout) = self.history.last().expect(INTERNAL_ERROR);
+        self.remaining = remaining.clone();
+        self.timeout = *timeout;
+        self.restart();
+    }
+
+    fn game_finished(&mut self, _data: &T, _result: Option<&GameResult>) {
+        self.running = None;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::Cell, rc::Rc, time::Duration};
+
+    use crate::{
+        engine::{
+            io::{parse_saved_game_with_clock, save_game_with_clock, CompatibilityPolicy},
+            logging::EventLog,
+            Engine, GameState,
+        },
+        test::{play, CountingGame, RevCountingGame},
+    };
+
+    use super::{GameClock, TimeControl, TimeSource};
+
+    #[derive(Debug, Clone, Default)]
+    struct ManualTime(Rc<Cell<Duration>>);
+
+    impl ManualTime {
+        fn advance(&self, secs: u64) {
+            self.0.set(self.0.get() + Duration::from_secs(secs));
+        }
+    }
+
+    impl TimeSource for ManualTime {
+        fn now(&self) -> Duration {
+            self.0.get()
+        }
+    }
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn clock_test() {
+        let time = ManualTime::default();
+        let control = TimeControl::new(secs(10), secs(1)).with_move_limit(secs(5));
+        let clock = GameClock::with_time_source(2, control, time.clone());
+        let mut engine = Engine::with_listener(2, CountingGame::new(20), (EventLog::new(), clock));
+        assert_eq!(engine.log().1.running_player(), Some(0));
+
+        time.advance(2);
+        assert_eq!(engine.log().1.remaining(0), secs(8));
+        play(&mut engine, &[0]);
+        assert_eq!(engine.log().1.remaining_times(), [secs(9), secs(10)]);
+        assert_eq!(engine.log().1.running_player(), Some(1));
+
+        // a retraction doesn't stop the clock
+        time.advance(3);
+        play(&mut engine, &[1]);
+        match engine.pull() {
+            GameState::PendingDecision(dec) => dec.into_follow_up_decision().unwrap().retract(),
+            _ => panic!("expected pending decision"),
+        }
+        time.advance(1);
+        assert_eq!(engine.log().1.remaining(1), secs(6));
+        play(&mut engine, &[1, 1]);
+        assert_eq!(engine.log().1.remaining_times(), [secs(9), secs(7)]);
+        assert_eq!(engine.data().history, [1, 3]);
+
+        time.advance(6);
+        assert_eq!(engine.log().1.timeout(), None);
+        assert_eq!(engine.log_mut().1.check_timeout(), Some(0));
+        assert_eq!(engine.log().1.serialized(), [3000, 7000]);
+    }
+
+    #[test]
+    fn undo_clock_test() {
+        let time = ManualTime::default();
+        let control = TimeControl::new(secs(10), secs(1)).with_move_limit(secs(5));
+        let clock = GameClock::with_time_source(2, control, time.clone());
+        let mut engine =
+            Engine::with_listener(2, RevCountingGame::new(20), (EventLog::new(), clock));
+        time.advance(2);
+        play(&mut engine, &[0]);
+        time.advance(6);
+        play(&mut engine, &[0]);
+        assert_eq!(engine.log().1.remaining_times(), [secs(9), secs(4)]);
+        assert_eq!(engine.log().1.timeout(), Some(1));
+
+        // the times (and the timeout) of the undone decision are reverted
+        time.advance(3);
+        assert!(engine.undo_last_decision());
+        assert_eq!(engine.log().1.remaining_times(), [secs(9), secs(10)]);
+        assert_eq!(engine.log().1.timeout(), None);
+        assert_eq!(engine.log().1.running_player(), Some(1));
+        time.advance(1);
+        assert_eq!(engine.log().1.remaining(1), secs(9));
+
+        // a redone decision doesn't consume time
+        assert!(engine.redo_decision());
+        assert_eq!(engine.log().1.remaining_times(), [secs(9), secs(10)]);
+        assert!(engine.undo_last_decision());
+        assert!(engine.undo_last_decision());
+        assert_eq!(engine.log().1.remaining_times(), [secs(10), secs(10)]);
+        assert!(!engine.undo_last_decision());
+    }
+
+    #[test]
+    fn save_clock_test() {
+        let time = ManualTime::default();
+        let clock = GameClock::with_time_source(2, TimeControl::new(secs(10), secs(0)), time);
+        let mut engine = Engine::with_listener(2, CountingGame::new(20), (EventLog::new(), clock));
+        play(&mut engine, &[0]);
+
+        let mut buffer = Vec::new();
+        save_game_with_clock(
+            &mut buffer,
+            "Counting",
+            [1, 0],
+            [("end".to_string(), "20".to_string())],
+            2,
+            engine.serialized_log(),
+            &vec![1500, 250],
+        )
+        .unwrap();
+        let ((_, _, log), clock) = parse_saved_game_with_clock(
+            buffer.as_slice(),
+            "Counting",
+            [1, 0],
+            CompatibilityPolicy::MinorEqual,
+        )
+        .unwrap();
+        assert_eq!(log, engine.serialized_log());
+        assert_eq!(clock, Some(vec![1500, 250]));
+
+        let invalid = String::from_utf8(buffer)
+            .unwrap()
+            .replace("T1500,250", "T1500");
+        let error = parse_saved_game_with_clock(
+            invalid.as_bytes(),
+            "Counting",
+            [1, 0],
+            CompatibilityPolicy::MinorEqual,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("Expected 2 times"), "{error}");
+
+        let mut clock = GameClock::new(2, TimeControl::new(secs(10), secs(0)));
+        clock.restore(&vec![1500, 250]);
+        assert_eq!(clock.remaining(1), Duration::from_millis(250));
+    }
+}
@@ -1,21 +1,31 @@
-use crate::{trait_definitions::Effect, GameData, RevEffect};
+use std::fmt::{self, Debug};
+
+use crate::{
+    trait_definitions::Effect, Decision, DecisionObject, GameData, GameResult, ObservableGameData,
+    OptionId,
+};
 
 use super::{
-    logging::EventLog, Engine, EventListener, InternalState, NotListening, PDecisionState,
-    PEffectState, INTERNAL_ERROR,
+    logging::{DecisionLog, UndoLog},
+    Engine, EngineError, EventListener, InternalState, NotListening, PDecisionState, PEffectState,
+    INTERNAL_ERROR,
 };
 
-#[derive(Debug)]
-pub enum GameState<'a, T: GameData, L: EventListener<T> = NotListening> {
-    PendingEffect(PendingEffect<'a, T, L>),
-    PendingDecision(PendingDecision<'a, T, L>),
-    Finished(Finished<'a, T, L>),
+pub enum GameState<
+    'a,
+    T: GameData,
+    L: EventListener<T> = NotListening,
+    D: DecisionObject<T> + ?Sized = dyn Decision<T>,
+> {
+    PendingEffect(PendingEffect<'a, T, L, D>),
+    PendingDecision(PendingDecision<'a, T, L, D>),
+    Finished(Finished<'a, T, L, D>),
 }
 
-impl<T: GameData, L: EventListener<T>> Engine<T, L> {
-    pub fn pull(&mut self) -> GameState<'_, T, L> {
+impl<T: GameData, L: EventListener<T>, D: DecisionObject<T> + ?Sized> Engine<T, L, D> {
+    pub fn pull(&mut self) -> GameState<'_, T, L, D> {
         match &self.state {
-            InternalState::PEffect(_) => GameState::PendingEffect(PendingEffect { engine: self }),
+            InternalState::PEffect(..) => GameState::PendingEffect(PendingEffect { engine: self }),
             InternalState::PDecision(_, _) => {
                 assert!(
                     self.option_count() > 0,
@@ -31,14 +41,28 @@ impl<T: GameData, L: EventListener<T>> Engine<T, L> {
     pub fn data(&self) -> &T {
         &self.data
     }
+
+    /// The data as seen by the given player.
+    pub fn view(&self, player: usize) -> T::View
+    where
+        T: ObservableGameData,
+    {
+        self.data.view(player)
+    }
 }
 
-#[derive(Debug)]
-pub struct PendingEffect<'a, T: GameData, L: EventListener<T> = NotListening> {
-    engine: &'a mut Engine<T, L>,
+pub struct PendingEffect<
+    'a,
+    T: GameData,
+    L: EventListener<T> = NotListening,
+    D: DecisionObject<T> + ?Sized = dyn Decision<T>,
+> {
+    engine: &'a mut Engine<T, L, D>,
 }
 
-impl<'a, T: GameData, L: EventListener<T>> PendingEffect<'a, T, L> {
+impl<'a, T: GameData, L: EventListener<T>, D: DecisionObject<T> + ?Sized>
+    PendingEffect<'a, T, L, D>
+{
     pub fn next_effect(self) {
         self.engine.next_effect();
     }
@@ -52,25 +76,35 @@ impl<'a, T: GameData, L: EventListener<T>> PendingEffect<'a, T, L> {
     }
 }
 
-impl<T: GameData> PendingEffect<'_, T, EventLog<T>>
-where
-    T::EffectType: RevEffect<T>,
-{
+impl<T: GameData, L: UndoLog<T>, D: DecisionObject<T> + ?Sized> PendingEffect<'_, T, L, D> {
     pub fn undo_last_decision(&mut self) -> bool {
         self.engine.undo_last_decision()
     }
 }
 
-#[derive(Debug)]
-pub struct PendingDecision<'a, T: GameData, L: EventListener<T> = NotListening> {
-    engine: &'a mut Engine<T, L>,
+pub struct PendingDecision<
+    'a,
+    T: GameData,
+    L: EventListener<T> = NotListening,
+    D: DecisionObject<T> + ?Sized = dyn Decision<T>,
+> {
+    engine: &'a mut Engine<T, L, D>,
 }
 
-impl<'a, T: GameData, L: EventListener<T>> PendingDecision<'a, T, L> {
+impl<'a, T: GameData, L: EventListener<T>, D: DecisionObject<T> + ?Sized>
+    PendingDecision<'a, T, L, D>
+{
     pub fn select_option(self, index: usize) {
         self.engine.select_option(index)
     }
 
+    /// Same as `select_option`, but returns an error for an invalid index.
+    pub fn try_select_option(self, index: usize) -> Result<(), EngineError> {
+        self.engine.check_option(index)?;
+        self.engine.select_option(index);
+        Ok(())
+    }
+
     pub fn option_count(&self) -> usize {
         self.engine.option_count()
     }
@@ -87,6 +121,34 @@ impl<'a, T: GameData, L: EventListener<T>> PendingDecision<'a, T, L> {
         self.engine.data()
     }
 
+    /// The engine, e.g. for cloning it to calculate the best option.
+    pub fn engine(&self) -> &Engine<T, L, D> {
+        self.engine
+    }
+
+    /// The data as seen by the deciding player.
+    pub fn view(&self) -> T::View
+    where
+        T: ObservableGameData,
+    {
+        self.engine.data().view(self.player())
+    }
+
+    /// Weights of the options if this is a chance decision, see `Decision::chance_weights`.
+    pub fn chance_weights(&self) -> Option<Vec<u32>> {
+        self.engine.decision().chance_weights()
+    }
+
+    /// Stable identifier of the option, see `Decision::option_id`.
+    pub fn option_id(&self, index: usize) -> Option<OptionId> {
+        self.engine.decision().option_id(self.data(), index)
+    }
+
+    /// Index of the option with the given identifier, see `Decision::option_id`.
+    pub fn option_index(&self, id: OptionId) -> Option<usize> {
+        self.engine.decision().option_index(self.data(), id)
+    }
+
     pub fn level_in_chain(&self) -> usize {
         self.engine.level_in_chain()
     }
@@ -95,7 +157,7 @@ impl<'a, T: GameData, L: EventListener<T>> PendingDecision<'a, T, L> {
         self.engine.level_in_chain() > 0
     }
 
-    pub fn into_follow_up_decision(self) -> Option<FollowUpDecision<'a, T, L>> {
+    pub fn into_follow_up_decision(self) -> Option<FollowUpDecision<'a, T, L, D>> {
         if self.is_follow_up_decision() {
             Some(FollowUpDecision {
                 engine: self.engine,
@@ -105,7 +167,7 @@ impl<'a, T: GameData, L: EventListener<T>> PendingDecision<'a, T, L> {
         }
     }
 
-    pub fn try_into_follow_up_decision(self) -> Result<FollowUpDecision<'a, T, L>, Self> {
+    pub fn try_into_follow_up_decision(self) -> Result<FollowUpDecision<'a, T, L, D>, Self> {
         if self.is_follow_up_decision() {
             Ok(FollowUpDecision {
                 engine: self.engine,
@@ -115,18 +177,24 @@ impl<'a, T: GameData, L: EventListener<T>> PendingDecision<'a, T, L> {
         }
     }
 
+    /// Same as `apply_option`, but returns an error for an invalid index.
+    pub fn try_apply_option(self, index: usize) -> Result<bool, EngineError> {
+        self.engine.check_option(index)?;
+        Ok(self.apply_option(index))
+    }
+
     /// Returns true if this was the final subdecision of the decision chain (and thus effects were applied)
     pub fn apply_option(self, index: usize) -> bool {
         self.engine.select_option(index);
 
         match &mut self.engine.state {
-            InternalState::PEffect(_) => {
+            InternalState::PEffect(..) => {
                 let mut effect = Some(self.engine.take_effect());
                 while let Some(next) = effect {
                     effect = next.apply(&mut self.engine.data);
                     self.engine.listener.effect_applied(next);
                 }
-                self.engine.state = self.engine.fetch_next_state();
+                self.engine.complete_effects();
                 true
             }
             InternalState::PDecision(_, _) => false,
@@ -135,25 +203,30 @@ impl<'a, T: GameData, L: EventListener<T>> PendingDecision<'a, T, L> {
     }
 }
 
-impl<T: GameData> PendingDecision<'_, T, EventLog<T>> {
+impl<T: GameData, L: DecisionLog<T>, D: DecisionObject<T> + ?Sized> PendingDecision<'_, T, L, D> {
     pub fn undo_last_decision(&mut self) -> bool
     where
-        T::EffectType: RevEffect<T>,
+        L: UndoLog<T>,
     {
         self.engine.undo_last_decision()
     }
 
+    /// Redoes the next decision of the main line. Not possible within a follow-up decision
+    /// or while the game is paused.
     pub fn redo_decision(self) -> bool {
-        if !self.engine.listener.redo_available() {
+        if self.is_follow_up_decision()
+            || self.engine.meta.is_paused()
+            || !self.engine.listener.redo_available()
+        {
             return false;
         }
 
         loop {
-            let index = self.engine.listener.redo_step().expect(INTERNAL_ERROR);
+            let (index, _) = self.engine.listener.redo_step().expect(INTERNAL_ERROR);
             self.engine.select_and_apply_option(index);
 
             match &mut self.engine.state {
-                InternalState::PEffect(_) => {
+                InternalState::PEffect(..) => {
                     break;
                 }
                 InternalState::PDecision(_, _) => {}
@@ -165,21 +238,38 @@ impl<T: GameData> PendingDecision<'_, T, EventLog<T>> {
             effect = next.apply(&mut self.engine.data);
             self.engine.listener.redo_effect(next);
         }
-        self.engine.state = self.engine.fetch_next_state();
+        self.engine.decision_count += 1;
+        self.engine.meta.decision_completed();
+        self.engine.listener.effects_completed(&self.engine.data);
+        self.engine.listener.decision_redone(&self.engine.data);
+        self.engine.enter_next_state();
         true
     }
 }
 
-#[derive(Debug)]
-pub struct FollowUpDecision<'a, T: GameData, L: EventListener<T>> {
-    engine: &'a mut Engine<T, L>,
+pub struct FollowUpDecision<
+    'a,
+    T: GameData,
+    L: EventListener<T>,
+    D: DecisionObject<T> + ?Sized = dyn Decision<T>,
+> {
+    engine: &'a mut Engine<T, L, D>,
 }
 
-impl<'a, T: GameData, L: EventListener<T>> FollowUpDecision<'a, T, L> {
+impl<'a, T: GameData, L: EventListener<T>, D: DecisionObject<T> + ?Sized>
+    FollowUpDecision<'a, T, L, D>
+{
     pub fn select_option(self, index: usize) {
         self.engine.select_option(index)
     }
 
+    /// Same as `select_option`, but returns an error for an invalid index.
+    pub fn try_select_option(self, index: usize) -> Result<(), EngineError> {
+        self.engine.check_option(index)?;
+        self.engine.select_option(index);
+        Ok(())
+    }
+
     pub fn option_count(&self) -> usize {
         self.engine.option_count()
     }
@@ -205,6 +295,16 @@ impl<'a, T: GameData, L: EventListener<T>> FollowUpDecision<'a, T, L> {
         assert!(self.engine.retract_n(1), "{}", INTERNAL_ERROR)
     }
 
+    /// Same as `retract`, but returns an error instead of panicking.
+    pub fn try_retract(self) -> Result<(), EngineError> {
+        self.engine.try_retract_n(1)
+    }
+
+    /// Same as `retract_n`, but returns an error if the retraction is not possible.
+    pub fn try_retract_n(self, n: usize) -> Result<(), EngineError> {
+        self.engine.try_retract_n(n)
+    }
+
     /// Retracts from n subdecisions and returns whether the retraction was successful.
     ///
     /// This is the case if and only if n <= #{pending decisions}.
@@ -219,32 +319,103 @@ impl<'a, T: GameData, L: EventListener<T>> FollowUpDecision<'a, T, L> {
     }
 }
 
-impl<T: GameData> FollowUpDecision<'_, T, EventLog<T>>
-where
-    T::EffectType: RevEffect<T>,
-{
+impl<T: GameData, L: UndoLog<T>, D: DecisionObject<T> + ?Sized> FollowUpDecision<'_, T, L, D> {
     pub fn undo_last_decision(&mut self) -> bool {
         self.engine.undo_last_decision()
     }
 }
 
-#[derive(Debug)]
-pub struct Finished<'a, T: GameData, L: EventListener<T>> {
-    engine: &'a mut Engine<T, L>,
-    // TODO additional information?
+pub struct Finished<
+    'a,
+    T: GameData,
+    L: EventListener<T>,
+    D: DecisionObject<T> + ?Sized = dyn Decision<T>,
+> {
+    engine: &'a mut Engine<T, L, D>,
 }
 
-impl<'a, T: GameData, L: EventListener<T>> Finished<'a, T, L> {
+impl<'a, T: GameData, L: EventListener<T>, D: DecisionObject<T> + ?Sized> Finished<'a, T, L, D> {
     pub fn data(&self) -> &T {
         self.engine.data()
     }
+
+    /// Outcome of the game, see `Engine::result`.
+    pub fn result(&self) -> Option<GameResult> {
+        self.engine.result()
+    }
 }
 
-impl<T: GameData> Finished<'_, T, EventLog<T>>
-where
-    T::EffectType: RevEffect<T>,
-{
+impl<T: GameData, L: UndoLog<T>, D: DecisionObject<T> + ?Sized> Finished<'_, T, L, D> {
     pub fn undo_last_decision(&mut self) -> bool {
         self.engine.undo_last_decision()
     }
 }
+
+impl<T, L, D> Debug for GameState<'_, T, L, D>
+where
+    T: GameData + Debug,
+    L: EventListener<T> + Debug,
+    D: DecisionObject<T> + ?Sized,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameState::PendingEffect(state) => f.debug_tuple("PendingEffect").field(state).finish(),
+            GameState::PendingDecision(state) => {
+                f.debug_tuple("PendingDecision").field(state).finish()
+            }
+            GameState::Finished(state) => f.debug_tuple("Finished").field(state).finish(),
+        }
+    }
+}
+
+impl<T, L, D> Debug for PendingEffect<'_, T, L, D>
+where
+    T: GameData + Debug,
+    L: EventListener<T> + Debug,
+    D: DecisionObject<T> + ?Sized,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PendingEffect")
+            .field("engine", &self.engine)
+            .finish()
+    }
+}
+
+impl<T, L, D> Debug for PendingDecision<'_, T, L, D>
+where
+    T: GameData + Debug,
+    L: EventListener<T> + Debug,
+    D: DecisionObject<T> + ?Sized,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PendingDecision")
+            .field("engine", &self.engine)
+            .finish()
+    }
+}
+
+impl<T, L, D> Debug for FollowUpDecision<'_, T, L, D>
+where
+    T: GameData + Debug,
+    L: EventListener<T> + Debug,
+    D: DecisionObject<T> + ?Sized,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FollowUpDecision")
+            .field("engine", &self.engine)
+            .finish()
+    }
+}
+
+impl<T, L, D> Debug for Finished<'_, T, L, D>
+where
+    T: GameData + Debug,
+    L: EventListener<T> + Debug,
+    D: DecisionObject<T> + ?Sized,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Finished")
+            .field("engine", &self.engine)
+            .finish()
+    }
+}
@@ -2,17 +2,34 @@ use std::{
     fmt::Display,
     fs::File,
     io::{self, BufRead, BufWriter, Write},
+    mem,
     path::Path,
 };
 
-use crate::{engine::INTERNAL_ERROR, GameData};
+use crate::{engine::INTERNAL_ERROR, GameData, MoveNotation, StateHash};
 
-use super::{GameState, LoggingEngine};
+use super::{
+    logging::{EventLog, Variation},
+    meta::{MetaAction, MetaActionError},
+    migration::MigrationRegistry,
+    Engine, EventListener, GameState, LoggingEngine, PendingDecision,
+};
 
 const PLAYER_SEPARATOR: char = 'P';
+const HASH_SEPARATOR: char = '#';
 const CURRENT_STATE: &str = "C";
+const VARIATION_START: char = 'V';
+const VARIATION_SEPARATOR: char = ':';
+const VARIATION_END: &str = "E";
+const CLOCK_START: char = 'T';
+const CLOCK_SEPARATOR: char = ',';
+const META_START: char = 'M';
+/// Appended to the header of saves with an escaped initial state.
+/// The initial state of older saves is read without unescaping.
+const ESCAPED_FORMAT: &str = "f2";
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[non_exhaustive]
 pub enum CompatibilityPolicy {
     MajorLessEqual,
@@ -30,47 +47,314 @@ impl CompatibilityPolicy {
             CompatibilityPolicy::MinorEqual => save == game,
         }
     }
+
+    fn check(&self, game: [u32; 2], save: [u32; 2]) -> Result<(), LoadGameError> {
+        if self.is_compatible(game, save) {
+            Ok(())
+        } else {
+            Err(LoadGameError::VersionMismatch { game, save })
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SerializedLog {
     pub log: Vec<(usize, usize)>,
     /// Attention: redo information is stored in reverse order!
     pub redo_stack: Vec<(usize, usize)>,
+    /// Side variations of the line consisting of the log and the redo stack.
+    pub variations: Vec<SerializedVariation>,
+    /// State fingerprints, each with the index of the log entry that completes the
+    /// corresponding decision (sorted by the index).
+    pub hashes: Vec<(usize, u64)>,
+    /// Meta actions, each with the number of log entries before it (sorted by the number).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub meta: Vec<(usize, MetaAction)>,
+}
+
+/// A side variation that branches off from a line of decisions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SerializedVariation {
+    /// Number of completed decisions of the parent line before the variation starts.
+    pub position: usize,
+    /// Index of the variation within all continuations at its position
+    /// (the continuation of the parent line has the lowest index not used by a side variation).
+    pub index: usize,
+    /// Selected options of the main line of the variation.
+    pub line: Vec<(usize, usize)>,
+    /// Side variations of this variation, with positions relative to its start.
+    pub variations: Vec<SerializedVariation>,
+}
+
+/// Remaining time of each player in milliseconds, see `clock::GameClock`.
+pub type SerializedClock = Vec<u64>;
+
+/// Log of complete moves in human-readable notation, see `MoveNotation`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NotationLog {
+    /// Player and token of each move.
+    pub log: Vec<(usize, String)>,
+    /// Attention: redo information is stored in reverse order!
+    pub redo_stack: Vec<(usize, String)>,
+}
+
+/// A complete saved game, independent of the file format.
+///
+/// With the `serde` feature, this can be stored in any format supported by serde.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SavedGame {
+    pub header: String,
+    pub version: [u32; 2],
+    /// Initial game state as key-value pairs.
+    pub initial_state: Vec<(String, String)>,
+    pub num_players: usize,
+    pub log: SerializedLog,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub clock: Option<SerializedClock>,
+}
+
+impl SavedGame {
+    pub fn new<H: Into<String>, I>(
+        header: H,
+        version: [u32; 2],
+        initial_state: I,
+        num_players: usize,
+        log: SerializedLog,
+    ) -> Self
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        Self {
+            header: header.into(),
+            version,
+            initial_state: initial_state.into_iter().collect(),
+            num_players,
+            log,
+            clock: None,
+        }
+    }
+
+    pub fn with_clock(mut self, clock: SerializedClock) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Checks header and version and loads the game state via a function to interpret
+    /// the key-value pairs representing the initial state (see `load_game`).
+    pub fn restore<T: GameData + StateHash, H: AsRef<str>, F>(
+        self,
+        expected_header: H,
+        version: [u32; 2],
+        compatibility_policy: CompatibilityPolicy,
+        parse_initial_state: F,
+    ) -> Result<LoggingEngine<T>, LoadGameError>
+    where
+        F: Fn(&[(String, String)]) -> Result<T, String>,
+    {
+        self.check(expected_header.as_ref(), version, compatibility_policy)?;
+        let initial_state = self.initial_state;
+        restore_game_state(
+            self.num_players,
+            || parse_initial_state(&initial_state),
+            self.log,
+        )
+    }
+
+    /// Same as `restore`, but verifies the state fingerprints (see `restore_verified_game_state`).
+    pub fn restore_verified<T: GameData + StateHash, H: AsRef<str>, F>(
+        self,
+        expected_header: H,
+        version: [u32; 2],
+        compatibility_policy: CompatibilityPolicy,
+        parse_initial_state: F,
+        hash_interval: usize,
+    ) -> Result<LoggingEngine<T>, LoadGameError>
+    where
+        F: Fn(&[(String, String)]) -> Result<T, String>,
+    {
+        self.check(expected_header.as_ref(), version, compatibility_policy)?;
+        let initial_state = self.initial_state;
+        restore_verified_game_state(
+            self.num_players,
+            || parse_initial_state(&initial_state),
+            self.log,
+            hash_interval,
+        )
+    }
+
+    fn check(
+        &self,
+        expected_header: &str,
+        version: [u32; 2],
+        compatibility_policy: CompatibilityPolicy,
+    ) -> Result<(), LoadGameError> {
+        if self.header != expected_header {
+            return Err(LoadGameError::HeaderMismatch {
+                expected: expected_header.to_string(),
+                found: self.header.clone(),
+            });
+        }
+        compatibility_policy.check(version, self.version)
+    }
+
+    /// Saves the game in the line-based format via the provided writer (see `save_game`).
+    pub fn write<W: Write>(self, mut writer: W) -> Result<(), io::Error> {
+        save_game(
+            &mut writer,
+            self.header,
+            self.version,
+            self.initial_state,
+            self.num_players,
+            self.log,
+        )?;
+        match self.clock {
+            Some(clock) => write_clock(writer, &clock),
+            None => Ok(()),
+        }
+    }
+}
+
+const ESCAPE: char = '\\';
+/// Escaped representation of an empty string
+const EMPTY: &str = "\\e";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InitialStateError {
+    /// Uneven number of key-value entries, i.e. the last key has no value
+    MissingValue { key: String },
+    /// Unknown or incomplete escape sequence
+    InvalidEscape { entry: String },
+}
+
+impl Display for InitialStateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InitialStateError::MissingValue { key } => {
+                write!(
+                    f,
+                    "Uneven number of key-value entries, missing value for key {key}"
+                )
+            }
+            InitialStateError::InvalidEscape { entry } => {
+                write!(f, "Invalid escape sequence in entry {entry}")
+            }
+        }
+    }
+}
+
+/// Escapes whitespace and the escape character, so that the result is a single non-empty word.
+fn escape(input: &str) -> String {
+    if input.is_empty() {
+        return EMPTY.to_string();
+    }
+    let mut result = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            ESCAPE => result.push_str("\\\\"),
+            ' ' => result.push_str("\\s"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            '\x0C' => result.push_str("\\f"),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+fn unescape(input: &str) -> Result<String, InitialStateError> {
+    if input == EMPTY {
+        return Ok(String::new());
+    }
+    let invalid = || InitialStateError::InvalidEscape {
+        entry: input.to_string(),
+    };
+    let mut result = String::with_capacity(input.len());
+    let mut chars = input.chars();
+    while let Some(c) = chars.next() {
+        if c == ESCAPE {
+            result.push(match chars.next().ok_or_else(invalid)? {
+                ESCAPE => ESCAPE,
+                's' => ' ',
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                'f' => '\x0C',
+                _ => return Err(invalid()),
+            });
+        } else {
+            result.push(c);
+        }
+    }
+    Ok(result)
 }
 
 /// Serialized string of the given initial game state, provided as key-value pairs.
+///
+/// Whitespace (including newlines) within keys and values is escaped, so that the result
+/// is a single line.
 pub fn serialize_initial_state<I>(initial_state: I) -> String
 where
     I: IntoIterator<Item = (String, String)>,
 {
     let key_val_pairs = initial_state
         .into_iter()
-        .map(|(key, val)| {
-            // TODO: escaping of spaces / newlines
-            assert!(!key.contains(' ') && !key.contains('\n'));
-            assert!(!val.contains(' ') && !val.contains('\n'));
-            key + " " + &val
-        })
+        .map(|(key, val)| escape(&key) + " " + &escape(&val))
         .collect::<Vec<_>>();
     key_val_pairs.join(" ")
 }
 
-/// Serialized string of the given initial game state, provided as key-value pairs.
-pub fn deserialize_initial_state(input: &str) -> Result<Vec<(String, String)>, String> {
+/// Key-value pairs of the initial game state, parsed from the serialized string.
+pub fn deserialize_initial_state(input: &str) -> Result<Vec<(String, String)>, InitialStateError> {
+    let mut key_val_pairs = Vec::new();
+    let mut it = input.split_ascii_whitespace();
+    loop {
+        match (it.next(), it.next()) {
+            (Some(key), Some(val)) => key_val_pairs.push((unescape(key)?, unescape(val)?)),
+            (None, None) => break,
+            (Some(key), None) => {
+                return Err(InitialStateError::MissingValue {
+                    key: key.to_string(),
+                })
+            }
+            _ => unreachable!(),
+        }
+    }
+    Ok(key_val_pairs)
+}
+
+/// Initial state in the format of saves before escaping was introduced,
+/// i.e. whitespace separated entries without escape sequences.
+fn deserialize_raw_initial_state(input: &str) -> Result<Vec<(String, String)>, InitialStateError> {
     let mut key_val_pairs = Vec::new();
-    let mut it = input.split_ascii_whitespace().peekable();
+    let mut it = input.split_ascii_whitespace();
     loop {
         match (it.next(), it.next()) {
             (Some(key), Some(val)) => key_val_pairs.push((key.to_string(), val.to_string())),
             (None, None) => break,
-            (_, None) => return Err("Uneven number of key-value entries".to_string()),
+            (Some(key), None) => {
+                return Err(InitialStateError::MissingValue {
+                    key: key.to_string(),
+                })
+            }
             _ => unreachable!(),
         }
     }
     Ok(key_val_pairs)
 }
 
+fn write_header<W: Write>(mut writer: W, header: &str, version: [u32; 2]) -> Result<(), io::Error> {
+    writeln!(
+        writer,
+        "{header} v{}.{} {ESCAPED_FORMAT}",
+        version[0], version[1]
+    )
+}
+
 /// Saves the game state to the given file. Initial game state can be provided as key-value pairs.
 ///
 /// Note: Atomicity of file acccess needs to be ensured by the application.
@@ -108,17 +392,200 @@ pub fn save_game<W: Write, H: AsRef<str>, I>(
 where
     I: IntoIterator<Item = (String, String)>,
 {
-    writeln!(writer, "{} v{}.{}", header.as_ref(), version[0], version[1])?;
+    write_header(&mut writer, header.as_ref(), version)?;
     let initial_state = serialize_initial_state(initial_state);
     writeln!(writer, "{initial_state}")?;
     writeln!(writer, "{num_players}")?;
-    for (index, player) in log.log.into_iter() {
-        writeln!(writer, "{index}{PLAYER_SEPARATOR}{player}")?;
+    let mut hashes = log.hashes.into_iter().peekable();
+    let mut meta = log.meta.into_iter().peekable();
+    for (i, (index, player)) in log.log.into_iter().enumerate() {
+        while let Some((_, action)) = meta.next_if(|&(position, _)| position == i) {
+            write_meta_action(&mut writer, action)?;
+        }
+        write!(writer, "{index}{PLAYER_SEPARATOR}{player}")?;
+        if let Some((_, hash)) = hashes.next_if(|&(decision_nr, _)| decision_nr == i) {
+            write!(writer, "{HASH_SEPARATOR}{hash:x}")?;
+        }
+        writeln!(writer)?;
+    }
+    for (_, action) in meta {
+        write_meta_action(&mut writer, action)?;
     }
     writeln!(writer, "{CURRENT_STATE}")?;
     for (index, player) in log.redo_stack.into_iter() {
         writeln!(writer, "{index}{PLAYER_SEPARATOR}{player}")?;
     }
+    write_variations(&mut writer, &log.variations)
+}
+
+/// Same as `save_game`, but additionally saves the remaining time of each player.
+///
+/// The clock is ignored when the game is loaded with `parse_saved_game`
+/// or the `load_game` functions, see `parse_saved_game_with_clock`.
+pub fn save_game_with_clock<W: Write, H: AsRef<str>, I>(
+    mut writer: W,
+    header: H,
+    version: [u32; 2],
+    initial_state: I,
+    num_players: usize,
+    log: SerializedLog,
+    clock: &SerializedClock,
+) -> Result<(), io::Error>
+where
+    I: IntoIterator<Item = (String, String)>,
+{
+    save_game(
+        &mut writer,
+        header,
+        version,
+        initial_state,
+        num_players,
+        log,
+    )?;
+    write_clock(writer, clock)
+}
+
+fn write_clock<W: Write>(mut writer: W, clock: &SerializedClock) -> Result<(), io::Error> {
+    let times = clock
+        .iter()
+        .map(u64::to_string)
+        .collect::<Vec<_>>()
+        .join(&CLOCK_SEPARATOR.to_string());
+    writeln!(writer, "{CLOCK_START}{times}")
+}
+
+fn write_meta_action<W: Write>(mut writer: W, action: MetaAction) -> Result<(), io::Error> {
+    let code = match action {
+        MetaAction::Resign(_) => 'R',
+        MetaAction::OfferDraw(_) => 'O',
+        MetaAction::AcceptDraw(_) => 'A',
+        MetaAction::DeclineDraw(_) => 'D',
+        MetaAction::Timeout(_) => 'T',
+        MetaAction::Pause(_) => 'P',
+        MetaAction::Resume(_) => 'U',
+    };
+    writeln!(writer, "{META_START}{code}{}", action.player())
+}
+
+fn parse_meta_action(input: &str, curr_line: usize) -> Result<MetaAction, LoadGameError> {
+    let mut chars = input.chars();
+    let constructor = match chars.next() {
+        Some('R') => MetaAction::Resign,
+        Some('O') => MetaAction::OfferDraw,
+        Some('A') => MetaAction::AcceptDraw,
+        Some('D') => MetaAction::DeclineDraw,
+        Some('T') => MetaAction::Timeout,
+        Some('P') => MetaAction::Pause,
+        Some('U') => MetaAction::Resume,
+        _ => {
+            return Err(LoadGameError::from_file(
+                curr_line,
+                format!("Invalid meta action: {input}"),
+            ))
+        }
+    };
+    Ok(constructor(parse_number(chars.as_str(), curr_line)?))
+}
+
+fn write_variations<W: Write>(
+    writer: &mut W,
+    variations: &[SerializedVariation],
+) -> Result<(), io::Error> {
+    for variation in variations {
+        writeln!(
+            writer,
+            "{VARIATION_START}{}{VARIATION_SEPARATOR}{}",
+            variation.position, variation.index
+        )?;
+        for (index, player) in variation.line.iter() {
+            writeln!(writer, "{index}{PLAYER_SEPARATOR}{player}")?;
+        }
+        write_variations(writer, &variation.variations)?;
+        writeln!(writer, "{VARIATION_END}")?;
+    }
+    Ok(())
+}
+
+/// Converts the log into notation form by replaying it. A pending follow-up decision
+/// is not part of the result, since it doesn't belong to a complete move.
+///
+/// Variations and state fingerprints are not supported by the notation format and are
+/// skipped. Meta actions aren't supported either, but result in an error since they
+/// might change the outcome of the game.
+pub fn notation_log<T: MoveNotation, F>(
+    num_players: usize,
+    create_data: F,
+    log: &SerializedLog,
+) -> Result<NotationLog, LoadGameError>
+where
+    F: Fn() -> Result<T, String>,
+{
+    if let Some(&(decision_nr, action)) = log.meta.first() {
+        return Err(LoadGameError::UnsupportedMetaAction {
+            decision_nr,
+            action,
+        });
+    }
+    let engine = restore_game_state_impl(num_players, create_data()?, log.log.iter())?;
+    let num_moves = engine.log().decision_chains().len();
+    let mut line = log.log[..log.log.len() - engine.pending_chain_len()].to_vec();
+    line.extend(log.redo_stack.iter().rev());
+
+    // replay the complete line first to verify it and find the end of each move
+    let chains = restore_game_state_impl(num_players, create_data()?, line.iter())?
+        .log()
+        .decision_chains();
+
+    let mut engine = LoggingEngine::new_logging(num_players, create_data()?);
+    let mut moves = Vec::with_capacity(chains.len());
+    let mut decision_nr = 0;
+    for chain in chains {
+        let mut contexts = Vec::with_capacity(chain.len());
+        let mut token = String::new();
+        for (i, &(index, _)) in chain.iter().enumerate() {
+            let decision = pending_decision(&mut engine, decision_nr)?;
+            contexts.push((decision.context(), index));
+            if i + 1 == chain.len() {
+                // the data is unchanged until the last option of the move is applied
+                token = decision.data().write_notation(&contexts);
+            }
+            decision.apply_option(index);
+            decision_nr += 1;
+        }
+        moves.push((chain[0].1, token));
+    }
+    let mut redo_stack = moves.split_off(num_moves);
+    redo_stack.reverse();
+    Ok(NotationLog {
+        log: moves,
+        redo_stack,
+    })
+}
+
+/// Saves the game state in notation form via the provided writer (see `notation_log`).
+/// Initial game state can be provided as key-value pairs.
+pub fn save_game_with_notation<W: Write, H: AsRef<str>, I>(
+    mut writer: W,
+    header: H,
+    version: [u32; 2],
+    initial_state: I,
+    num_players: usize,
+    log: NotationLog,
+) -> Result<(), io::Error>
+where
+    I: IntoIterator<Item = (String, String)>,
+{
+    write_header(&mut writer, header.as_ref(), version)?;
+    let initial_state = serialize_initial_state(initial_state);
+    writeln!(writer, "{initial_state}")?;
+    writeln!(writer, "{num_players}")?;
+    for (player, token) in log.log.iter() {
+        writeln!(writer, "{}{PLAYER_SEPARATOR}{player}", escape(token))?;
+    }
+    writeln!(writer, "{CURRENT_STATE}")?;
+    for (player, token) in log.redo_stack.iter() {
+        writeln!(writer, "{}{PLAYER_SEPARATOR}{player}", escape(token))?;
+    }
     Ok(())
 }
 
@@ -131,6 +598,16 @@ pub enum LoadGameError {
         line: usize,
         msg: String,
     },
+    /// Header of the saved game does not match the game
+    HeaderMismatch {
+        expected: String,
+        found: String,
+    },
+    /// Syntactic error in the initial state
+    InvalidInitialState {
+        line: usize,
+        error: InitialStateError,
+    },
     /// Version error
     VersionMismatch {
         game: [u32; 2],
@@ -152,6 +629,40 @@ pub enum LoadGameError {
     GameAlreadyFinished {
         decision_nr: usize,
     },
+    /// Semantic error: the game is paused by a meta action
+    GamePaused {
+        decision_nr: usize,
+    },
+    /// Semantic error: variation does not match its parent line
+    InvalidVariation {
+        position: usize,
+        index: usize,
+    },
+    /// Semantic error: the state fingerprint after the decision does not match
+    StateMismatch {
+        decision_nr: usize,
+    },
+    /// A migration of the saved game failed
+    MigrationFailed {
+        msg: String,
+    },
+    /// Semantic error: the token can not be resolved for the move
+    InvalidNotation {
+        move_nr: usize,
+        token: String,
+        msg: String,
+    },
+    /// Semantic error: the meta action can not be applied after the given number of decisions
+    InvalidMetaAction {
+        decision_nr: usize,
+        action: MetaAction,
+        error: MetaActionError,
+    },
+    /// The meta action after the given number of decisions can't be stored in notation form
+    UnsupportedMetaAction {
+        decision_nr: usize,
+        action: MetaAction,
+    },
 }
 
 impl LoadGameError {
@@ -180,6 +691,9 @@ impl Display for LoadGameError {
         match self {
             LoadGameError::IO(e) => write!(f, "{e}"),
             LoadGameError::InvalidFileContent { line, msg } => write!(f, "{msg} at line {line}"),
+            LoadGameError::HeaderMismatch { expected, found }
+                => write!(f, "invalid header: {found}, expected: {expected}"),
+            LoadGameError::InvalidInitialState { line, error } => write!(f, "{error} at line {line}"),
             LoadGameError::VersionMismatch { game, save }
                 => write!(f, "saved version (v{}.{}) is incompatible with game version (v{}.{})", save[0], save[1], game[0], game[1]),
             LoadGameError::InvalidDecisionIndex { decision_nr, index, max_index }
@@ -188,77 +702,192 @@ impl Display for LoadGameError {
                 => write!(f, "expected player {expected_player}, but got player {player} at decision number {decision_nr}"),
             LoadGameError::GameAlreadyFinished { decision_nr }
                 => write!(f, "game is already finished at decision number {decision_nr}"),
+            LoadGameError::GamePaused { decision_nr }
+                => write!(f, "game is paused at decision number {decision_nr}"),
+            LoadGameError::InvalidVariation { position, index }
+                => write!(f, "variation with index {index} at position {position} does not match its parent line"),
+            LoadGameError::StateMismatch { decision_nr }
+                => write!(f, "state fingerprint does not match at decision number {decision_nr}"),
+            LoadGameError::MigrationFailed { msg } => write!(f, "migration failed: {msg}"),
+            LoadGameError::InvalidNotation { move_nr, token, msg }
+                => write!(f, "invalid token {token}: {msg} at move number {move_nr}"),
+            LoadGameError::InvalidMetaAction { decision_nr, action, error }
+                => write!(f, "invalid meta action {action:?}: {error} at decision number {decision_nr}"),
+            LoadGameError::UnsupportedMetaAction { decision_nr, action }
+                => write!(f, "meta action {action:?} is not supported by the notation format at decision number {decision_nr}"),
         }
     }
 }
 
+/// Initial state as key-value pairs, number of players and the serialized log.
+pub type ParsedGame = (Vec<(String, String)>, usize, SerializedLog);
+
+/// Initial state as key-value pairs, number of players and the notation log.
+pub type ParsedNotationGame = (Vec<(String, String)>, usize, NotationLog);
+
 /// Reads the game state via the provided reader into key-value pairs for the initial state
 /// and a serialized log.
 pub fn parse_saved_game<R: BufRead, H: AsRef<str>>(
-    mut reader: R,
+    reader: R,
     expected_header: H,
     version: [u32; 2],
     compatibility_policy: CompatibilityPolicy,
-) -> Result<(Vec<(String, String)>, usize, SerializedLog), LoadGameError> {
+) -> Result<ParsedGame, LoadGameError> {
+    let (save_version, parsed) = parse_saved_game_with_version(reader, expected_header)?;
+    compatibility_policy.check(version, save_version)?;
+    Ok(parsed)
+}
+
+/// Reads the game state via the provided reader, without checking the version of the save.
+pub fn parse_saved_game_with_version<R: BufRead, H: AsRef<str>>(
+    reader: R,
+    expected_header: H,
+) -> Result<([u32; 2], ParsedGame), LoadGameError> {
+    let ((save_version, parsed, _), error) = parse_saved_game_impl(reader, expected_header, false)?;
+    debug_assert!(error.is_none());
+    Ok((save_version, parsed))
+}
+
+/// Same as `parse_saved_game`, but additionally returns the remaining time of each player
+/// if the game was saved with `save_game_with_clock`.
+pub fn parse_saved_game_with_clock<R: BufRead, H: AsRef<str>>(
+    reader: R,
+    expected_header: H,
+    version: [u32; 2],
+    compatibility_policy: CompatibilityPolicy,
+) -> Result<(ParsedGame, Option<SerializedClock>), LoadGameError> {
+    let ((save_version, parsed, clock), error) =
+        parse_saved_game_impl(reader, expected_header, false)?;
+    debug_assert!(error.is_none());
+    compatibility_policy.check(version, save_version)?;
+    Ok((parsed, clock))
+}
+
+/// Version, parsed game and clock.
+type ParsedSave = ([u32; 2], ParsedGame, Option<SerializedClock>);
+
+/// If `lenient` is set, a syntactic error within the decisions doesn't abort parsing.
+/// Instead, the decisions read so far are returned together with the error.
+fn parse_saved_game_impl<R: BufRead, H: AsRef<str>>(
+    mut reader: R,
+    expected_header: H,
+    lenient: bool,
+) -> Result<(ParsedSave, Option<LoadGameError>), LoadGameError> {
     let mut curr_line = 0;
-    let mut line = String::new();
-    let mut next_line = |buf: &mut String, curr_line: &mut usize| -> Result<usize, LoadGameError> {
-        buf.clear();
-        let result = reader.read_line(buf)?;
-        *curr_line += 1;
-        if buf.ends_with('\n') {
-            buf.pop();
-        }
-        Ok(result)
+    let (save_version, initial_state, num_players) =
+        parse_preamble(&mut reader, &mut curr_line, expected_header.as_ref())?;
+
+    // read decisions
+    let mut result = SerializedLog {
+        log: Vec::new(),
+        redo_stack: Vec::new(),
+        variations: Vec::new(),
+        hashes: Vec::new(),
+        meta: Vec::new(),
+    };
+    let mut clock = None;
+    let parsed = parse_decisions(
+        &mut reader,
+        &mut curr_line,
+        num_players,
+        &mut result,
+        &mut clock,
+    );
+    let error = match parsed {
+        Ok(()) => None,
+        Err(e) if lenient => Some(e),
+        Err(e) => return Err(e),
     };
+    Ok((
+        (save_version, (initial_state, num_players, result), clock),
+        error,
+    ))
+}
+
+/// Version, initial state and number of players.
+type Preamble = ([u32; 2], Vec<(String, String)>, usize);
+
+/// Reads header, version, initial state and number of players.
+fn parse_preamble<R: BufRead>(
+    reader: &mut R,
+    curr_line: &mut usize,
+    expected_header: &str,
+) -> Result<Preamble, LoadGameError> {
+    let mut line = String::new();
 
     // read header and version
-    next_line(&mut line, &mut curr_line)?;
-    let expected = expected_header.as_ref();
+    read_line(reader, &mut line, curr_line)?;
     let header_err = || {
         LoadGameError::from_file(
-            curr_line,
-            format!("Invalid header: {line}, expected: {expected} vX.Y"),
+            *curr_line,
+            format!("Invalid header: {line}, expected: {expected_header} vX.Y"),
         )
     };
-    let version_str = line.split(' ').last().ok_or_else(header_err)?;
-    let header_name = line.strip_suffix(version_str).unwrap().strip_suffix(' ');
+    let version_str = line
+        .strip_prefix(expected_header)
+        .and_then(|rest| rest.strip_prefix(' '))
+        .ok_or_else(header_err)?;
+    let (version_str, escaped) = match version_str.split_once(' ') {
+        Some((version_str, ESCAPED_FORMAT)) => (version_str, true),
+        Some(_) => return Err(header_err()),
+        None => (version_str, false),
+    };
     let version_str = version_str.strip_prefix('v').ok_or_else(header_err)?;
-    let header_name = header_name.ok_or_else(header_err)?;
-    if header_name != expected_header.as_ref() {
-        return Err(header_err());
-    }
     let save_version = version_str
         .split('.')
         .map(str::parse)
         .collect::<Result<Vec<_>, _>>()
         .map_err(|_| header_err())?;
     let save_version: [u32; 2] = save_version.try_into().map_err(|_| header_err())?;
-    if !compatibility_policy.is_compatible(version, save_version) {
-        return Err(LoadGameError::VersionMismatch {
-            game: version,
-            save: save_version,
-        });
-    }
 
     // read initial state
-    next_line(&mut line, &mut curr_line)?;
-    let initial_state =
-        deserialize_initial_state(&line).map_err(|msg| LoadGameError::from_file(curr_line, msg))?;
+    read_line(reader, &mut line, curr_line)?;
+    let initial_state = if escaped {
+        deserialize_initial_state(&line)
+    } else {
+        deserialize_raw_initial_state(&line)
+    };
+    let initial_state = initial_state.map_err(|error| LoadGameError::InvalidInitialState {
+        line: *curr_line,
+        error,
+    })?;
 
     // read number of players
-    next_line(&mut line, &mut curr_line)?;
+    read_line(reader, &mut line, curr_line)?;
     let num_players = line
         .parse::<usize>()
-        .map_err(|_| LoadGameError::from_file(curr_line, "Expected player number"))?;
+        .map_err(|_| LoadGameError::from_file(*curr_line, "Expected player number"))?;
+    Ok((save_version, initial_state, num_players))
+}
 
-    // read decisions
-    let mut result = SerializedLog {
-        log: Vec::new(),
-        redo_stack: Vec::new(),
-    };
+fn read_line<R: BufRead>(
+    reader: &mut R,
+    buf: &mut String,
+    curr_line: &mut usize,
+) -> Result<usize, LoadGameError> {
+    buf.clear();
+    let result = reader.read_line(buf)?;
+    *curr_line += 1;
+    if buf.ends_with('\n') {
+        buf.pop();
+    }
+    Ok(result)
+}
+
+/// Reads the decisions into the log. On error, the decisions read so far are kept
+/// (excluding unterminated variations).
+fn parse_decisions<R: BufRead>(
+    reader: &mut R,
+    curr_line: &mut usize,
+    num_players: usize,
+    result: &mut SerializedLog,
+    clock: &mut Option<SerializedClock>,
+) -> Result<(), LoadGameError> {
+    let mut line = String::new();
     let mut found_current = false;
-    while next_line(&mut line, &mut curr_line)? > 0 {
+    let mut open_variations: Vec<SerializedVariation> = Vec::new();
+    while read_line(reader, &mut line, curr_line)? > 0 {
+        let curr_line = *curr_line;
         if line == CURRENT_STATE && found_current {
             return Err(LoadGameError::from_file(
                 curr_line,
@@ -267,16 +896,77 @@ pub fn parse_saved_game<R: BufRead, H: AsRef<str>>(
         } else if line == CURRENT_STATE {
             found_current = true;
             continue;
+        } else if line == VARIATION_END {
+            let variation = open_variations.pop().ok_or_else(|| {
+                LoadGameError::from_file(curr_line, "Variation end without start")
+            })?;
+            match open_variations.last_mut() {
+                Some(parent) => parent.variations.push(variation),
+                None => result.variations.push(variation),
+            }
+            continue;
+        } else if let Some(times) = line.strip_prefix(CLOCK_START) {
+            if clock.is_some() || !open_variations.is_empty() {
+                return Err(LoadGameError::from_file(
+                    curr_line,
+                    "Clock is only allowed once outside of variations",
+                ));
+            }
+            let times = times
+                .split(CLOCK_SEPARATOR)
+                .map(|time| {
+                    time.parse::<u64>().map_err(|e| {
+                        LoadGameError::from_file(curr_line, format!("Invalid time: {e}"))
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if times.len() != num_players {
+                return Err(LoadGameError::from_file(
+                    curr_line,
+                    format!("Expected {num_players} times, found {}", times.len()),
+                ));
+            }
+            *clock = Some(times);
+            continue;
+        } else if let Some(action) = line.strip_prefix(META_START) {
+            if found_current || !open_variations.is_empty() {
+                return Err(LoadGameError::from_file(
+                    curr_line,
+                    "Meta actions are only allowed before the current state",
+                ));
+            }
+            let action = parse_meta_action(action, curr_line)?;
+            result.meta.push((result.log.len(), action));
+            continue;
+        } else if let Some(variation) = line.strip_prefix(VARIATION_START) {
+            let mut split = variation.split(VARIATION_SEPARATOR);
+            if let (Some(position), Some(index), None) = (split.next(), split.next(), split.next())
+            {
+                open_variations.push(SerializedVariation {
+                    position: parse_number(position, curr_line)?,
+                    index: parse_number(index, curr_line)?,
+                    line: Vec::new(),
+                    variations: Vec::new(),
+                });
+                continue;
+            } else {
+                return Err(LoadGameError::from_file(
+                    curr_line,
+                    format!("Invalid line: {line}, expected: {VARIATION_START}<position>{VARIATION_SEPARATOR}<index>"),
+                ));
+            }
         }
-        let mut split = line.split(PLAYER_SEPARATOR);
+        let (decision, hash) = match line.split_once(HASH_SEPARATOR) {
+            Some((decision, hash)) => (decision, Some(hash)),
+            None => (line.as_str(), None),
+        };
+        let mut split = decision.split(PLAYER_SEPARATOR);
         if let (Some(index), Some(player), None) = (split.next(), split.next(), split.next()) {
-            let index = index
-                .parse::<usize>()
-                .map_err(|e| LoadGameError::from_file(curr_line, format!("Invalid number: {e}")))?;
-            let player = player
-                .parse::<usize>()
-                .map_err(|e| LoadGameError::from_file(curr_line, format!("Invalid number: {e}")))?;
-            if found_current {
+            let index = parse_number(index, curr_line)?;
+            let player = parse_number(player, curr_line)?;
+            if let Some(variation) = open_variations.last_mut() {
+                variation.line.push((index, player));
+            } else if found_current {
                 result.redo_stack.push((index, player));
             } else {
                 result.log.push((index, player));
@@ -287,66 +977,568 @@ pub fn parse_saved_game<R: BufRead, H: AsRef<str>>(
                 format!("Invalid line: {line}, expected: <dec>{PLAYER_SEPARATOR}<player>"),
             ));
         }
+        if let Some(hash) = hash {
+            if found_current || !open_variations.is_empty() {
+                return Err(LoadGameError::from_file(
+                    curr_line,
+                    "State fingerprints are only allowed before the current state",
+                ));
+            }
+            let hash = u64::from_str_radix(hash, 16).map_err(|e| {
+                LoadGameError::from_file(curr_line, format!("Invalid fingerprint: {e}"))
+            })?;
+            result.hashes.push((result.log.len() - 1, hash));
+        }
     }
-    Ok((initial_state, num_players, result))
-}
-
-/// Loads the game state via a function providing the initial state
-pub fn restore_game_state<T: GameData, F>(
-    num_players: usize,
-    create_data: F,
-    log: SerializedLog,
-) -> Result<LoggingEngine<T>, LoadGameError>
-where
-    F: Fn() -> Result<T, String>,
-{
-    let mut result = restore_game_state_impl(num_players, create_data()?, log.log.iter())?;
-    if !log.redo_stack.is_empty() {
-        // apply full log to verify correctness of redo stack
-        restore_game_state_impl(
-            num_players,
-            create_data()?,
-            log.log.iter().chain(log.redo_stack.iter().rev()),
-        )?;
-        result.log_mut().redo_stack = log.redo_stack;
+    if !open_variations.is_empty() {
+        return Err(LoadGameError::from_file(
+            *curr_line,
+            "Variation is not terminated",
+        ));
     }
-    Ok(result)
+    Ok(())
 }
 
-pub fn restore_game_state_impl<'a, T: GameData>(
-    num_players: usize,
-    data: T,
-    log: impl Iterator<Item = &'a (usize, usize)>,
-) -> Result<LoggingEngine<T>, LoadGameError> {
-    let mut engine = LoggingEngine::new_logging(num_players, data);
-    for (i, &(index, player)) in log.enumerate() {
-        let decision = engine
-            .get_decision()
-            .ok_or(LoadGameError::GameAlreadyFinished { decision_nr: i })?;
-        if index >= decision.option_count() {
-            return Err(LoadGameError::InvalidDecisionIndex {
-                decision_nr: i,
-                index,
-                max_index: decision.option_count(),
-            });
-        } else if player != decision.player() {
-            return Err(LoadGameError::UnexpectedPlayer {
-                decision_nr: i,
-                player,
-                expected_player: decision.player(),
-            });
+/// Reads a game state in notation form via the provided reader into key-value pairs
+/// for the initial state and a notation log.
+pub fn parse_notation_game<R: BufRead, H: AsRef<str>>(
+    mut reader: R,
+    expected_header: H,
+    version: [u32; 2],
+    compatibility_policy: CompatibilityPolicy,
+) -> Result<ParsedNotationGame, LoadGameError> {
+    let mut curr_line = 0;
+    let (save_version, initial_state, num_players) =
+        parse_preamble(&mut reader, &mut curr_line, expected_header.as_ref())?;
+    compatibility_policy.check(version, save_version)?;
+
+    let mut result = NotationLog {
+        log: Vec::new(),
+        redo_stack: Vec::new(),
+    };
+    let mut line = String::new();
+    let mut found_current = false;
+    while read_line(&mut reader, &mut line, &mut curr_line)? > 0 {
+        if line == CURRENT_STATE && found_current {
+            return Err(LoadGameError::from_file(
+                curr_line,
+                "Current state is ambiguous",
+            ));
+        } else if line == CURRENT_STATE {
+            found_current = true;
+            continue;
         }
-        match engine.pull() {
-            GameState::PendingDecision(d) => d.apply_option(index),
-            _ => panic!("{}", INTERNAL_ERROR),
+        // the player is separated by the last separator, since the token might contain it
+        let (token, player) = line.rsplit_once(PLAYER_SEPARATOR).ok_or_else(|| {
+            LoadGameError::from_file(
+                curr_line,
+                format!("Invalid line: {line}, expected: <token>{PLAYER_SEPARATOR}<player>"),
+            )
+        })?;
+        let token = unescape(token).map_err(|e| LoadGameError::from_file(curr_line, e))?;
+        let entry = (parse_number(player, curr_line)?, token);
+        if found_current {
+            result.redo_stack.push(entry);
+        } else {
+            result.log.push(entry);
+        }
+    }
+    Ok((initial_state, num_players, result))
+}
+
+fn parse_number(input: &str, curr_line: usize) -> Result<usize, LoadGameError> {
+    input
+        .parse::<usize>()
+        .map_err(|e| LoadGameError::from_file(curr_line, format!("Invalid number: {e}")))
+}
+
+/// Loads the game state via a function providing the initial state.
+///
+/// The state fingerprints of the log are verified and kept in the restored log.
+/// Use `restore_verified_game_state` to also record fingerprints of new decisions.
+///
+/// The loading functions return a `LoggingEngine`, which can be converted to a
+/// `SendEngine` with `Engine::try_convert`.
+pub fn restore_game_state<T: GameData + StateHash, F>(
+    num_players: usize,
+    create_data: F,
+    log: SerializedLog,
+) -> Result<LoggingEngine<T>, LoadGameError>
+where
+    F: Fn() -> Result<T, String>,
+{
+    restore_with_log(
+        num_players,
+        create_data,
+        log,
+        EventLog::new(),
+        Some(T::state_hash),
+    )
+}
+
+/// Loads the game state via a function providing the initial state and verifies the
+/// state fingerprints. The restored log records fingerprints every `hash_interval` decisions.
+pub fn restore_verified_game_state<T: GameData + StateHash, F>(
+    num_players: usize,
+    create_data: F,
+    log: SerializedLog,
+    hash_interval: usize,
+) -> Result<LoggingEngine<T>, LoadGameError>
+where
+    F: Fn() -> Result<T, String>,
+{
+    restore_with_log(
+        num_players,
+        create_data,
+        log,
+        EventLog::with_state_hashes(hash_interval),
+        Some(T::state_hash),
+    )
+}
+
+/// Loads the game state into an engine with the given event log. The state fingerprints
+/// are verified and kept if a hash function is provided, otherwise they are ignored.
+pub(crate) fn restore_with_log<T: GameData, F>(
+    num_players: usize,
+    create_data: F,
+    log: SerializedLog,
+    event_log: EventLog<T>,
+    state_hash: Option<fn(&T) -> u64>,
+) -> Result<LoggingEngine<T>, LoadGameError>
+where
+    F: Fn() -> Result<T, String>,
+{
+    let mut result = LoggingEngine::with_listener(num_players, create_data()?, event_log);
+    let mut hashes = log.hashes.iter().peekable();
+    replay_with_meta(&mut result, &log.log, &log.meta, |decision_nr, engine| {
+        let Some(state_hash) = state_hash else {
+            return Ok(());
         };
+        if let Some(&(_, hash)) = hashes.next_if(|&&(i, _)| i == decision_nr) {
+            if engine.pending_chain_len() > 0 || state_hash(engine.data()) != hash {
+                return Err(LoadGameError::StateMismatch { decision_nr });
+            }
+            engine.log_mut().record_state_hash(hash);
+        }
+        Ok(())
+    })?;
+    restore_redo_information(&mut result, num_players, &create_data, &log)?;
+    Ok(result)
+}
+
+/// Result of a lenient restore, see `restore_game_state_lenient`.
+pub struct RecoveredGame<T: GameData> {
+    /// Engine with all valid parts of the log applied.
+    pub engine: LoggingEngine<T>,
+    /// The first error that was encountered, if any.
+    pub error: Option<LoadGameError>,
+    /// Parts of the log that were not applied, i.e. the rest of the log after the first invalid
+    /// decision, the invalid part of the redo stack and invalid variations.
+    pub unapplied: SerializedLog,
+}
+
+/// Loads the game state like `restore_game_state`, but doesn't fail on semantic errors
+/// within the log. Instead, the engine is replayed up to the last valid decision and
+/// returned together with the error and the unapplied rest of the log. Likewise, the
+/// redo stack is restored up to the last valid decision and invalid variations are skipped.
+///
+/// Only fails if the initial state can not be created.
+pub fn restore_game_state_lenient<T: GameData, F>(
+    num_players: usize,
+    create_data: F,
+    log: SerializedLog,
+) -> Result<RecoveredGame<T>, LoadGameError>
+where
+    F: Fn() -> Result<T, String>,
+{
+    let mut engine = LoggingEngine::new_logging(num_players, create_data()?);
+    let (applied, error) = replay_lenient(&mut engine, &log.log, &log.meta);
+    let mut unapplied = SerializedLog {
+        log: log.log[applied..].to_vec(),
+        redo_stack: Vec::new(),
+        variations: Vec::new(),
+        hashes: Vec::new(),
+        meta: Vec::new(),
+    };
+    if error.is_some() {
+        // redo information depends on the complete log
+        unapplied.redo_stack = log.redo_stack;
+        unapplied.variations = log.variations;
+        // the meta actions before the error were applied, no matter their position
+        let applied_meta = engine.serialized_log().meta.len();
+        unapplied.meta = log.meta[applied_meta..].to_vec();
+        return Ok(RecoveredGame {
+            engine,
+            error,
+            unapplied,
+        });
+    }
+    if log.redo_stack.is_empty() && log.variations.is_empty() {
+        return Ok(RecoveredGame {
+            engine,
+            error: None,
+            unapplied,
+        });
+    }
+
+    // find the valid part of the redo stack, consisting of complete decisions
+    let pending = engine.pending_chain_len();
+    let prefix = &log.log[..log.log.len() - pending];
+    let mut redo_engine = restore_game_state_impl(num_players, create_data()?, prefix.iter())?;
+    let redo_line = log.redo_stack.iter().rev().copied().collect::<Vec<_>>();
+    let (applied, mut error) = replay_lenient(&mut redo_engine, &redo_line, &[]);
+    let valid = applied - redo_engine.pending_chain_len();
+    let split = log.redo_stack.len() - valid;
+    let mut valid_log = SerializedLog {
+        log: log.log,
+        redo_stack: log.redo_stack[split..].to_vec(),
+        variations: Vec::new(),
+        hashes: Vec::new(),
+        meta: Vec::new(),
+    };
+    unapplied.redo_stack = log.redo_stack[..split].to_vec();
+
+    // each variation is only restored if it is valid by itself
+    for variation in log.variations {
+        valid_log.variations.push(variation);
+        if let Err(e) = restore_redo_information(&mut engine, num_players, &create_data, &valid_log)
+        {
+            error.get_or_insert(e);
+            unapplied
+                .variations
+                .push(valid_log.variations.pop().expect(INTERNAL_ERROR));
+        }
+    }
+    restore_redo_information(&mut engine, num_players, &create_data, &valid_log)?;
+    Ok(RecoveredGame {
+        engine,
+        error,
+        unapplied,
+    })
+}
+
+/// Applies options and meta actions until an invalid one is found. Returns the number
+/// of applied options and the error, if any.
+fn replay_lenient<T: GameData>(
+    engine: &mut LoggingEngine<T>,
+    log: &[(usize, usize)],
+    meta: &[(usize, MetaAction)],
+) -> (usize, Option<LoadGameError>) {
+    let mut applied = 0;
+    let result = replay_with_meta(engine, log, meta, |_, _| {
+        applied += 1;
+        Ok(())
+    });
+    (applied, result.err())
+}
+
+fn restore_redo_information<T: GameData, F>(
+    result: &mut LoggingEngine<T>,
+    num_players: usize,
+    create_data: &F,
+    log: &SerializedLog,
+) -> Result<(), LoadGameError>
+where
+    F: Fn() -> Result<T, String>,
+{
+    if !log.redo_stack.is_empty() || !log.variations.is_empty() {
+        // a pending follow-up decision is not part of the variation tree
+        let pending = result.pending_chain_len();
+        let mut line = log.log[..log.log.len() - pending].to_vec();
+        line.extend(log.redo_stack.iter().rev());
+        // apply full line to verify correctness of redo stack and variations
+        let (chains, side_variations) =
+            build_line(num_players, create_data, &[], &line, &log.variations)?;
+        let (variations, path) = assemble_variations(&chains, side_variations);
+        let num_decisions = result.log().decision_chains().len();
+        result
+            .log_mut()
+            .restore_variations(variations, &path[..num_decisions]);
+    }
+    Ok(())
+}
+
+/// Decision chains of the given line (after the prefix) and the side variations
+/// at each position of the line.
+type LineWithVariations = (Vec<Vec<(usize, usize)>>, Vec<Vec<(usize, Variation)>>);
+
+fn build_line<T: GameData, F>(
+    num_players: usize,
+    create_data: &F,
+    prefix: &[(usize, usize)],
+    line: &[(usize, usize)],
+    variations: &[SerializedVariation],
+) -> Result<LineWithVariations, LoadGameError>
+where
+    F: Fn() -> Result<T, String>,
+{
+    let engine = restore_game_state_impl(
+        num_players,
+        create_data()?,
+        prefix.iter().chain(line.iter()),
+    )?;
+    let mut chains = engine.log().decision_chains();
+    drain_prefix_chains(&mut chains, prefix.len());
+
+    let mut side_variations = (0..=chains.len()).map(|_| Vec::new()).collect::<Vec<_>>();
+    for variation in variations {
+        if variation.position > chains.len() {
+            return Err(LoadGameError::InvalidVariation {
+                position: variation.position,
+                index: variation.index,
+            });
+        }
+        let mut variation_prefix = prefix.to_vec();
+        variation_prefix.extend(chains[..variation.position].iter().flatten());
+        let result = build_variation(num_players, create_data, &variation_prefix, variation)?;
+        side_variations[variation.position].push((variation.index, result));
     }
+    Ok((chains, side_variations))
+}
+
+/// Removes the chains that consist of the first `prefix_len` options.
+pub(crate) fn drain_prefix_chains(chains: &mut Vec<Vec<(usize, usize)>>, prefix_len: usize) {
+    let mut len = 0;
+    let mut num_prefix_chains = 0;
+    while len < prefix_len {
+        len += chains[num_prefix_chains].len();
+        num_prefix_chains += 1;
+    }
+    chains.drain(..num_prefix_chains);
+}
+
+fn build_variation<T: GameData, F>(
+    num_players: usize,
+    create_data: &F,
+    prefix: &[(usize, usize)],
+    variation: &SerializedVariation,
+) -> Result<Variation, LoadGameError>
+where
+    F: Fn() -> Result<T, String>,
+{
+    let (chains, side_variations) = build_line(
+        num_players,
+        create_data,
+        prefix,
+        &variation.line,
+        &variation.variations,
+    )?;
+    let num_options = chains.iter().map(Vec::len).sum::<usize>();
+    if chains.is_empty() || num_options != variation.line.len() || !side_variations[0].is_empty() {
+        return Err(LoadGameError::InvalidVariation {
+            position: variation.position,
+            index: variation.index,
+        });
+    }
+    let (mut result, _) = assemble_variations(&chains, side_variations);
+    debug_assert!(result.len() == 1);
+    Ok(result.pop().expect(INTERNAL_ERROR))
+}
+
+/// Builds the continuations at the start of the line and the index of the line's
+/// decision within the continuations at each position.
+fn assemble_variations(
+    chains: &[Vec<(usize, usize)>],
+    mut side_variations: Vec<Vec<(usize, Variation)>>,
+) -> (Vec<Variation>, Vec<usize>) {
+    let mut sorted_siblings = |position: usize| {
+        let mut siblings = mem::take(&mut side_variations[position]);
+        siblings.sort_by_key(|&(index, _)| index);
+        siblings
+    };
+    let mut continuations = sorted_siblings(chains.len())
+        .into_iter()
+        .map(|(_, v)| v)
+        .collect::<Vec<_>>();
+    let mut path = vec![0; chains.len()];
+    for (position, chain) in chains.iter().enumerate().rev() {
+        let siblings = sorted_siblings(position);
+        // the line uses the lowest index that is not used by a side variation
+        let mut line_index = 0;
+        for &(index, _) in siblings.iter() {
+            if index == line_index {
+                line_index += 1;
+            } else if index > line_index {
+                break;
+            }
+        }
+        let mut current = siblings.into_iter().map(|(_, v)| v).collect::<Vec<_>>();
+        current.insert(line_index, Variation::new(chain.clone(), continuations));
+        path[position] = line_index;
+        continuations = current;
+    }
+    (continuations, path)
+}
+
+pub fn restore_game_state_impl<'a, T: GameData>(
+    num_players: usize,
+    data: T,
+    log: impl Iterator<Item = &'a (usize, usize)>,
+) -> Result<LoggingEngine<T>, LoadGameError> {
+    let mut engine = LoggingEngine::new_logging(num_players, data);
+    replay_log(&mut engine, log, |_, _| Ok(()))?;
     Ok(engine)
 }
 
+/// Loads the game state from a notation log via a function providing the initial state.
+/// Each token is resolved against the pending decisions when it is replayed.
+pub fn restore_game_state_from_notation<T: MoveNotation, F>(
+    num_players: usize,
+    create_data: F,
+    log: NotationLog,
+) -> Result<LoggingEngine<T>, LoadGameError>
+where
+    F: Fn() -> Result<T, String>,
+{
+    let mut engine = LoggingEngine::new_logging(num_players, create_data()?);
+    let mut moves = Vec::new();
+    let line = log.log.iter().chain(log.redo_stack.iter().rev());
+    for (move_nr, (player, token)) in line.enumerate() {
+        moves.push(read_move(&mut engine, move_nr, *player, token)?);
+    }
+    let mut redo_moves = moves.split_off(log.log.len());
+    redo_moves.reverse();
+    let serialized = SerializedLog {
+        log: moves.concat(),
+        redo_stack: redo_moves
+            .into_iter()
+            .flat_map(|m| m.into_iter().rev())
+            .collect(),
+        variations: Vec::new(),
+        hashes: Vec::new(),
+        meta: Vec::new(),
+    };
+    restore_with_log(num_players, create_data, serialized, EventLog::new(), None)
+}
+
+/// Resolves and applies the options of a move.
+fn read_move<T: MoveNotation>(
+    engine: &mut LoggingEngine<T>,
+    move_nr: usize,
+    player: usize,
+    token: &str,
+) -> Result<Vec<(usize, usize)>, LoadGameError> {
+    let mut chain = Vec::new();
+    let mut options = Vec::new();
+    loop {
+        let decision = pending_decision(engine, move_nr)?;
+        let context = decision.context();
+        let index = decision
+            .data()
+            .read_notation(token, &chain, &context)
+            .map_err(|msg| LoadGameError::InvalidNotation {
+                move_nr,
+                token: token.to_string(),
+                msg,
+            })?;
+        // the player of the move is the player of its first decision
+        let player = if chain.is_empty() {
+            player
+        } else {
+            decision.player()
+        };
+        check_option(&decision, move_nr, index, player)?;
+        decision.apply_option(index);
+        options.push((index, player));
+        chain.push((context, index));
+        if engine.pending_chain_len() == 0 {
+            return Ok(options);
+        }
+    }
+}
+
+/// Applies the given options and calls `verify` after each of them.
+fn replay_log<'a, T: GameData, L: EventListener<T>, V>(
+    engine: &mut Engine<T, L>,
+    log: impl Iterator<Item = &'a (usize, usize)>,
+    mut verify: V,
+) -> Result<(), LoadGameError>
+where
+    V: FnMut(usize, &mut Engine<T, L>) -> Result<(), LoadGameError>,
+{
+    for (i, &(index, player)) in log.enumerate() {
+        let decision = pending_decision(engine, i)?;
+        check_option(&decision, i, index, player)?;
+        decision.apply_option(index);
+        verify(i, engine)?;
+    }
+    Ok(())
+}
+
+/// Same as `replay_log`, but applies each meta action after the corresponding number of options.
+/// Meta actions after the end of the log are applied at its end.
+pub(crate) fn replay_with_meta<T: GameData, L: EventListener<T>, V>(
+    engine: &mut Engine<T, L>,
+    log: &[(usize, usize)],
+    meta: &[(usize, MetaAction)],
+    mut verify: V,
+) -> Result<(), LoadGameError>
+where
+    V: FnMut(usize, &mut Engine<T, L>) -> Result<(), LoadGameError>,
+{
+    let mut meta = meta.iter().peekable();
+    let mut apply_meta = |engine: &mut Engine<T, L>, position: usize| {
+        while let Some(&(decision_nr, action)) = meta.next_if(|&&(n, _)| n <= position) {
+            engine
+                .apply_meta_action(action)
+                .map_err(|error| LoadGameError::InvalidMetaAction {
+                    decision_nr,
+                    action,
+                    error,
+                })?;
+        }
+        Ok(())
+    };
+    apply_meta(engine, 0)?;
+    replay_log(engine, log.iter(), |decision_nr, engine| {
+        verify(decision_nr, engine)?;
+        apply_meta(engine, decision_nr + 1)
+    })?;
+    apply_meta(engine, usize::MAX)
+}
+
+/// The pending decision of the engine, or an error if the game is already finished.
+pub(crate) fn pending_decision<T: GameData, L: EventListener<T>>(
+    engine: &mut Engine<T, L>,
+    decision_nr: usize,
+) -> Result<PendingDecision<'_, T, L>, LoadGameError> {
+    if engine.meta_state().is_paused() {
+        return Err(LoadGameError::GamePaused { decision_nr });
+    }
+    match engine.pull() {
+        GameState::PendingDecision(decision) => Ok(decision),
+        GameState::Finished(_) => Err(LoadGameError::GameAlreadyFinished { decision_nr }),
+        GameState::PendingEffect(_) => panic!("{}", INTERNAL_ERROR),
+    }
+}
+
+/// Verifies that the option can be selected by the player.
+pub(crate) fn check_option<T: GameData, L: EventListener<T>>(
+    decision: &PendingDecision<T, L>,
+    decision_nr: usize,
+    index: usize,
+    player: usize,
+) -> Result<(), LoadGameError> {
+    if index >= decision.option_count() {
+        Err(LoadGameError::InvalidDecisionIndex {
+            decision_nr,
+            index,
+            max_index: decision.option_count(),
+        })
+    } else if player != decision.player() && decision.chance_weights().is_none() {
+        Err(LoadGameError::UnexpectedPlayer {
+            decision_nr,
+            player,
+            expected_player: decision.player(),
+        })
+    } else {
+        Ok(())
+    }
+}
+
 /// Loads the game state via the provided reader and a function to interpret the key-value pairs
-/// representing the initial state.
-pub fn load_game<T: GameData, R: BufRead, H: AsRef<str>, F>(
+/// representing the initial state. State fingerprints are verified, see `restore_game_state`.
+pub fn load_game<T: GameData + StateHash, R: BufRead, H: AsRef<str>, F>(
     reader: R,
     expected_header: H,
     version: [u32; 2],
@@ -360,3 +1552,551 @@ where
         parse_saved_game(reader, expected_header, version, compatibility_policy)?;
     restore_game_state(num_players, || parse_initial_state(&initial_state), log)
 }
+
+/// Loads the game state in notation form via the provided reader and a function to interpret
+/// the key-value pairs representing the initial state.
+pub fn load_game_from_notation<T: MoveNotation, R: BufRead, H: AsRef<str>, F>(
+    reader: R,
+    expected_header: H,
+    version: [u32; 2],
+    compatibility_policy: CompatibilityPolicy,
+    parse_initial_state: F,
+) -> Result<LoggingEngine<T>, LoadGameError>
+where
+    F: Fn(&[(String, String)]) -> Result<T, String>,
+{
+    let (initial_state, num_players, log) =
+        parse_notation_game(reader, expected_header, version, compatibility_policy)?;
+    restore_game_state_from_notation(num_players, || parse_initial_state(&initial_state), log)
+}
+
+/// Loads the game state via the provided reader and a function to interpret the key-value pairs
+/// representing the initial state, recovering as much of the game as possible
+/// (see `restore_game_state_lenient`).
+///
+/// A syntactic error within the decisions (e.g. for a truncated file) is reported as error
+/// of the result, after the decisions before it are applied. Errors in the header, the
+/// initial state or the number of players can't be recovered from.
+pub fn load_game_lenient<T: GameData, R: BufRead, H: AsRef<str>, F>(
+    reader: R,
+    expected_header: H,
+    version: [u32; 2],
+    compatibility_policy: CompatibilityPolicy,
+    parse_initial_state: F,
+) -> Result<RecoveredGame<T>, LoadGameError>
+where
+    F: Fn(&[(String, String)]) -> Result<T, String>,
+{
+    let ((save_version, (initial_state, num_players, log), _), parse_error) =
+        parse_saved_game_impl(reader, expected_header, true)?;
+    compatibility_policy.check(version, save_version)?;
+    let mut result =
+        restore_game_state_lenient(num_players, || parse_initial_state(&initial_state), log)?;
+    if let Some(parse_error) = parse_error {
+        // the semantic error occurs earlier than the syntactic error
+        result.error.get_or_insert(parse_error);
+    }
+    Ok(result)
+}
+
+/// Loads the game state via the provided reader and a function to interpret the key-value pairs
+/// representing the initial state. Saves of older versions are upgraded with the given migrations
+/// before the compatibility policy is checked against the resulting version.
+///
+/// State fingerprints are ignored, since a migration might change the game state.
+pub fn load_migrated_game<T: GameData, R: BufRead, H: AsRef<str>, F>(
+    reader: R,
+    expected_header: H,
+    version: [u32; 2],
+    compatibility_policy: CompatibilityPolicy,
+    migrations: &MigrationRegistry<T>,
+    parse_initial_state: F,
+) -> Result<LoggingEngine<T>, LoadGameError>
+where
+    F: Fn(&[(String, String)]) -> Result<T, String>,
+{
+    let (save_version, (mut initial_state, num_players, log)) =
+        parse_saved_game_with_version(reader, expected_header)?;
+    let migrated_version = migrations.migrate_initial_state(save_version, &mut initial_state)?;
+    compatibility_policy.check(version, migrated_version)?;
+    let create_data = || parse_initial_state(&initial_state);
+    let log = migrations.migrate_log(save_version, num_players, create_data, log)?;
+    restore_with_log(num_players, create_data, log, EventLog::new(), None)
+}
+
+/// Loads the game state via the provided reader and a function to interpret the key-value pairs
+/// representing the initial state. State fingerprints are verified, see `restore_verified_game_state`.
+pub fn load_verified_game<T: GameData + StateHash, R: BufRead, H: AsRef<str>, F>(
+    reader: R,
+    expected_header: H,
+    version: [u32; 2],
+    compatibility_policy: CompatibilityPolicy,
+    parse_initial_state: F,
+    hash_interval: usize,
+) -> Result<LoggingEngine<T>, LoadGameError>
+where
+    F: Fn(&[(String, String)]) -> Result<T, String>,
+{
+    let (initial_state, num_players, log) =
+        parse_saved_game(reader, expected_header, version, compatibility_policy)?;
+    restore_verified_game_state(
+        num_players,
+        || parse_initial_state(&initial_state),
+        log,
+        hash_interval,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        engine::{logging::EventLog, meta::MetaAction, LoggingEngine},
+        test::{play, RevCountingGame},
+        MoveNotation, StateHash,
+    };
+
+    use super::{
+        deserialize_initial_state, load_game, load_game_from_notation, load_game_lenient,
+        load_verified_game, notation_log, parse_saved_game, restore_game_state_lenient, save_game,
+        save_game_with_notation, serialize_initial_state, CompatibilityPolicy, InitialStateError,
+        LoadGameError, SerializedLog,
+    };
+
+    fn save_and_load(
+        engine: &LoggingEngine<RevCountingGame>,
+    ) -> (String, LoggingEngine<RevCountingGame>) {
+        let mut buffer = Vec::new();
+        save_game(
+            &mut buffer,
+            "Counting",
+            [1, 0],
+            [("finished_at".to_string(), "100".to_string())],
+            2,
+            engine.serialized_log(),
+        )
+        .unwrap();
+        let loaded = load_game(
+            buffer.as_slice(),
+            "Counting",
+            [1, 0],
+            CompatibilityPolicy::MinorEqual,
+            |state| Ok(RevCountingGame::new(state[0].1.parse().unwrap())),
+        )
+        .unwrap();
+        (String::from_utf8(buffer).unwrap(), loaded)
+    }
+
+    #[test]
+    fn initial_state_test() {
+        let state = vec![
+            ("board".to_string(), "..x\n.o.\n\tx..\r\n".to_string()),
+            ("player name".to_string(), "Some \\ Name\\s".to_string()),
+            (String::new(), "\\e".to_string()),
+            ("form\x0Cfeed".to_string(), String::new()),
+        ];
+        let serialized = serialize_initial_state(state.clone());
+        assert!(!serialized.contains('\n'));
+        assert_eq!(deserialize_initial_state(&serialized), Ok(state));
+
+        // old format
+        assert_eq!(
+            deserialize_initial_state("size 7  mode fast"),
+            Ok(vec![
+                ("size".to_string(), "7".to_string()),
+                ("mode".to_string(), "fast".to_string())
+            ])
+        );
+        assert_eq!(
+            deserialize_initial_state("size 7 mode"),
+            Err(InitialStateError::MissingValue {
+                key: "mode".to_string()
+            })
+        );
+        assert_eq!(
+            deserialize_initial_state("size 7\\"),
+            Err(InitialStateError::InvalidEscape {
+                entry: "7\\".to_string()
+            })
+        );
+        assert!(deserialize_initial_state("size \\x").is_err());
+    }
+
+    #[test]
+    fn legacy_initial_state_test() {
+        // saves without format marker are read without unescaping
+        let content = "Counting v1.0\npath C:\\games\\save name \\e\n2\n0P0\nC\n";
+        let (initial_state, num_players, log) = parse_saved_game(
+            content.as_bytes(),
+            "Counting",
+            [1, 0],
+            CompatibilityPolicy::MinorEqual,
+        )
+        .unwrap();
+        assert_eq!(
+            initial_state,
+            vec![
+                ("path".to_string(), "C:\\games\\save".to_string()),
+                ("name".to_string(), "\\e".to_string())
+            ]
+        );
+        assert_eq!(num_players, 2);
+        assert_eq!(log.log, vec![(0, 0)]);
+
+        let mut buffer = Vec::new();
+        save_game(
+            &mut buffer,
+            "Counting",
+            [1, 0],
+            initial_state.clone(),
+            2,
+            log,
+        )
+        .unwrap();
+        let content = String::from_utf8(buffer).unwrap();
+        assert!(content.starts_with("Counting v1.0 f2\npath C:\\\\games\\\\save name \\\\e\n"));
+        let (reloaded, _, _) = parse_saved_game(
+            content.as_bytes(),
+            "Counting",
+            [1, 0],
+            CompatibilityPolicy::MinorEqual,
+        )
+        .unwrap();
+        assert_eq!(reloaded, initial_state);
+    }
+
+    #[test]
+    fn save_variations_test() {
+        let mut engine = LoggingEngine::new_logging(2, RevCountingGame::new(100));
+        play(&mut engine, &[0, 0, 1, 0, 0]);
+        assert!(engine.undo_last_decision());
+        assert!(engine.undo_last_decision());
+        play(&mut engine, &[1, 1, 1, 0]);
+        assert!(engine.undo_last_decision());
+        play(&mut engine, &[0]);
+        assert!(engine.undo_last_decision());
+        assert!(engine.undo_last_decision());
+        assert!(engine.undo_last_decision());
+        assert!(engine.redo_decision());
+        assert!(engine.redo_variation(1));
+        play(&mut engine, &[1, 1]);
+        assert_eq!(engine.data().0.history, vec![1, 1, 2, 3]);
+
+        let (content, loaded) = save_and_load(&engine);
+        assert_eq!(
+            content,
+            "Counting v1.0 f2\nfinished_at 100\n2\n0P0\n0P1\n1P0\n0P0\n1P1\n1P1\nC\n\
+             V2:0\n1P0\n1P0\n0P1\nV1:1\n1P1\n0P1\nE\nE\nV3:1\n0P1\nE\n"
+        );
+        assert_eq!(loaded.data(), engine.data());
+        assert_eq!(loaded.serialized_log(), engine.serialized_log());
+        assert_eq!(loaded.variations(), engine.variations());
+        assert_eq!(
+            loaded.log().decision_chains(),
+            engine.log().decision_chains()
+        );
+    }
+
+    #[test]
+    fn state_hash_test() {
+        let log = EventLog::with_state_hashes(2);
+        let mut engine = LoggingEngine::with_listener(2, RevCountingGame::new(100), log);
+        play(&mut engine, &[0, 1, 0, 1, 1, 0]);
+        assert!(engine.undo_last_decision());
+        play(&mut engine, &[1, 0]);
+        assert!(engine.undo_last_decision());
+        assert_eq!(
+            engine.serialized_log().hashes,
+            vec![(2, 17 * 31 * 31 + 31 + 2)]
+        );
+        let (content, loaded) = save_and_load(&engine);
+        assert!(content.contains("\n0P1#"));
+        // the fingerprints are kept by a plain load
+        assert_eq!(loaded.serialized_log(), engine.serialized_log());
+        assert_eq!(save_and_load(&loaded).0, content);
+
+        let load = |content: &str| {
+            load_verified_game(
+                content.as_bytes(),
+                "Counting",
+                [1, 0],
+                CompatibilityPolicy::MinorEqual,
+                |state| Ok(RevCountingGame::new(state[0].1.parse().unwrap())),
+                1,
+            )
+        };
+        let loaded = load(&content).unwrap();
+        assert_eq!(loaded.data(), engine.data());
+        assert_eq!(
+            loaded.serialized_log().hashes,
+            vec![
+                (0, 17 * 31 + 1),
+                (2, 17 * 31 * 31 + 31 + 2),
+                (4, loaded.data().state_hash())
+            ]
+        );
+
+        let modified = content.replace("\n0P1#", "\n0P1#1");
+        assert!(matches!(
+            load(&modified),
+            Err(LoadGameError::StateMismatch { decision_nr: 2 })
+        ));
+        let modified = content.replace("\n0P0\n", "\n0P0#0\n");
+        assert!(matches!(
+            load(&modified),
+            Err(LoadGameError::StateMismatch { decision_nr: 0 })
+        ));
+        assert!(matches!(
+            load_game(
+                modified.as_bytes(),
+                "Counting",
+                [1, 0],
+                CompatibilityPolicy::MinorEqual,
+                |state| Ok(RevCountingGame::new(state[0].1.parse().unwrap())),
+            ),
+            Err(LoadGameError::StateMismatch { decision_nr: 0 })
+        ));
+    }
+
+    #[test]
+    fn lenient_test() {
+        let mut engine = LoggingEngine::new_logging(2, RevCountingGame::new(100));
+        play(&mut engine, &[0, 0, 1, 0, 0]);
+        assert!(engine.undo_last_decision());
+        assert!(engine.undo_last_decision());
+        play(&mut engine, &[1, 1, 0]);
+        assert!(engine.undo_last_decision());
+        assert!(engine.undo_last_decision());
+        let (content, _) = save_and_load(&engine);
+        assert!(content.ends_with("0P0\n0P1\nC\n0P1\n1P0\n1P0\nV2:1\n1P0\n0P0\n0P1\nE\n"));
+
+        let load = |content: &str| {
+            load_game_lenient(
+                content.as_bytes(),
+                "Counting",
+                [1, 0],
+                CompatibilityPolicy::MinorEqual,
+                |state| Ok(RevCountingGame::new(state[0].1.parse().unwrap())),
+            )
+            .unwrap()
+        };
+        let recovered = load(&content);
+        assert!(recovered.error.is_none());
+        assert_eq!(recovered.engine.serialized_log(), engine.serialized_log());
+
+        // invalid decision in the log
+        let mut recovered = load(&content.replace("0P1\nC", "3P1\nC"));
+        assert!(matches!(
+            recovered.error,
+            Some(LoadGameError::InvalidDecisionIndex {
+                decision_nr: 1,
+                index: 3,
+                ..
+            })
+        ));
+        assert_eq!(recovered.engine.data().0.history, vec![1]);
+        assert!(!recovered.engine.redo_decision());
+        assert_eq!(recovered.unapplied.log, vec![(3, 1)]);
+        assert_eq!(recovered.unapplied.redo_stack, vec![(0, 1), (1, 0), (1, 0)]);
+        assert_eq!(recovered.unapplied.variations.len(), 1);
+
+        // invalid redo stack
+        let mut recovered = load(&content.replace("C\n0P1\n", "C\n0P0\n"));
+        assert!(matches!(
+            recovered.error,
+            Some(LoadGameError::UnexpectedPlayer { decision_nr: 2, .. })
+        ));
+        assert_eq!(recovered.engine.data(), engine.data());
+        assert_eq!(recovered.unapplied.log, vec![]);
+        assert_eq!(recovered.unapplied.redo_stack, vec![(0, 0)]);
+        assert_eq!(recovered.engine.variations().len(), 2);
+        assert!(recovered.engine.redo_decision());
+        assert!(!recovered.engine.redo_decision());
+
+        // invalid variation
+        let recovered = load(&content.replace("0P0\n0P1\nE", "0P0\n0P0\nE"));
+        assert!(matches!(
+            recovered.error,
+            Some(LoadGameError::UnexpectedPlayer { decision_nr: 4, .. })
+        ));
+        assert_eq!(
+            recovered.engine.serialized_log().redo_stack,
+            engine.serialized_log().redo_stack
+        );
+        assert_eq!(recovered.engine.variations().len(), 1);
+        assert_eq!(recovered.unapplied.variations.len(), 1);
+
+        // truncated file
+        let truncated = &content[..content.find("0P1\nC").unwrap() + 1];
+        let recovered = load(truncated);
+        assert!(matches!(
+            recovered.error,
+            Some(LoadGameError::InvalidFileContent { line: 5, .. })
+        ));
+        assert_eq!(recovered.engine.data().0.history, vec![1]);
+        let truncated = &content[..content.find("1P0\nV").unwrap()];
+        let recovered = load(truncated);
+        assert!(matches!(
+            recovered.error,
+            Some(LoadGameError::UnexpectedPlayer { .. })
+        ));
+        assert_eq!(recovered.engine.data(), engine.data());
+        assert_eq!(recovered.unapplied.redo_stack, vec![(0, 1), (1, 0)]);
+    }
+
+    #[test]
+    fn lenient_meta_test() {
+        let log = SerializedLog {
+            log: vec![(0, 0), (0, 1)],
+            redo_stack: Vec::new(),
+            variations: Vec::new(),
+            hashes: Vec::new(),
+            meta: vec![
+                (1, MetaAction::OfferDraw(1)),
+                // the own offer can't be accepted
+                (1, MetaAction::AcceptDraw(1)),
+                (1, MetaAction::Pause(0)),
+                (2, MetaAction::Resign(0)),
+            ],
+        };
+        let recovered =
+            restore_game_state_lenient(2, || Ok(RevCountingGame::new(100)), log.clone()).unwrap();
+        assert!(matches!(
+            recovered.error,
+            Some(LoadGameError::InvalidMetaAction {
+                decision_nr: 1,
+                action: MetaAction::AcceptDraw(1),
+                ..
+            })
+        ));
+        assert_eq!(recovered.engine.meta_state().draw_offer(), Some(1));
+        assert_eq!(recovered.engine.serialized_log().meta, log.meta[..1]);
+        assert_eq!(recovered.unapplied.log, log.log[1..]);
+        assert_eq!(recovered.unapplied.meta, log.meta[1..]);
+    }
+
+    impl MoveNotation for RevCountingGame {
+        fn write_notation(&self, chain: &[((), usize)]) -> String {
+            match chain {
+                [(_, 0)] => "+1".to_string(),
+                [_, (_, i)] => format!("+{}", i + 2),
+                _ => unreachable!(),
+            }
+        }
+
+        fn read_notation(
+            &self,
+            token: &str,
+            chain: &[((), usize)],
+            _context: &(),
+        ) -> Result<usize, String> {
+            match (token, chain.len()) {
+                ("+1", 0) => Ok(0),
+                ("+2" | "+3", 0) => Ok(1),
+                ("+2", 1) => Ok(0),
+                ("+3", 1) => Ok(1),
+                _ => Err("unknown move".to_string()),
+            }
+        }
+    }
+
+    #[test]
+    fn notation_test() {
+        let mut engine = LoggingEngine::new_logging(2, RevCountingGame::new(100));
+        play(&mut engine, &[0, 1, 1, 0, 1, 0]);
+        assert!(engine.undo_last_decision());
+        assert!(engine.undo_last_decision());
+        let create_data = || Ok(RevCountingGame::new(100));
+        let log = notation_log(2, create_data, &engine.serialized_log()).unwrap();
+        assert_eq!(log.log, vec![(0, "+1".to_string()), (1, "+3".to_string())]);
+        let mut buffer = Vec::new();
+        save_game_with_notation(
+            &mut buffer,
+            "Counting",
+            [1, 0],
+            [("finished_at".to_string(), "100".to_string())],
+            2,
+            log,
+        )
+        .unwrap();
+        let content = String::from_utf8(buffer).unwrap();
+        assert_eq!(
+            content,
+            "Counting v1.0 f2\nfinished_at 100\n2\n+1P0\n+3P1\nC\n+2P1\n+1P0\n"
+        );
+
+        let load = |content: &str| {
+            load_game_from_notation(
+                content.as_bytes(),
+                "Counting",
+                [1, 0],
+                CompatibilityPolicy::MinorEqual,
+                |state| Ok(RevCountingGame::new(state[0].1.parse().unwrap())),
+            )
+        };
+        let loaded = load(&content).unwrap();
+        assert_eq!(loaded.data(), engine.data());
+        assert_eq!(loaded.serialized_log(), engine.serialized_log());
+        assert!(matches!(
+            load(&content.replace("+3P1", "+4P1")),
+            Err(LoadGameError::InvalidNotation { move_nr: 1, .. })
+        ));
+        assert!(matches!(
+            load(&content.replace("+3P1", "+3P0")),
+            Err(LoadGameError::UnexpectedPlayer { decision_nr: 1, .. })
+        ));
+
+        // a pending follow-up decision is not a complete move
+        play(&mut engine, &[1]);
+        let log = notation_log(2, create_data, &engine.serialized_log()).unwrap();
+        assert_eq!(log.log.len(), 2);
+        assert_eq!(log.redo_stack.len(), 2);
+
+        // meta actions can't be stored in notation form
+        engine.apply_meta_action(MetaAction::OfferDraw(0)).unwrap();
+        assert!(matches!(
+            notation_log(2, create_data, &engine.serialized_log()),
+            Err(LoadGameError::UnsupportedMetaAction {
+                decision_nr: 3,
+                action: MetaAction::OfferDraw(0),
+            })
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_test() {
+        use super::{SavedGame, SerializedLog};
+
+        let mut engine = LoggingEngine::new_logging(2, RevCountingGame::new(100));
+        play(&mut engine, &[0, 1, 0, 1, 1]);
+        assert!(engine.undo_last_decision());
+        play(&mut engine, &[0]);
+        assert!(engine.undo_last_decision());
+        let game = SavedGame::new(
+            "Counting",
+            [1, 2],
+            [("finished_at".to_string(), "100".to_string())],
+            2,
+            engine.serialized_log(),
+        );
+        let json = serde_json::to_string(&game).unwrap();
+        let parsed: SavedGame = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, game);
+        let log_json = serde_json::to_string(engine.log()).unwrap();
+        let log: SerializedLog = serde_json::from_str(&log_json).unwrap();
+        assert_eq!(log, engine.serialized_log());
+
+        let restore = |game: SavedGame, policy| {
+            game.restore("Counting", [1, 1], policy, |state| {
+                Ok(RevCountingGame::new(state[0].1.parse().unwrap()))
+            })
+        };
+        let loaded = restore(parsed.clone(), CompatibilityPolicy::MajorEqual).unwrap();
+        assert_eq!(loaded.data(), engine.data());
+        assert_eq!(loaded.variations(), engine.variations());
+        assert!(matches!(
+            restore(parsed, CompatibilityPolicy::MinorLessEqual),
+            Err(LoadGameError::VersionMismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,466 @@
+use std::fmt::{self, Debug};
+
+use crate::{GameData, GameResult};
+
+use super::{
+    io::SerializedLog,
+    logging::{AsEventLog, DecisionLog, EventLog, UndoLog},
+    meta::MetaAction,
+    EventListener,
+};
+
+// Composite listeners forward every callback to all of their members. Since effects are
+// passed by value, only the first member takes ownership of them, while all other members
+// are notified via `effect_observed` (see `EventListener`). Logging capabilities are
+// provided by the first member.
+
+/// Combines two listeners, where the first one owns the effects and provides
+/// the logging capabilities (e.g. an `EventLog`).
+impl<T: GameData, A: EventListener<T>, B: EventListener<T>> EventListener<T> for (A, B) {
+    fn effect_applied(&mut self, effect: Box<T::EffectType>) {
+        self.1.effect_observed(&effect);
+        self.0.effect_applied(effect);
+    }
+
+    fn option_selected(&mut self, index: usize, player: usize) {
+        self.0.option_selected(index, player);
+        self.1.option_selected(index, player);
+    }
+
+    fn retracted_by_n(&mut self, n: usize) {
+        self.0.retracted_by_n(n);
+        self.1.retracted_by_n(n);
+    }
+
+    fn effects_completed(&mut self, data: &T) {
+        self.0.effects_completed(data);
+        self.1.effects_completed(data);
+    }
+
+    fn decision_pending(
+        &mut self,
+        data: &T,
+        player: usize,
+        option_count: usize,
+        level_in_chain: usize,
+    ) {
+        self.0
+            .decision_pending(data, player, option_count, level_in_chain);
+        self.1
+            .decision_pending(data, player, option_count, level_in_chain);
+    }
+
+    fn decision_undone(&mut self, data: &T) {
+        self.0.decision_undone(data);
+        self.1.decision_undone(data);
+    }
+
+    fn decision_redone(&mut self, data: &T) {
+        self.0.decision_redone(data);
+        self.1.decision_redone(data);
+    }
+
+    fn game_finished(&mut self, data: &T, result: Option<&GameResult>) {
+        self.0.game_finished(data, result);
+        self.1.game_finished(data, result);
+    }
+
+    fn meta_action_applied(&mut self, action: &MetaAction) {
+        self.0.meta_action_applied(action);
+        self.1.meta_action_applied(action);
+    }
+
+    fn effect_observed(&mut self, effect: &T::EffectType) {
+        self.0.effect_observed(effect);
+        self.1.effect_observed(effect);
+    }
+}
+
+impl<T: GameData, A: DecisionLog<T>, B: EventListener<T>> DecisionLog<T> for (A, B) {
+    fn redo_available(&self) -> bool {
+        self.0.redo_available()
+    }
+
+    fn redo_step(&mut self) -> Option<(usize, usize)> {
+        let (index, player) = self.0.redo_step()?;
+        self.1.option_selected(index, player);
+        Some((index, player))
+    }
+
+    fn redo_effect(&mut self, effect: Box<T::EffectType>) {
+        self.1.effect_observed(&effect);
+        self.0.redo_effect(effect);
+    }
+
+    fn serialized(&self) -> SerializedLog {
+        self.0.serialized()
+    }
+}
+
+impl<T: GameData, A: UndoLog<T>, B: EventListener<T>> UndoLog<T> for (A, B) {
+    fn undo_last_decision(&mut self, data: &mut T) -> bool {
+        self.0.undo_last_decision(data)
+    }
+
+    fn undo_meta_actions(&mut self) -> bool {
+        self.0.undo_meta_actions()
+    }
+}
+
+impl<T: GameData, A: AsEventLog<T>, B> AsEventLog<T> for (A, B) {
+    fn event_log(&self) -> &EventLog<T> {
+        self.0.event_log()
+    }
+
+    fn event_log_mut(&mut self) -> &mut EventLog<T> {
+        self.0.event_log_mut()
+    }
+}
+
+/// The first listener owns the effects, the others observe them.
+impl<T: GameData> EventListener<T> for Vec<Box<dyn EventListener<T>>> {
+    fn effect_applied(&mut self, effect: Box<T::EffectType>) {
+        if let Some((first, others)) = self.split_first_mut() {
+            for listener in others {
+                listener.effect_observed(&effect);
+            }
+            first.effect_applied(effect);
+        }
+    }
+
+    fn option_selected(&mut self, index: usize, player: usize) {
+        for listener in self.iter_mut() {
+            listener.option_selected(index, player);
+        }
+    }
+
+    fn retracted_by_n(&mut self, n: usize) {
+        for listener in self.iter_mut() {
+            listener.retracted_by_n(n);
+        }
+    }
+
+    fn effects_completed(&mut self, data: &T) {
+        for listener in self.iter_mut() {
+            listener.effects_completed(data);
+        }
+    }
+
+    fn decision_pending(
+        &mut self,
+        data: &T,
+        player: usize,
+        option_count: usize,
+        level_in_chain: usize,
+    ) {
+        for listener in self.iter_mut() {
+            listener.decision_pending(data, player, option_count, level_in_chain);
+        }
+    }
+
+    fn decision_undone(&mut self, data: &T) {
+        for listener in self.iter_mut() {
+            listener.decision_undone(data);
+        }
+    }
+
+    fn decision_redone(&mut self, data: &T) {
+        for listener in self.iter_mut() {
+            listener.decision_redone(data);
+        }
+    }
+
+    fn game_finished(&mut self, data: &T, result: Option<&GameResult>) {
+        for listener in self.iter_mut() {
+            listener.game_finished(data, result);
+        }
+    }
+
+    fn meta_action_applied(&mut self, action: &MetaAction) {
+        for listener in self.iter_mut() {
+            listener.meta_action_applied(action);
+        }
+    }
+
+    fn effect_observed(&mut self, effect: &T::EffectType) {
+        for listener in self.iter_mut() {
+            listener.effect_observed(effect);
+        }
+    }
+}
+
+/// A primary listener (usually a log) together with any number of observers, which can
+/// be added at runtime. The primary listener owns the effects and provides the logging
+/// capabilities, the observers are notified via `effect_observed`.
+pub struct ListenerChain<T: GameData, L: EventListener<T>> {
+    primary: L,
+    observers: Vec<Box<dyn EventListener<T>>>,
+}
+
+impl<T: GameData, L: EventListener<T>> ListenerChain<T, L> {
+    pub fn new(primary: L) -> Self {
+        Self {
+            primary,
+            observers: Vec::new(),
+        }
+    }
+
+    pub fn with_observer<O: EventListener<T> + 'static>(mut self, observer: O) -> Self {
+        self.add_observer(observer);
+        self
+    }
+
+    pub fn add_observer<O: EventListener<T> + 'static>(&mut self, observer: O) {
+        self.observers.push(Box::new(observer));
+    }
+
+    pub fn primary(&self) -> &L {
+        &self.primary
+    }
+
+    pub fn primary_mut(&mut self) -> &mut L {
+        &mut self.primary
+    }
+
+    pub fn observers(&self) -> &[Box<dyn EventListener<T>>] {
+        &self.observers
+    }
+
+    pub fn observers_mut(&mut self) -> &mut Vec<Box<dyn EventListener<T>>> {
+        &mut self.observers
+    }
+
+    pub fn into_primary(self) -> L {
+        self.primary
+    }
+}
+
+impl<T: GameData, L: EventListener<T> + Debug> Debug for ListenerChain<T, L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ListenerChain {{ primary: {:?}, observers: {} }}",
+            &self.primary,
+            self.observers.len()
+        )
+    }
+}
+
+impl<T: GameData, L: EventListener<T>> EventListener<T> for ListenerChain<T, L> {
+    fn effect_applied(&mut self, effect: Box<T::EffectType>) {
+        self.observers.effect_observed(&effect);
+        self.primary.effect_applied(effect);
+    }
+
+    fn option_selected(&mut self, index: usize, player: usize) {
+        self.primary.option_selected(index, player);
+        self.observers.option_selected(index, player);
+    }
+
+    fn retracted_by_n(&mut self, n: usize) {
+        self.primary.retracted_by_n(n);
+        self.observers.retracted_by_n(n);
+    }
+
+    fn effects_completed(&mut self, data: &T) {
+        self.primary.effects_completed(data);
+        self.observers.effects_completed(data);
+    }
+
+    fn decision_pending(
+        &mut self,
+        data: &T,
+        player: usize,
+        option_count: usize,
+        level_in_chain: usize,
+    ) {
+        self.primary
+            .decision_pending(data, player, option_count, level_in_chain);
+        self.observers
+            .decision_pending(data, player, option_count, level_in_chain);
+    }
+
+    fn decision_undone(&mut self, data: &T) {
+        self.primary.decision_undone(data);
+        self.observers.decision_undone(data);
+    }
+
+    fn decision_redone(&mut self, data: &T) {
+        self.primary.decision_redone(data);
+        self.observers.decision_redone(data);
+    }
+
+    fn game_finished(&mut self, data: &T, result: Option<&GameResult>) {
+        self.primary.game_finished(data, result);
+        self.observers.game_finished(data, result);
+    }
+
+    fn meta_action_applied(&mut self, action: &MetaAction) {
+        self.primary.meta_action_applied(action);
+        self.observers.meta_action_applied(action);
+    }
+
+    fn effect_observed(&mut self, effect: &T::EffectType) {
+        self.primary.effect_observed(effect);
+        self.observers.effect_observed(effect);
+    }
+}
+
+impl<T: GameData, L: DecisionLog<T>> DecisionLog<T> for ListenerChain<T, L> {
+    fn redo_available(&self) -> bool {
+        self.primary.redo_available()
+    }
+
+    fn redo_step(&mut self) -> Option<(usize, usize)> {
+        let (index, player) = self.primary.redo_step()?;
+        self.observers.option_selected(index, player);
+        Some((index, player))
+    }
+
+    fn redo_effect(&mut self, effect: Box<T::EffectType>) {
+        self.observers.effect_observed(&effect);
+        self.primary.redo_effect(effect);
+    }
+
+    fn serialized(&self) -> SerializedLog {
+        self.primary.serialized()
+    }
+}
+
+impl<T: GameData, L: UndoLog<T>> UndoLog<T> for ListenerChain<T, L> {
+    fn undo_last_decision(&mut self, data: &mut T) -> bool {
+        self.primary.undo_last_decision(data)
+    }
+
+    fn undo_meta_actions(&mut self) -> bool {
+        self.primary.undo_meta_actions()
+    }
+}
+
+impl<T: GameData, L: EventListener<T> + AsEventLog<T>> AsEventLog<T> for ListenerChain<T, L> {
+    fn event_log(&self) -> &EventLog<T> {
+        self.primary.event_log()
+    }
+
+    fn event_log_mut(&mut self) -> &mut EventLog<T> {
+        self.primary.event_log_mut()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        engine::{
+            logging::{EventLog, SnapshotLog},
+            Engine, EventListener, LoggingEngine, NotListening,
+        },
+        test::{play, RevCountingGame},
+        GameData, GameResult,
+    };
+
+    use super::ListenerChain;
+
+    #[derive(Debug, Default, PartialEq, Eq)]
+    struct Stats {
+        options: Vec<(usize, usize)>,
+        effects: usize,
+        finished: usize,
+    }
+
+    #[derive(Clone, Default)]
+    struct StatsRecorder(Rc<RefCell<Stats>>);
+
+    impl<T: GameData> EventListener<T> for StatsRecorder {
+        fn effect_applied(&mut self, _effect: Box<T::EffectType>) {
+            panic!("observers don't own effects");
+        }
+
+        fn effect_observed(&mut self, _effect: &T::EffectType) {
+            self.0.borrow_mut().effects += 1;
+        }
+
+        fn option_selected(&mut self, index: usize, player: usize) {
+            self.0.borrow_mut().options.push((index, player));
+        }
+
+        fn retracted_by_n(&mut self, _n: usize) {}
+
+        fn game_finished(&mut self, _data: &T, _result: Option<&GameResult>) {
+            self.0.borrow_mut().finished += 1;
+        }
+    }
+
+    #[test]
+    fn tuple_test() {
+        let stats = StatsRecorder::default();
+        let mut engine =
+            Engine::with_listener(2, RevCountingGame::new(3), (EventLog::new(), stats.clone()));
+        play(&mut engine, &[0, 1, 0]);
+        assert!(engine.undo_last_decision());
+        play(&mut engine, &[0]);
+        assert!(engine.undo_last_decision());
+        assert_eq!(engine.variations().len(), 2);
+        assert!(engine.redo_variation(1));
+        assert!(engine.is_finished());
+
+        let mut expected = LoggingEngine::new_logging(2, RevCountingGame::new(3));
+        play(&mut expected, &[0, 1, 0]);
+        assert!(expected.undo_last_decision());
+        play(&mut expected, &[0]);
+        assert!(expected.undo_last_decision());
+        assert!(expected.redo_variation(1));
+        assert_eq!(engine.serialized_log(), expected.serialized_log());
+        assert_eq!(
+            engine.event_log().decision_chains(),
+            expected.log().decision_chains()
+        );
+        assert_eq!(
+            *stats.0.borrow(),
+            Stats {
+                options: vec![(0, 0), (1, 1), (0, 1), (0, 1), (1, 1), (0, 1)],
+                effects: 4,
+                finished: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn chain_test() {
+        let stats = StatsRecorder::default();
+        let mut log = ListenerChain::new(EventLog::new()).with_observer(stats.clone());
+        log.add_observer(vec![
+            Box::new(stats.clone()) as Box<dyn EventListener<RevCountingGame>>
+        ]);
+        let mut engine = Engine::with_listener(2, RevCountingGame::new(100), log);
+        play(&mut engine, &[1, 0, 0]);
+        assert!(engine.undo_last_decision());
+        assert!(engine.redo_decision());
+        let bookmark = engine.event_log_mut().bookmark();
+        play(&mut engine, &[0]);
+        assert!(engine.reset_to(bookmark));
+        assert_eq!(engine.data().0.history, vec![2, 1]);
+        assert_eq!(engine.log().observers().len(), 2);
+        let stats = stats.0.borrow();
+        assert_eq!(stats.options.len(), 2 * 5);
+        assert_eq!(stats.effects, 2 * 4);
+    }
+
+    #[test]
+    fn observing_log_test() {
+        let data = RevCountingGame::new(100);
+        let log = (EventLog::new(), SnapshotLog::new(&data));
+        let mut engine = Engine::with_listener(2, data, log);
+        play(&mut engine, &[1, 0, 0]);
+        assert_eq!(engine.log().1.num_decisions(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "must own the effects")]
+    fn observing_event_log_test() {
+        let data = RevCountingGame::new(100);
+        let mut engine = Engine::with_listener(2, data, (NotListening {}, EventLog::new()));
+        play(&mut engine, &[0]);
+    }
+}
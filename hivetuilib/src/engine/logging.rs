@@ -1,20 +1,61 @@
-use std::fmt::{self, Debug};
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug},
+    mem,
+};
 
-use crate::{GameData, RevEffect};
+use crate::{Effect, GameData, Outcome, RevEffect, StateHash};
 
-use super::{io::SerializedLog, EventListener};
+use super::{
+    io::{SerializedLog, SerializedVariation},
+    meta::MetaAction,
+    EventListener, INTERNAL_ERROR,
+};
+
+/// A listener that records the selected options and is able to redo them.
+pub trait DecisionLog<T: GameData>: EventListener<T> {
+    fn redo_available(&self) -> bool;
+
+    /// Returns the next option to redo as index and player.
+    /// The event is also pushed to the log (so don't do this a second time).
+    fn redo_step(&mut self) -> Option<(usize, usize)>;
+
+    /// Records an effect that was applied while redoing a decision.
+    fn redo_effect(&mut self, effect: Box<T::EffectType>);
+
+    fn serialized(&self) -> SerializedLog;
+}
+
+/// A decision log that is able to revert the data to the state before the last decision.
+pub trait UndoLog<T: GameData>: DecisionLog<T> {
+    fn undo_last_decision(&mut self, data: &mut T) -> bool;
+
+    /// Removes the meta actions after the last completed decision, which is used
+    /// if there is no decision to undo. Returns whether an action was removed.
+    fn undo_meta_actions(&mut self) -> bool {
+        false
+    }
+}
+
+/// Access to an `EventLog`, either directly or as member of a composite listener.
+pub trait AsEventLog<T: GameData> {
+    fn event_log(&self) -> &EventLog<T>;
+
+    fn event_log_mut(&mut self) -> &mut EventLog<T>;
+}
 
 #[derive(Clone)]
 pub enum Event<T: GameData> {
     Effect(Box<T::EffectType>),
     /// index, player
     Decision(usize, usize),
+    Meta(MetaAction),
 }
 
 impl<T: GameData> Event<T> {
     pub fn is_decision(&self) -> bool {
         match self {
-            Event::Effect(_) => false,
+            Event::Effect(_) | Event::Meta(_) => false,
             Event::Decision(_, _) => true,
         }
     }
@@ -27,28 +68,135 @@ impl<T: GameData> Debug for Event<T> {
                 write!(f, "Event::Effect(Box<_>)")
             }
             Event::Decision(val, player) => write!(f, "Event::Decision({val:?}, {player:?})"),
+            Event::Meta(action) => write!(f, "Event::Meta({action:?})"),
+        }
+    }
+}
+
+/// An alternative continuation in the variation tree of an `EventLog`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Variation {
+    /// selected options (index, player) of the first decision
+    decision: Vec<(usize, usize)>,
+    /// continuations after the decision, the first one is the main line
+    continuations: Vec<Variation>,
+}
+
+impl Variation {
+    pub(crate) fn new(decision: Vec<(usize, usize)>, continuations: Vec<Variation>) -> Self {
+        Self {
+            decision,
+            continuations,
+        }
+    }
+
+    /// Selected options (index, player) of the first decision of this variation.
+    pub fn decision(&self) -> &[(usize, usize)] {
+        &self.decision
+    }
+
+    /// Continuations after the first decision, the first one is the main line.
+    pub fn continuations(&self) -> &[Variation] {
+        &self.continuations
+    }
+
+    /// Selected options of the main line of this variation.
+    pub fn main_line(&self) -> Vec<(usize, usize)> {
+        let mut result = Vec::new();
+        let mut current = Some(self);
+        while let Some(variation) = current {
+            result.extend_from_slice(&variation.decision);
+            current = variation.continuations.first();
+        }
+        result
+    }
+
+    /// Side variations, relative to the main line of this variation.
+    pub(crate) fn serialized_variations(&self) -> Vec<SerializedVariation> {
+        let mut result = Vec::new();
+        let mut current = Some(self);
+        let mut position = 1;
+        while let Some(variation) = current {
+            let side_lines = variation.continuations.iter().enumerate().skip(1);
+            result.extend(side_lines.map(|(i, v)| SerializedVariation::new(position, i, v)));
+            current = variation.continuations.first();
+            position += 1;
+        }
+        result
+    }
+}
+
+impl SerializedVariation {
+    fn new(position: usize, index: usize, variation: &Variation) -> Self {
+        Self {
+            position,
+            index,
+            line: variation.main_line(),
+            variations: variation.serialized_variations(),
         }
     }
 }
 
-// TODO: hash for more verification?
-// TODO: safe point you can reset to (via generational indizes)?
-// TODO: snapshots (requires clone) - lift RevEffect requirement?
+/// Handle to a position (decision boundary) on the current line of an `EventLog`.
+///
+/// The handle is generational: it becomes invalid if the line is changed at an earlier
+/// position, e.g. by undoing past the bookmark and playing a different decision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bookmark {
+    position: usize,
+    generation: u64,
+}
+
+impl Bookmark {
+    /// Number of completed decisions at the bookmarked position.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+/// Log of events which allows to undo and redo decisions.
+///
+/// Instead of discarding the redo information when a different decision is played,
+/// all explored lines are kept as a tree of variations.
 pub struct EventLog<T: GameData> {
     pub(crate) log: Vec<Event<T>>,
-    pub(crate) redo_stack: Vec<(usize, usize)>,
+    /// for every completed decision of the log: the index of the decision in the
+    /// continuations of its position, and the other continuations
+    pub(crate) past_variations: Vec<(usize, Vec<Variation>)>,
+    /// continuations at the current position, the first one is the main line
+    pub(crate) variations: Vec<Variation>,
+    /// remaining options of the decision that is currently redone (in reverse order)
+    redo_chain: Vec<(usize, usize)>,
+    /// generation of each position on the current line that was bookmarked so far
+    generations: Vec<u64>,
+    next_generation: u64,
+    named_bookmarks: HashMap<String, Bookmark>,
+    /// state fingerprint function, if fingerprints are recorded
+    hasher: Option<fn(&T) -> u64>,
+    /// interval (in decisions) of recording fingerprints
+    hash_interval: usize,
+    /// recorded fingerprints with the number of completed decisions, sorted by the latter
+    hashes: Vec<(usize, u64)>,
 }
 
 impl<T: GameData> Debug for EventLog<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "EventLog: {{log: {:#?}, redo_stack: {:#?}}}",
-            &self.log, &self.redo_stack
+            "EventLog: {{log: {:#?}, past_variations: {:#?}, variations: {:#?}, named_bookmarks: {:#?}, hashes: {:#?}}}",
+            &self.log, &self.past_variations, &self.variations, &self.named_bookmarks, &self.hashes
         )
     }
 }
 
+/// Serializes the log in its serialized form, i.e. as `SerializedLog`.
+#[cfg(feature = "serde")]
+impl<T: GameData> serde::Serialize for EventLog<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.serialized().serialize(serializer)
+    }
+}
+
 impl<T: GameData> Default for EventLog<T> {
     fn default() -> Self {
         Self::new()
@@ -59,82 +207,406 @@ impl<T: GameData> EventLog<T> {
     pub fn new() -> Self {
         Self {
             log: Vec::new(),
-            redo_stack: Vec::new(),
+            past_variations: Vec::new(),
+            variations: Vec::new(),
+            redo_chain: Vec::new(),
+            generations: Vec::new(),
+            next_generation: 0,
+            named_bookmarks: HashMap::new(),
+            hasher: None,
+            hash_interval: 1,
+            hashes: Vec::new(),
         }
     }
 
+    /// Creates a log that records a fingerprint of the state every `interval` decisions.
+    /// The fingerprints are included in the serialized log.
+    pub fn with_state_hashes(interval: usize) -> Self
+    where
+        T: StateHash,
+    {
+        assert!(interval > 0, "Hash interval must be positive");
+        let mut result = Self::new();
+        result.hasher = Some(T::state_hash);
+        result.hash_interval = interval;
+        result
+    }
+
+    /// Records a fingerprint of the current state, e.g. a verified fingerprint of a loaded
+    /// game. Nothing is recorded if there already is a fingerprint for the current position.
+    pub(crate) fn record_state_hash(&mut self, hash: u64) {
+        let position = self.position();
+        if self.hashes.last().map_or(true, |&(n, _)| n < position) {
+            self.hashes.push((position, hash));
+        }
+    }
+
+    /// Number of completed decisions at the current position.
+    pub fn position(&self) -> usize {
+        self.past_variations.len()
+    }
+
+    /// Creates a bookmark at the current position. Within a pending follow-up decision,
+    /// the position before the decision chain is used.
+    pub fn bookmark(&mut self) -> Bookmark {
+        let position = self.position();
+        while self.generations.len() <= position {
+            self.generations.push(self.next_generation);
+            self.next_generation += 1;
+        }
+        Bookmark {
+            position,
+            generation: self.generations[position],
+        }
+    }
+
+    /// Creates a bookmark at the current position and stores it with the given name.
+    /// An existing bookmark with the same name is replaced.
+    pub fn bookmark_as<S: Into<String>>(&mut self, name: S) -> Bookmark {
+        let bookmark = self.bookmark();
+        self.named_bookmarks.insert(name.into(), bookmark);
+        bookmark
+    }
+
+    /// Returns the bookmark with the given name (which is not necessarily still valid).
+    pub fn named_bookmark(&self, name: &str) -> Option<Bookmark> {
+        self.named_bookmarks.get(name).copied()
+    }
+
+    pub fn remove_named_bookmark(&mut self, name: &str) -> Option<Bookmark> {
+        self.named_bookmarks.remove(name)
+    }
+
+    /// Whether the bookmark still refers to a position on the current line.
+    pub fn is_valid(&self, bookmark: Bookmark) -> bool {
+        self.generations.get(bookmark.position) == Some(&bookmark.generation)
+    }
+
+    /// Available continuations at the current position. The first one is the main line,
+    /// which is followed by `redo_decision`.
+    pub fn variations(&self) -> &[Variation] {
+        &self.variations
+    }
+
+    /// Moves the variation with the given index to the front, making it the main line.
+    /// Returns whether the index is valid.
+    pub fn promote_variation(&mut self, index: usize) -> bool {
+        if index < self.variations.len() {
+            if index != 0 {
+                self.line_changed();
+            }
+            let variation = self.variations.remove(index);
+            self.variations.insert(0, variation);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Removes the variation with the given index (including all continuations).
+    pub fn delete_variation(&mut self, index: usize) -> Option<Variation> {
+        if index < self.variations.len() {
+            if index == 0 {
+                self.line_changed();
+            }
+            Some(self.variations.remove(index))
+        } else {
+            None
+        }
+    }
+
+    /// Prepares redoing the first decision of the given variation.
+    pub(crate) fn start_variation(&mut self, index: usize) -> bool {
+        debug_assert!(self.redo_chain.is_empty());
+        if index >= self.variations.len() {
+            return false;
+        }
+        let mut decision = self.enter_variation(index);
+        decision.reverse();
+        self.redo_chain = decision;
+        true
+    }
+
+    /// Moves to the given continuation and returns its decision.
+    fn enter_variation(&mut self, index: usize) -> Vec<(usize, usize)> {
+        if index != 0 {
+            self.line_changed();
+        }
+        let variation = self.variations.remove(index);
+        let siblings = mem::replace(&mut self.variations, variation.continuations);
+        self.past_variations.push((index, siblings));
+        variation.decision
+    }
+
+    /// Invalidates all bookmarks after the current position.
+    fn line_changed(&mut self) {
+        self.generations.truncate(self.position() + 1);
+    }
+
+    /// Replaces the variation tree, given the continuations at the initial position and
+    /// the index of each completed decision of the log within the continuations.
+    pub(crate) fn restore_variations(&mut self, mut variations: Vec<Variation>, path: &[usize]) {
+        debug_assert!(self.redo_chain.is_empty());
+        self.generations.clear();
+        self.past_variations.clear();
+        for &index in path {
+            let variation = variations.remove(index);
+            self.past_variations.push((index, variations));
+            variations = variation.continuations;
+        }
+        self.variations = variations;
+    }
+
+    /// Selected options of all completed decisions, grouped per decision.
+    pub(crate) fn decision_chains(&self) -> Vec<Vec<(usize, usize)>> {
+        let mut result = Vec::new();
+        let mut current = Vec::new();
+        for event in self.log.iter() {
+            match event {
+                Event::Effect(_) => {
+                    if !current.is_empty() {
+                        result.push(mem::take(&mut current));
+                    }
+                }
+                &Event::Decision(index, player) => current.push((index, player)),
+                Event::Meta(_) => {}
+            }
+        }
+        result
+    }
+
+    /// Selected options of the trailing decisions of the log.
+    fn trailing_decisions(&self) -> Vec<(usize, usize)> {
+        let mut result = self
+            .log
+            .iter()
+            .rev()
+            .map_while(|event| match event {
+                Event::Effect(_) | Event::Meta(_) => None,
+                &Event::Decision(index, player) => Some((index, player)),
+            })
+            .collect::<Vec<_>>();
+        result.reverse();
+        result
+    }
+
+    /// Moves into the variation tree after a new decision was completed.
+    fn enter_new_decision(&mut self) {
+        let decision = self.trailing_decisions();
+        match self.variations.iter().position(|v| v.decision == decision) {
+            Some(index) => {
+                self.enter_variation(index);
+            }
+            None => {
+                self.line_changed();
+                let siblings = mem::take(&mut self.variations);
+                self.past_variations.push((0, siblings));
+            }
+        }
+    }
+}
+
+/// Inherent versions of the `DecisionLog` and `UndoLog` methods,
+/// so that they can be used without importing the traits.
+impl<T: GameData> EventLog<T> {
     pub fn redo_effect(&mut self, effect: Box<T::EffectType>) {
-        self.log.push(Event::Effect(effect));
+        DecisionLog::redo_effect(self, effect)
     }
 
     pub fn redo_available(&self) -> bool {
-        !self.redo_stack.is_empty()
+        DecisionLog::redo_available(self)
     }
 
-    /// The event is also pushed to the log (so don't do this a second time).
-    pub fn redo_step(&mut self) -> Option<usize> {
-        self.redo_stack.pop().map(|(index, player)| {
-            self.log.push(Event::Decision(index, player));
-            index
-        })
+    pub fn redo_step(&mut self) -> Option<(usize, usize)> {
+        DecisionLog::redo_step(self)
     }
 
     pub fn serialized(&self) -> SerializedLog {
+        DecisionLog::serialized(self)
+    }
+
+    pub fn undo_last_decision(&mut self, data: &mut T) -> bool
+    where
+        T::EffectType: RevEffect<T>,
+    {
+        UndoLog::undo_last_decision(self, data)
+    }
+}
+
+impl<T: GameData> AsEventLog<T> for EventLog<T> {
+    fn event_log(&self) -> &EventLog<T> {
+        self
+    }
+
+    fn event_log_mut(&mut self) -> &mut EventLog<T> {
+        self
+    }
+}
+
+impl<T: GameData> DecisionLog<T> for EventLog<T> {
+    fn redo_effect(&mut self, effect: Box<T::EffectType>) {
+        self.log.push(Event::Effect(effect));
+    }
+
+    fn redo_available(&self) -> bool {
+        !self.redo_chain.is_empty() || !self.variations.is_empty()
+    }
+
+    fn redo_step(&mut self) -> Option<(usize, usize)> {
+        if self.redo_chain.is_empty() && !self.start_variation(0) {
+            return None;
+        }
+        let (index, player) = self.redo_chain.pop()?;
+        self.log.push(Event::Decision(index, player));
+        Some((index, player))
+    }
+
+    fn serialized(&self) -> SerializedLog {
         let log_it = self.log.iter().filter_map(|event| match event {
-            Event::Effect(_) => None,
+            Event::Effect(_) | Event::Meta(_) => None,
             &Event::Decision(index, player) => Some((index, player)),
         });
+        let meta = self
+            .log
+            .iter()
+            .scan(0, |num_options, event| {
+                Some(match event {
+                    Event::Decision(..) => {
+                        *num_options += 1;
+                        None
+                    }
+                    &Event::Meta(action) => Some((*num_options, action)),
+                    Event::Effect(_) => None,
+                })
+            })
+            .flatten()
+            .collect();
+        let mut redo_stack = self
+            .variations
+            .first()
+            .map(Variation::main_line)
+            .unwrap_or_default();
+        redo_stack.reverse();
+
+        let mut variations = Vec::new();
+        for (position, (line_index, siblings)) in self.past_variations.iter().enumerate() {
+            // skip the index of the decision on the line
+            let indizes = (0..).filter(|i| i != line_index);
+            variations.extend(
+                indizes
+                    .zip(siblings)
+                    .map(|(i, v)| SerializedVariation::new(position, i, v)),
+            );
+        }
+        let position = self.past_variations.len();
+        let side_lines = self.variations.iter().enumerate().skip(1);
+        variations.extend(side_lines.map(|(i, v)| SerializedVariation::new(position, i, v)));
+        if let Some(main) = self.variations.first() {
+            variations.extend(main.serialized_variations().into_iter().map(|mut v| {
+                v.position += position;
+                v
+            }));
+        }
+        // map the fingerprints to the last option of the corresponding decision
+        let mut decision_ends = self.decision_chains().into_iter().scan(0, |len, chain| {
+            *len += chain.len();
+            Some(*len - 1)
+        });
+        let mut num_decisions = 0;
+        let hashes = self
+            .hashes
+            .iter()
+            .map(|&(position, hash)| {
+                let end = decision_ends
+                    .nth(position - num_decisions - 1)
+                    .expect(INTERNAL_ERROR);
+                num_decisions = position;
+                (end, hash)
+            })
+            .collect();
         SerializedLog {
             log: log_it.collect(),
-            redo_stack: self.redo_stack.clone(),
+            redo_stack,
+            variations,
+            hashes,
+            meta,
         }
     }
 }
 
-impl<T: GameData> EventLog<T>
+impl<T: GameData> UndoLog<T> for EventLog<T>
 where
     T::EffectType: RevEffect<T>,
 {
-    // TODO: correct behavior when in subdecision state?
-    pub fn undo_last_decision(&mut self, data: &mut T) -> bool {
-        // initialize with sentinel value to avoid edge case
-        let mut current_event = Event::Decision(0, 0);
-        // pop subdecision
-        while current_event.is_decision() {
-            if let Some(event) = self.log.pop() {
-                current_event = event;
-            } else {
-                return false;
-            }
-        }
+    fn undo_last_decision(&mut self, data: &mut T) -> bool {
+        // the log is left untouched if there is no completed decision
+        let Some(last_effect) = self.log.iter().rposition(|e| matches!(e, Event::Effect(_))) else {
+            return false;
+        };
+        // drop an incomplete subdecision (the engine usually retracts it beforehand)
+        // and the meta actions after the decision
+        self.log.truncate(last_effect + 1);
+        let mut current_event = self.log.pop().expect(INTERNAL_ERROR);
         // undo effects
         while let Event::Effect(effect) = current_event {
             current_event = self.log.pop().expect("Internal error: Inconsistent log.");
             effect.undo(data);
         }
-        // push decision to redo stack
+        // collect the decision
+        let mut decision = Vec::new();
         while let Event::Decision(index, player) = current_event {
-            self.redo_stack.push((index, player));
+            decision.push((index, player));
             if let Some(event) = self.log.pop() {
                 current_event = event;
             } else {
-                return true;
+                break;
             }
         }
-        self.log.push(current_event);
+        if !current_event.is_decision() {
+            self.log.push(current_event);
+        }
+        decision.reverse();
+
+        // move back in the variation tree
+        let (index, mut siblings) = self.past_variations.pop().expect(INTERNAL_ERROR);
+        let position = self.position();
+        self.hashes.retain(|&(n, _)| n <= position);
+        let continuations = mem::take(&mut self.variations);
+        siblings.insert(index, Variation::new(decision, continuations));
+        self.variations = siblings;
         true
     }
+
+    fn undo_meta_actions(&mut self) -> bool {
+        let start = self
+            .log
+            .iter()
+            .rposition(|e| matches!(e, Event::Effect(_)))
+            .map_or(0, |i| i + 1);
+        let len = self.log.len();
+        let tail = self.log.split_off(start);
+        self.log
+            .extend(tail.into_iter().filter(|e| !matches!(e, Event::Meta(_))));
+        self.log.len() < len
+    }
 }
 
 impl<T: GameData> EventListener<T> for EventLog<T> {
     fn effect_applied(&mut self, effect: Box<T::EffectType>) {
+        if self.log.last().is_some_and(Event::is_decision) {
+            self.enter_new_decision();
+        }
         self.log.push(Event::Effect(effect));
-        self.redo_stack.clear();
+    }
+
+    fn effect_observed(&mut self, _effect: &T::EffectType) {
+        panic!(
+            "An EventLog must own the effects, i.e. be the first member of a composite listener!"
+        );
     }
 
     fn option_selected(&mut self, index: usize, player: usize) {
         self.log.push(Event::Decision(index, player));
-        self.redo_stack.clear();
     }
 
     fn retracted_by_n(&mut self, mut n: usize) {
@@ -144,4 +616,373 @@ impl<T: GameData> EventListener<T> for EventLog<T> {
             assert!(top.is_decision());
         }
     }
+
+    fn meta_action_applied(&mut self, action: &MetaAction) {
+        self.log.push(Event::Meta(*action));
+    }
+
+    fn effects_completed(&mut self, data: &T) {
+        if let Some(hasher) = self.hasher {
+            let position = self.position();
+            if position % self.hash_interval == 0 {
+                self.hashes.push((position, hasher(data)));
+            }
+        }
+    }
+}
+
+const DEFAULT_SNAPSHOT_INTERVAL: usize = 16;
+
+/// A log for cloneable game data that does not require reversible effects.
+///
+/// Undo restores the most recent snapshot of the data and replays the remaining
+/// decisions. Snapshots are taken at decision boundaries, every `interval` decisions,
+/// as soon as the effects of the decision are completed.
+#[derive(Clone)]
+pub struct SnapshotLog<T: GameData + Clone> {
+    /// selected options of all decisions (including a possibly incomplete one)
+    log: Vec<(usize, usize)>,
+    /// length of the log after each completed decision
+    decision_ends: Vec<usize>,
+    redo_stack: Vec<(usize, usize)>,
+    /// meta actions, each with the length of the log before it
+    meta: Vec<(usize, MetaAction)>,
+    /// number of completed decisions and the corresponding data, sorted by the former
+    snapshots: Vec<(usize, T)>,
+    interval: usize,
+}
+
+impl<T: GameData + Clone> Debug for SnapshotLog<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let snapshots = self.snapshots.iter().map(|(n, _)| n).collect::<Vec<_>>();
+        write!(
+            f,
+            "SnapshotLog: {{log: {:?}, decision_ends: {:?}, redo_stack: {:?}, meta: {:?}, snapshots: {:?}, interval: {:?}}}",
+            &self.log, &self.decision_ends, &self.redo_stack, &self.meta, snapshots, self.interval
+        )
+    }
+}
+
+impl<T: GameData + Clone> SnapshotLog<T> {
+    /// Creates a new log, using the provided data as initial snapshot.
+    pub fn new(initial: &T) -> Self {
+        Self::with_interval(initial, DEFAULT_SNAPSHOT_INTERVAL)
+    }
+
+    /// Creates a new log which takes a snapshot every `interval` decisions.
+    pub fn with_interval(initial: &T, interval: usize) -> Self {
+        assert!(interval > 0, "Snapshot interval must be positive");
+        Self {
+            log: Vec::new(),
+            decision_ends: Vec::new(),
+            redo_stack: Vec::new(),
+            meta: Vec::new(),
+            snapshots: vec![(0, initial.clone())],
+            interval,
+        }
+    }
+
+    /// Number of completed decisions.
+    pub fn num_decisions(&self) -> usize {
+        self.decision_ends.len()
+    }
+
+    pub fn interval(&self) -> usize {
+        self.interval
+    }
+
+    fn decision_start(&self, decision_nr: usize) -> usize {
+        match decision_nr {
+            0 => 0,
+            n => self.decision_ends[n - 1],
+        }
+    }
+
+    fn complete_decision(&mut self) {
+        if self.decision_ends.last().copied().unwrap_or(0) < self.log.len() {
+            self.decision_ends.push(self.log.len());
+        }
+    }
+
+    /// Resets the data to the state after the given number of decisions.
+    fn restore(&mut self, data: &mut T, target: usize) {
+        let (mut current, snapshot) = self
+            .snapshots
+            .iter()
+            .rev()
+            .find(|(n, _)| *n <= target)
+            .expect(INTERNAL_ERROR);
+        *data = snapshot.clone();
+        while current < target {
+            let start = self.decision_start(current);
+            let end = self.decision_ends[current];
+            replay_decision(data, &self.log[start..end]);
+            current += 1;
+            self.take_snapshot(current, data);
+        }
+    }
+
+    /// Takes a snapshot after the given number of decisions if it is at the interval
+    /// and there is none for it yet.
+    fn take_snapshot(&mut self, current: usize, data: &T) {
+        let missing = self.snapshots.last().map_or(true, |(n, _)| *n < current);
+        if current % self.interval == 0 && missing {
+            self.snapshots.push((current, data.clone()));
+        }
+    }
+}
+
+/// Applies the given chain of selected options, including all resulting effects.
+fn replay_decision<T: GameData>(data: &mut T, options: &[(usize, usize)]) {
+    let mut decision = data.next_decision().expect(INTERNAL_ERROR);
+    for &(index, _) in options {
+        match decision.select_option(data, index) {
+            Outcome::Effect(effect) => {
+                let mut effect = Some(effect);
+                while let Some(next) = effect {
+                    effect = next.apply(data);
+                }
+                return;
+            }
+            Outcome::FollowUp(follow_up) => {
+                decision = follow_up;
+            }
+        }
+    }
+    panic!("{}", INTERNAL_ERROR)
+}
+
+impl<T: GameData + Clone> DecisionLog<T> for SnapshotLog<T> {
+    fn redo_effect(&mut self, _effect: Box<T::EffectType>) {
+        self.complete_decision();
+    }
+
+    fn redo_available(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    fn redo_step(&mut self) -> Option<(usize, usize)> {
+        let option = self.redo_stack.pop()?;
+        self.log.push(option);
+        Some(option)
+    }
+
+    fn serialized(&self) -> SerializedLog {
+        SerializedLog {
+            log: self.log.clone(),
+            redo_stack: self.redo_stack.clone(),
+            variations: Vec::new(),
+            hashes: Vec::new(),
+            meta: self.meta.clone(),
+        }
+    }
+}
+
+impl<T: GameData + Clone> UndoLog<T> for SnapshotLog<T> {
+    fn undo_last_decision(&mut self, data: &mut T) -> bool {
+        let Some(end) = self.decision_ends.pop() else {
+            return false;
+        };
+        // drop an incomplete subdecision
+        self.log.truncate(end);
+        let target = self.decision_ends.len();
+        let start = self.decision_start(target);
+        self.redo_stack.extend(self.log.drain(start..).rev());
+        self.meta.retain(|&(n, _)| n <= start);
+        self.snapshots.retain(|(n, _)| *n <= target);
+        self.restore(data, target);
+        true
+    }
+
+    fn undo_meta_actions(&mut self) -> bool {
+        let start = self.decision_start(self.decision_ends.len());
+        let len = self.meta.len();
+        self.meta.retain(|&(n, _)| n < start);
+        self.meta.len() < len
+    }
+}
+
+impl<T: GameData + Clone> EventListener<T> for SnapshotLog<T> {
+    fn effect_applied(&mut self, _effect: Box<T::EffectType>) {
+        self.complete_decision();
+        self.redo_stack.clear();
+    }
+
+    fn effect_observed(&mut self, _effect: &T::EffectType) {
+        self.complete_decision();
+        self.redo_stack.clear();
+    }
+
+    fn option_selected(&mut self, index: usize, player: usize) {
+        self.log.push((index, player));
+        self.redo_stack.clear();
+    }
+
+    fn retracted_by_n(&mut self, n: usize) {
+        let start = self.decision_start(self.decision_ends.len());
+        assert!(n <= self.log.len() - start);
+        self.log.truncate(self.log.len() - n);
+    }
+
+    fn meta_action_applied(&mut self, action: &MetaAction) {
+        self.meta.push((self.log.len(), *action));
+    }
+
+    fn effects_completed(&mut self, data: &T) {
+        self.take_snapshot(self.decision_ends.len(), data);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        engine::{logging::SnapshotLog, Engine},
+        test::{play, CountingGame, RevCountingGame},
+    };
+
+    #[test]
+    fn snapshot_undo_redo_test() {
+        let data = CountingGame::new(100);
+        let log = SnapshotLog::with_interval(&data, 2);
+        let mut engine = Engine::with_listener(2, data, log);
+        play(&mut engine, &[0, 1, 1, 0, 1, 0, 0]);
+        assert_eq!(engine.data().history, vec![1, 3, 1, 2, 1]);
+        assert_eq!(engine.log().num_decisions(), 5);
+
+        assert!(engine.undo_last_decision());
+        assert_eq!(engine.data().history, vec![1, 3, 1, 2]);
+        assert!(engine.undo_last_decision());
+        assert!(engine.undo_last_decision());
+        assert_eq!(engine.data().history, vec![1, 3]);
+        assert_eq!(engine.data().sum, 4);
+        assert_eq!(engine.data().player, 0);
+
+        assert!(engine.redo_decision());
+        assert_eq!(engine.data().history, vec![1, 3, 1]);
+        assert!(engine.undo_last_decision());
+        assert!(engine.undo_last_decision());
+        assert!(engine.undo_last_decision());
+        assert_eq!(engine.data(), &CountingGame::new(100));
+        assert!(!engine.undo_last_decision());
+
+        while engine.redo_decision() {}
+        assert_eq!(engine.data().history, vec![1, 3, 1, 2, 1]);
+        let serialized = engine.serialized_log();
+        assert_eq!(
+            serialized.log,
+            vec![(0, 0), (1, 1), (1, 1), (0, 0), (1, 1), (0, 1), (0, 0)]
+        );
+        assert!(serialized.redo_stack.is_empty());
+    }
+
+    #[test]
+    fn snapshot_during_play_test() {
+        let data = CountingGame::new(100);
+        let log = SnapshotLog::with_interval(&data, 2);
+        let mut engine = Engine::with_listener(2, data, log);
+        play(&mut engine, &[0, 1, 1, 0, 1, 0, 0]);
+        let taken = engine.log().snapshots.iter().map(|(n, _)| *n);
+        assert_eq!(taken.collect::<Vec<_>>(), vec![0, 2, 4]);
+
+        // undo starts from the latest snapshot instead of the initial state
+        engine.log_mut().snapshots[0].1 = CountingGame::new(0);
+        assert!(engine.undo_last_decision());
+        assert_eq!(engine.data().history, vec![1, 3, 1, 2]);
+        assert!(engine.undo_last_decision());
+        assert_eq!(engine.data().history, vec![1, 3, 1]);
+        assert!(engine.redo_decision());
+        assert_eq!(engine.log().snapshots.len(), 3);
+    }
+
+    #[test]
+    fn snapshot_undo_in_follow_up_test() {
+        let mut engine = Engine::new_snapshot_logging(2, CountingGame::new(100));
+        play(&mut engine, &[1]);
+        assert!(!engine.undo_last_decision());
+        play(&mut engine, &[0, 0]);
+        assert_eq!(engine.data().history, vec![1, 1]);
+        play(&mut engine, &[1]);
+        assert!(engine.undo_last_decision());
+        assert_eq!(engine.data().history, vec![1]);
+        assert_eq!(engine.serialized_log().redo_stack, vec![(0, 1)]);
+    }
+
+    #[test]
+    fn variation_tree_test() {
+        let mut engine = Engine::new_logging(2, RevCountingGame::new(100));
+        play(&mut engine, &[0, 0, 1, 0]);
+        assert_eq!(engine.data().0.history, vec![1, 1, 2]);
+        assert!(engine.undo_last_decision());
+        assert!(engine.undo_last_decision());
+        assert_eq!(engine.variations().len(), 1);
+        assert_eq!(engine.variations()[0].decision(), &[(0, 1)]);
+
+        // a new line is added as variation
+        play(&mut engine, &[1, 1]);
+        assert_eq!(engine.data().0.history, vec![1, 3]);
+        assert!(engine.variations().is_empty());
+        assert!(engine.undo_last_decision());
+        assert_eq!(engine.variations().len(), 2);
+        assert_eq!(engine.variations()[0].main_line(), vec![(1, 1), (1, 1)]);
+        assert_eq!(
+            engine.variations()[1].main_line(),
+            vec![(0, 1), (1, 0), (0, 0)]
+        );
+
+        // switching to the old line
+        assert!(engine.redo_variation(1));
+        assert_eq!(engine.data().0.history, vec![1, 1]);
+        assert!(engine.redo_decision());
+        assert_eq!(engine.data().0.history, vec![1, 1, 2]);
+        assert!(!engine.redo_decision());
+        assert!(engine.undo_last_decision());
+        assert!(engine.undo_last_decision());
+        assert_eq!(engine.variations()[1].decision(), &[(0, 1)]);
+
+        // playing a known decision follows the existing variation
+        play(&mut engine, &[0]);
+        assert_eq!(engine.variations().len(), 1);
+        assert!(engine.undo_last_decision());
+
+        assert!(engine.log_mut().promote_variation(1));
+        assert_eq!(engine.variations()[0].decision(), &[(0, 1)]);
+        assert!(engine.log_mut().delete_variation(1).is_some());
+        assert!(!engine.log_mut().promote_variation(1));
+        assert!(engine.redo_decision());
+        assert!(engine.redo_decision());
+        assert_eq!(engine.data().0.history, vec![1, 1, 2]);
+    }
+
+    #[test]
+    fn bookmark_test() {
+        let mut engine = Engine::new_logging(2, RevCountingGame::new(100));
+        let start = engine.log_mut().bookmark_as("start");
+        play(&mut engine, &[0, 1, 0]);
+        let second = engine.log_mut().bookmark();
+        play(&mut engine, &[0, 0, 1]);
+        assert_eq!(engine.data().0.history, vec![1, 2, 1, 1]);
+
+        assert!(engine.reset_to(second));
+        assert_eq!(engine.data().0.history, vec![1, 2]);
+        assert!(engine.reset_to(start));
+        assert_eq!(engine.data().0.history, Vec::<u32>::new());
+        assert!(engine.reset_to(second));
+        assert_eq!(engine.data().0.history, vec![1, 2]);
+        assert_eq!(engine.log().named_bookmark("start"), Some(start));
+
+        // bookmarks are invalidated by changing the line
+        play(&mut engine, &[1, 1]);
+        assert!(engine.reset_to(start));
+        assert!(engine.log().is_valid(second));
+        play(&mut engine, &[1, 1]);
+        assert!(!engine.log().is_valid(second));
+        assert!(!engine.reset_to(second));
+        assert_eq!(engine.data().0.history, vec![3]);
+
+        // within a follow-up decision
+        play(&mut engine, &[1]);
+        assert!(engine.reset_to(start));
+        assert_eq!(engine.data().0.history, Vec::<u32>::new());
+        assert!(engine.log().is_valid(start));
+    }
 }
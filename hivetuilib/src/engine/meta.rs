@@ -0,0 +1,446 @@
+//! Out-of-band actions like resignations and draw offers, which are not modeled
+//! as decisions of the game.
+
+use std::{
+    fmt::{self, Display},
+    mem,
+};
+
+use crate::{DecisionObject, GameData, GameResult, Termination};
+
+use super::{Engine, EventListener, InternalState};
+
+/// An action of a player outside of the decisions of the game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MetaAction {
+    Resign(usize),
+    OfferDraw(usize),
+    /// Accepts the draw offer of another player.
+    AcceptDraw(usize),
+    DeclineDraw(usize),
+    /// The player exceeded the time (e.g. claimed by the opponent or flagged by a clock).
+    Timeout(usize),
+    Pause(usize),
+    Resume(usize),
+}
+
+impl MetaAction {
+    pub fn player(&self) -> usize {
+        match *self {
+            MetaAction::Resign(player)
+            | MetaAction::OfferDraw(player)
+            | MetaAction::AcceptDraw(player)
+            | MetaAction::DeclineDraw(player)
+            | MetaAction::Timeout(player)
+            | MetaAction::Pause(player)
+            | MetaAction::Resume(player) => player,
+        }
+    }
+}
+
+/// Status of the game resulting from meta actions.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MetaState {
+    paused: bool,
+    draw_offer: Option<usize>,
+    termination: Option<GameResult>,
+    /// The applied actions, each with the number of decisions completed before it.
+    actions: Vec<(usize, MetaAction)>,
+}
+
+impl MetaState {
+    /// While the game is paused, no options can be selected.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// The player who offered a draw. An offer expires when the next decision is completed.
+    pub fn draw_offer(&self) -> Option<usize> {
+        self.draw_offer
+    }
+
+    /// The result if the game was ended by a meta action.
+    pub fn termination(&self) -> Option<&GameResult> {
+        self.termination.as_ref()
+    }
+
+    pub(super) fn decision_completed(&mut self) {
+        self.draw_offer = None;
+    }
+
+    /// Reverts the actions after the undone decision and recomputes the status
+    /// from the remaining ones.
+    pub(super) fn decision_undone(&mut self, num_players: usize, decision_count: usize) {
+        self.recompute(num_players, decision_count, |n| n <= decision_count);
+    }
+
+    /// Reverts the actions after the last completed decision.
+    pub(super) fn actions_undone(&mut self, num_players: usize, decision_count: usize) {
+        self.recompute(num_players, decision_count, |n| n < decision_count);
+    }
+
+    fn recompute<F: Fn(usize) -> bool>(
+        &mut self,
+        num_players: usize,
+        decision_count: usize,
+        keep: F,
+    ) {
+        let actions = mem::take(&mut self.actions);
+        *self = Self::default();
+        for (n, action) in actions.into_iter().filter(|&(n, _)| keep(n)) {
+            self.apply(num_players, n, action);
+        }
+        if self
+            .actions
+            .last()
+            .is_some_and(|&(n, _)| n < decision_count)
+        {
+            self.decision_completed();
+        }
+    }
+
+    fn check(&self, num_players: usize, action: MetaAction) -> Result<(), MetaActionError> {
+        let player = action.player();
+        if player >= num_players {
+            return Err(MetaActionError::InvalidPlayer(player));
+        }
+        match action {
+            MetaAction::AcceptDraw(_) | MetaAction::DeclineDraw(_)
+                if self.draw_offer.map_or(true, |p| p == player) =>
+            {
+                Err(MetaActionError::NoDrawOffer)
+            }
+            MetaAction::Pause(_) if self.paused => Err(MetaActionError::AlreadyPaused),
+            MetaAction::Resume(_) if !self.paused => Err(MetaActionError::NotPaused),
+            _ => Ok(()),
+        }
+    }
+
+    /// Records the action after the given number of completed decisions and updates
+    /// the status. Returns whether the action finished the game.
+    fn apply(&mut self, num_players: usize, decision_count: usize, action: MetaAction) -> bool {
+        if self
+            .actions
+            .last()
+            .is_some_and(|&(n, _)| n < decision_count)
+        {
+            self.decision_completed();
+        }
+        self.actions.push((decision_count, action));
+        let player = action.player();
+        let termination = match action {
+            MetaAction::Resign(_) => lost_by(num_players, player, Termination::Resignation),
+            MetaAction::AcceptDraw(_) => {
+                GameResult::draw().with_termination(Termination::Agreement)
+            }
+            MetaAction::Timeout(_) => lost_by(num_players, player, Termination::Timeout),
+            MetaAction::OfferDraw(_) => {
+                self.draw_offer = Some(player);
+                return false;
+            }
+            MetaAction::DeclineDraw(_) => {
+                self.draw_offer = None;
+                return false;
+            }
+            MetaAction::Pause(_) => {
+                self.paused = true;
+                return false;
+            }
+            MetaAction::Resume(_) => {
+                self.paused = false;
+                return false;
+            }
+        };
+        self.draw_offer = None;
+        self.paused = false;
+        self.termination = Some(termination);
+        true
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetaActionError {
+    Finished,
+    /// Effects of a decision are partially applied.
+    PendingEffect,
+    InvalidPlayer(usize),
+    /// There is no draw offer of another player to accept or decline.
+    NoDrawOffer,
+    AlreadyPaused,
+    NotPaused,
+}
+
+impl Display for MetaActionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MetaActionError::Finished => write!(f, "the game is already finished"),
+            MetaActionError::PendingEffect => write!(f, "effects are partially applied"),
+            MetaActionError::InvalidPlayer(player) => write!(f, "invalid player {player}"),
+            MetaActionError::NoDrawOffer => write!(f, "no draw offer of another player"),
+            MetaActionError::AlreadyPaused => write!(f, "the game is already paused"),
+            MetaActionError::NotPaused => write!(f, "the game is not paused"),
+        }
+    }
+}
+
+/// The game is lost by the given player, i.e. all other players win.
+fn lost_by(num_players: usize, player: usize, termination: Termination) -> GameResult {
+    GameResult {
+        winners: (0..num_players).filter(|&p| p != player).collect(),
+        draw: false,
+        scores: Vec::new(),
+        termination,
+    }
+}
+
+impl<T: GameData, L: EventListener<T>, D: DecisionObject<T> + ?Sized> Engine<T, L, D> {
+    /// Applies the meta action and reports it to the listener. A pending decision chain is
+    /// retracted first. Resignations, accepted draw offers and timeouts finish the game,
+    /// with the corresponding termination reason in the result (see `Engine::result`).
+    /// Undoing the last decision also undoes the actions after it.
+    pub fn apply_meta_action(&mut self, action: MetaAction) -> Result<(), MetaActionError> {
+        match self.state {
+            InternalState::Finished => return Err(MetaActionError::Finished),
+            InternalState::PEffect(_, 0) => return Err(MetaActionError::PendingEffect),
+            _ => {}
+        }
+        self.meta.check(self.num_players, action)?;
+
+        self.retract_pending_chain();
+        let finished = self
+            .meta
+            .apply(self.num_players, self.decision_count, action);
+        self.listener.meta_action_applied(&action);
+        if finished {
+            self.state = InternalState::Finished;
+            self.listener
+                .game_finished(&self.data, self.meta.termination.as_ref());
+        }
+        Ok(())
+    }
+
+    pub fn meta_state(&self) -> &MetaState {
+        &self.meta
+    }
+
+    /// Outcome of a finished game: the termination by a meta action
+    /// or otherwise the result reported by the game data.
+    pub fn result(&self) -> Option<GameResult> {
+        match self.state {
+            InternalState::Finished => self.meta.termination.clone().or_else(|| self.data.result()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        engine::{
+            io::{
+                parse_saved_game, restore_game_state, save_game, CompatibilityPolicy, LoadGameError,
+            },
+            logging::{Event, SnapshotLog},
+            Engine, EngineError, GameState, LoggingEngine,
+        },
+        test::{play, CountingGame, RevCountingGame},
+        GameResult, Termination,
+    };
+
+    use super::{MetaAction, MetaActionError};
+
+    #[test]
+    fn resign_test() {
+        let mut engine = LoggingEngine::new_logging(2, CountingGame::new(20));
+        play(&mut engine, &[0, 1]);
+        // the pending decision chain is retracted
+        assert_eq!(engine.apply_meta_action(MetaAction::Resign(0)), Ok(()));
+        assert!(engine.is_finished());
+        assert!(matches!(
+            engine.log().log.last(),
+            Some(Event::Meta(MetaAction::Resign(0)))
+        ));
+        let expected = GameResult::win(1).with_termination(Termination::Resignation);
+        assert_eq!(engine.meta_state().termination(), Some(&expected));
+        match engine.pull() {
+            GameState::Finished(finished) => assert_eq!(finished.result(), Some(expected)),
+            _ => panic!("expected finished game"),
+        }
+        assert_eq!(
+            engine.apply_meta_action(MetaAction::Timeout(1)),
+            Err(MetaActionError::Finished)
+        );
+    }
+
+    #[test]
+    fn draw_offer_test() {
+        let mut engine = LoggingEngine::new_logging(2, CountingGame::new(20));
+        assert_eq!(
+            engine.apply_meta_action(MetaAction::AcceptDraw(1)),
+            Err(MetaActionError::NoDrawOffer)
+        );
+        assert_eq!(
+            engine.apply_meta_action(MetaAction::OfferDraw(2)),
+            Err(MetaActionError::InvalidPlayer(2))
+        );
+        engine.apply_meta_action(MetaAction::OfferDraw(0)).unwrap();
+        assert_eq!(engine.meta_state().draw_offer(), Some(0));
+        assert_eq!(
+            engine.apply_meta_action(MetaAction::AcceptDraw(0)),
+            Err(MetaActionError::NoDrawOffer)
+        );
+        // the offer expires with the next decision
+        play(&mut engine, &[0]);
+        assert_eq!(engine.meta_state().draw_offer(), None);
+
+        engine.apply_meta_action(MetaAction::Pause(1)).unwrap();
+        assert!(engine.meta_state().is_paused());
+        assert_eq!(
+            engine.apply_meta_action(MetaAction::Pause(0)),
+            Err(MetaActionError::AlreadyPaused)
+        );
+        engine.apply_meta_action(MetaAction::Resume(0)).unwrap();
+        engine.apply_meta_action(MetaAction::OfferDraw(1)).unwrap();
+        engine.apply_meta_action(MetaAction::AcceptDraw(0)).unwrap();
+        assert_eq!(
+            engine.result(),
+            Some(GameResult::draw().with_termination(Termination::Agreement))
+        );
+    }
+
+    #[test]
+    fn save_meta_test() {
+        let mut engine = LoggingEngine::new_logging(2, CountingGame::new(20));
+        engine.apply_meta_action(MetaAction::Pause(0)).unwrap();
+        engine.apply_meta_action(MetaAction::Resume(1)).unwrap();
+        play(&mut engine, &[1, 0]);
+        engine.apply_meta_action(MetaAction::OfferDraw(1)).unwrap();
+        engine
+            .apply_meta_action(MetaAction::DeclineDraw(0))
+            .unwrap();
+        play(&mut engine, &[0]);
+        engine.apply_meta_action(MetaAction::Timeout(1)).unwrap();
+        let log = engine.serialized_log();
+        assert_eq!(
+            log.meta,
+            [
+                (0, MetaAction::Pause(0)),
+                (0, MetaAction::Resume(1)),
+                (2, MetaAction::OfferDraw(1)),
+                (2, MetaAction::DeclineDraw(0)),
+                (3, MetaAction::Timeout(1)),
+            ]
+        );
+
+        let mut buffer = Vec::new();
+        save_game(&mut buffer, "Counting", [1, 0], [], 2, log.clone()).unwrap();
+        let content = String::from_utf8(buffer.clone()).unwrap();
+        assert!(content.ends_with("1P0\n0P0\nMO1\nMD0\n0P1\nMT1\nC\n"));
+        let (_, num_players, parsed) = parse_saved_game(
+            buffer.as_slice(),
+            "Counting",
+            [1, 0],
+            CompatibilityPolicy::MinorEqual,
+        )
+        .unwrap();
+        assert_eq!(parsed, log);
+        let restored =
+            restore_game_state(num_players, || Ok(CountingGame::new(20)), parsed).unwrap();
+        assert_eq!(restored.data().history, [2, 1]);
+        assert_eq!(restored.result(), engine.result());
+        assert!(restored.result().unwrap().is_winner(0));
+        assert_eq!(restored.serialized_log(), log);
+    }
+
+    #[test]
+    fn undo_termination_test() {
+        let mut engine = LoggingEngine::new_logging(2, RevCountingGame::new(20));
+        play(&mut engine, &[0, 0]);
+        engine.apply_meta_action(MetaAction::Resign(1)).unwrap();
+        assert!(engine.is_finished());
+
+        assert!(engine.undo_last_decision());
+        assert!(!engine.is_finished());
+        assert_eq!(engine.meta_state().termination(), None);
+        assert!(engine.serialized_log().meta.is_empty());
+        assert_eq!(engine.data().0.history, [1]);
+    }
+
+    #[test]
+    fn pause_test() {
+        let mut engine = LoggingEngine::new_logging(2, RevCountingGame::new(20));
+        play(&mut engine, &[0]);
+        engine.apply_meta_action(MetaAction::Pause(1)).unwrap();
+        match engine.pull() {
+            GameState::PendingDecision(dec) => {
+                assert_eq!(dec.try_select_option(0), Err(EngineError::Paused))
+            }
+            _ => panic!("expected pending decision"),
+        }
+        assert!(!engine.redo_decision());
+
+        // a saved game with a decision during the pause is rejected
+        let mut log = engine.serialized_log();
+        log.log.push((0, 1));
+        let restored = restore_game_state(2, || Ok(RevCountingGame::new(20)), log);
+        assert!(matches!(
+            restored,
+            Err(LoadGameError::GamePaused { decision_nr: 1 })
+        ));
+
+        engine.apply_meta_action(MetaAction::Resume(0)).unwrap();
+        play(&mut engine, &[0]);
+        assert_eq!(engine.data().0.history, [1, 1]);
+    }
+
+    #[test]
+    fn undo_meta_test() {
+        let mut engine = LoggingEngine::new_logging(2, RevCountingGame::new(20));
+        play(&mut engine, &[0]);
+        engine.apply_meta_action(MetaAction::OfferDraw(0)).unwrap();
+        play(&mut engine, &[0]);
+        engine.apply_meta_action(MetaAction::Pause(0)).unwrap();
+
+        // the status before the undone decision is restored
+        assert!(engine.undo_last_decision());
+        assert!(!engine.meta_state().is_paused());
+        assert_eq!(engine.meta_state().draw_offer(), Some(0));
+        assert_eq!(
+            engine.serialized_log().meta,
+            [(1, MetaAction::OfferDraw(0))]
+        );
+
+        // the offer expires with the redone decision
+        assert!(engine.redo_decision());
+        assert_eq!(engine.meta_state().draw_offer(), None);
+        assert_eq!(engine.data().0.history, [1, 1]);
+    }
+
+    #[test]
+    fn undo_meta_only_test() {
+        let mut engine = LoggingEngine::new_logging(2, RevCountingGame::new(20));
+        engine.apply_meta_action(MetaAction::OfferDraw(1)).unwrap();
+        engine.apply_meta_action(MetaAction::Resign(0)).unwrap();
+        assert!(engine.undo_last_decision());
+        assert!(!engine.is_finished());
+        assert_eq!(engine.meta_state().draw_offer(), None);
+        assert!(engine.serialized_log().meta.is_empty());
+        assert!(!engine.undo_last_decision());
+
+        let data = CountingGame::new(20);
+        let log = SnapshotLog::new(&data);
+        let mut engine = Engine::with_listener(2, data, log);
+        play(&mut engine, &[0]);
+        engine.apply_meta_action(MetaAction::Resign(1)).unwrap();
+        assert!(engine.undo_last_decision());
+        assert!(!engine.is_finished());
+        assert!(engine.data().history.is_empty());
+
+        engine.apply_meta_action(MetaAction::Pause(1)).unwrap();
+        assert!(engine.undo_last_decision());
+        assert!(!engine.meta_state().is_paused());
+        assert!(engine.serialized_log().meta.is_empty());
+        assert!(!engine.undo_last_decision());
+    }
+}
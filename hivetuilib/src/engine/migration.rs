@@ -0,0 +1,397 @@
+use std::ops::RangeInclusive;
+
+use crate::GameData;
+
+use super::{
+    io::{
+        check_option, drain_prefix_chains, pending_decision, restore_game_state_impl,
+        LoadGameError, SerializedLog, SerializedVariation,
+    },
+    logging::EventLog,
+    LoggingEngine, PendingDecision,
+};
+
+/// Rewrites the key-value pairs of the initial state.
+pub type StateMigration = Box<dyn Fn(&mut Vec<(String, String)>) -> Result<(), String>>;
+
+/// Maps the index stored in the save to the index of the corresponding option,
+/// given the pending decision of the replayed game.
+pub type IndexMigration<T> =
+    Box<dyn Fn(&PendingDecision<T, EventLog<T>>, usize) -> Result<usize, String>>;
+
+/// Upgrades saves with a version in the given range to the target version.
+pub struct Migration<T: GameData> {
+    versions: RangeInclusive<[u32; 2]>,
+    target: [u32; 2],
+    state: Option<StateMigration>,
+    indices: Option<IndexMigration<T>>,
+}
+
+impl<T: GameData> Migration<T> {
+    pub fn new(versions: RangeInclusive<[u32; 2]>, target: [u32; 2]) -> Self {
+        Self {
+            versions,
+            target,
+            state: None,
+            indices: None,
+        }
+    }
+
+    pub fn with_state<F>(mut self, migrate_state: F) -> Self
+    where
+        F: Fn(&mut Vec<(String, String)>) -> Result<(), String> + 'static,
+    {
+        self.state = Some(Box::new(migrate_state));
+        self
+    }
+
+    pub fn with_indices<F>(mut self, migrate_index: F) -> Self
+    where
+        F: Fn(&PendingDecision<T, EventLog<T>>, usize) -> Result<usize, String> + 'static,
+    {
+        self.indices = Some(Box::new(migrate_index));
+        self
+    }
+
+    pub fn versions(&self) -> &RangeInclusive<[u32; 2]> {
+        &self.versions
+    }
+
+    pub fn target(&self) -> [u32; 2] {
+        self.target
+    }
+}
+
+/// Collection of migrations that are used to upgrade saves of older game versions.
+///
+/// Starting with the version of the save, the first migration whose range contains the
+/// current version is applied, until no migration matches. Index migrations are called
+/// on the replayed game of the current version, in the order of the applied migrations.
+pub struct MigrationRegistry<T: GameData> {
+    migrations: Vec<Migration<T>>,
+}
+
+impl<T: GameData> Default for MigrationRegistry<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: GameData> MigrationRegistry<T> {
+    pub fn new() -> Self {
+        Self {
+            migrations: Vec::new(),
+        }
+    }
+
+    pub fn add_migration(&mut self, migration: Migration<T>) -> &mut Self {
+        self.migrations.push(migration);
+        self
+    }
+
+    /// Migrations that are applied to a save with the given version and the resulting version.
+    pub fn migration_path(&self, save_version: [u32; 2]) -> (Vec<&Migration<T>>, [u32; 2]) {
+        let mut path = Vec::new();
+        let mut version = save_version;
+        // requiring a higher target version guarantees termination
+        while let Some(migration) = self
+            .migrations
+            .iter()
+            .find(|m| m.versions.contains(&version) && m.target > version)
+        {
+            path.push(migration);
+            version = migration.target;
+        }
+        (path, version)
+    }
+
+    /// Rewrites the initial state and returns the resulting version.
+    pub fn migrate_initial_state(
+        &self,
+        save_version: [u32; 2],
+        initial_state: &mut Vec<(String, String)>,
+    ) -> Result<[u32; 2], LoadGameError> {
+        let (path, version) = self.migration_path(save_version);
+        for migration in path {
+            if let Some(migrate_state) = &migration.state {
+                migrate_state(initial_state)
+                    .map_err(|msg| LoadGameError::MigrationFailed { msg })?;
+            }
+        }
+        Ok(version)
+    }
+
+    /// Remaps the decision indices of the log (including redo stack and variations)
+    /// by replaying the game with the already migrated initial state.
+    pub fn migrate_log<F>(
+        &self,
+        save_version: [u32; 2],
+        num_players: usize,
+        create_data: F,
+        log: SerializedLog,
+    ) -> Result<SerializedLog, LoadGameError>
+    where
+        F: Fn() -> Result<T, String>,
+    {
+        let (path, _) = self.migration_path(save_version);
+        let path = path
+            .into_iter()
+            .filter_map(|m| m.indices.as_ref())
+            .collect::<Vec<_>>();
+        if path.is_empty() {
+            return Ok(log);
+        }
+        let remap = |decision: &PendingDecision<T, EventLog<T>>, index| {
+            path.iter()
+                .try_fold(index, |index, migrate_index| migrate_index(decision, index))
+        };
+
+        let mut engine = LoggingEngine::new_logging(num_players, create_data()?);
+        let new_log = remap_line(&mut engine, 0, &log.log, &remap)?;
+        let mut redo_stack = Vec::new();
+        let mut variations = Vec::new();
+        if !log.redo_stack.is_empty() || !log.variations.is_empty() {
+            // a pending follow-up decision is not part of the redo stack
+            let prefix = &new_log[..new_log.len() - engine.pending_chain_len()];
+            let mut engine = restore_game_state_impl(num_players, create_data()?, prefix.iter())?;
+            let redo_line = log.redo_stack.iter().rev().copied().collect::<Vec<_>>();
+            redo_stack = remap_line(&mut engine, prefix.len(), &redo_line, &remap)?;
+            redo_stack.reverse();
+            let chains = engine.log().decision_chains();
+            for variation in log.variations {
+                variations.push(migrate_variation(
+                    num_players,
+                    &create_data,
+                    &remap,
+                    &[],
+                    &chains,
+                    variation,
+                )?);
+            }
+        }
+        Ok(SerializedLog {
+            log: new_log,
+            redo_stack,
+            variations,
+            hashes: log.hashes,
+            meta: log.meta,
+        })
+    }
+}
+
+fn migrate_variation<T: GameData, F, M>(
+    num_players: usize,
+    create_data: &F,
+    remap: &M,
+    prefix: &[(usize, usize)],
+    parent_chains: &[Vec<(usize, usize)>],
+    variation: SerializedVariation,
+) -> Result<SerializedVariation, LoadGameError>
+where
+    F: Fn() -> Result<T, String>,
+    M: Fn(&PendingDecision<T, EventLog<T>>, usize) -> Result<usize, String>,
+{
+    if variation.position > parent_chains.len() {
+        return Err(LoadGameError::InvalidVariation {
+            position: variation.position,
+            index: variation.index,
+        });
+    }
+    let mut prefix = prefix.to_vec();
+    prefix.extend(parent_chains[..variation.position].iter().flatten());
+    let mut engine = restore_game_state_impl(num_players, create_data()?, prefix.iter())?;
+    let line = remap_line(&mut engine, prefix.len(), &variation.line, remap)?;
+    let mut chains = engine.log().decision_chains();
+    drain_prefix_chains(&mut chains, prefix.len());
+    let mut variations = Vec::new();
+    for child in variation.variations {
+        variations.push(migrate_variation(
+            num_players,
+            create_data,
+            remap,
+            &prefix,
+            &chains,
+            child,
+        )?);
+    }
+    Ok(SerializedVariation {
+        position: variation.position,
+        index: variation.index,
+        line,
+        variations,
+    })
+}
+
+/// Applies the given options after remapping their indices and returns the remapped options.
+/// `start` is the number of options applied before the line, used for error reporting.
+fn remap_line<T: GameData, M>(
+    engine: &mut LoggingEngine<T>,
+    start: usize,
+    line: &[(usize, usize)],
+    remap: &M,
+) -> Result<Vec<(usize, usize)>, LoadGameError>
+where
+    M: Fn(&PendingDecision<T, EventLog<T>>, usize) -> Result<usize, String>,
+{
+    let mut result = Vec::with_capacity(line.len());
+    for (i, &(index, player)) in line.iter().enumerate() {
+        let decision_nr = start + i;
+        let decision = pending_decision(engine, decision_nr)?;
+        let index = remap(&decision, index).map_err(|msg| LoadGameError::MigrationFailed {
+            msg: format!("{msg} at decision number {decision_nr}"),
+        })?;
+        check_option(&decision, decision_nr, index, player)?;
+        decision.apply_option(index);
+        result.push((index, player));
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        engine::{
+            io::{load_migrated_game, save_game, CompatibilityPolicy, LoadGameError},
+            logging::EventLog,
+            LoggingEngine, PendingDecision,
+        },
+        test::{play, RevCountingGame},
+    };
+
+    use super::{Migration, MigrationRegistry};
+
+    fn rename_key(state: &mut Vec<(String, String)>) -> Result<(), String> {
+        let (key, _) = state.first_mut().ok_or("missing key")?;
+        *key = "finished_at".to_string();
+        state.push(("mode".to_string(), "classic".to_string()));
+        Ok(())
+    }
+
+    fn swap_top_level(
+        decision: &PendingDecision<RevCountingGame, EventLog<RevCountingGame>>,
+        index: usize,
+    ) -> Result<usize, String> {
+        Ok(if decision.level_in_chain() == 0 {
+            1 - index
+        } else {
+            index
+        })
+    }
+
+    #[test]
+    fn migration_test() {
+        let mut engine = LoggingEngine::new_logging(2, RevCountingGame::new(100));
+        play(&mut engine, &[0, 1, 1, 0, 1]);
+        assert!(engine.undo_last_decision());
+        assert!(engine.undo_last_decision());
+        play(&mut engine, &[1, 0]);
+        assert!(engine.undo_last_decision());
+        let mut buffer = Vec::new();
+        save_game(
+            &mut buffer,
+            "Counting",
+            [1, 0],
+            [("end".to_string(), "100".to_string())],
+            2,
+            engine.serialized_log(),
+        )
+        .unwrap();
+
+        let mut migrations = MigrationRegistry::new();
+        migrations
+            .add_migration(
+                Migration::new([1, 0]..=[1, 0], [1, 1])
+                    .with_state(rename_key)
+                    .with_indices(swap_top_level),
+            )
+            .add_migration(Migration::new([1, 1]..=[1, 9], [2, 0]).with_indices(swap_top_level));
+        assert_eq!(migrations.migration_path([1, 0]).1, [2, 0]);
+        assert_eq!(migrations.migration_path([1, 5]).0.len(), 1);
+        assert_eq!(migrations.migration_path([2, 0]).0.len(), 0);
+
+        let load = |version, migrations: &MigrationRegistry<RevCountingGame>| {
+            load_migrated_game(
+                buffer.as_slice(),
+                "Counting",
+                version,
+                CompatibilityPolicy::MinorEqual,
+                migrations,
+                |state| {
+                    if state[0].0 != "finished_at" {
+                        return Err(format!("unknown key: {}", state[0].0));
+                    }
+                    Ok(RevCountingGame::new(state[0].1.parse().unwrap()))
+                },
+            )
+        };
+        // swapping twice restores the original game
+        let loaded = load([2, 0], &migrations).unwrap();
+        assert_eq!(loaded.data(), engine.data());
+        assert_eq!(loaded.serialized_log(), engine.serialized_log());
+        assert_eq!(loaded.variations(), engine.variations());
+        assert!(matches!(
+            load([2, 1], &migrations),
+            Err(LoadGameError::VersionMismatch { save: [2, 0], .. })
+        ));
+        assert!(matches!(
+            load([1, 0], &MigrationRegistry::new()),
+            Err(LoadGameError::InvalidFileContent { .. })
+        ));
+
+        // an option that no longer exists can not be migrated
+        let mut migrations = MigrationRegistry::new();
+        migrations.add_migration(
+            Migration::new([1, 0]..=[1, 0], [1, 1])
+                .with_state(rename_key)
+                .with_indices(|decision, index| match (decision.level_in_chain(), index) {
+                    (0, 0) => Ok(0),
+                    (0, _) => Err("removed option".to_string()),
+                    _ => Ok(1 - index),
+                }),
+        );
+        assert!(matches!(
+            load([1, 1], &migrations),
+            Err(LoadGameError::MigrationFailed { .. })
+        ));
+    }
+
+    #[test]
+    fn migration_error_position_test() {
+        let mut engine = LoggingEngine::new_logging(2, RevCountingGame::new(100));
+        play(&mut engine, &[0, 0, 1, 0]);
+        assert!(engine.undo_last_decision());
+        let mut buffer = Vec::new();
+        save_game(
+            &mut buffer,
+            "Counting",
+            [1, 0],
+            [],
+            2,
+            engine.serialized_log(),
+        )
+        .unwrap();
+
+        let mut migrations = MigrationRegistry::new();
+        migrations.add_migration(Migration::new([1, 0]..=[1, 0], [1, 1]).with_indices(
+            |decision, index| match (decision.level_in_chain(), index) {
+                (0, 1) => Err("removed option".to_string()),
+                _ => Ok(index),
+            },
+        ));
+        let result = load_migrated_game(
+            buffer.as_slice(),
+            "Counting",
+            [1, 1],
+            CompatibilityPolicy::MinorEqual,
+            &migrations,
+            |_| Ok(RevCountingGame::new(100)),
+        );
+        // the failing option is the first one of the redo stack
+        match result {
+            Err(LoadGameError::MigrationFailed { msg }) => {
+                assert_eq!(msg, "removed option at decision number 2")
+            }
+            _ => panic!("expected failed migration"),
+        }
+    }
+}
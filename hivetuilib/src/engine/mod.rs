@@ -1,19 +1,33 @@
 pub mod abstract_engine;
+pub mod chance;
+pub mod clock;
 mod concrete_engine;
 pub mod io;
+mod listeners;
 pub mod logging;
+pub mod meta;
+pub mod migration;
+pub mod remote;
+pub mod replay;
+pub mod runner;
 
 pub use concrete_engine::*;
 use io::SerializedLog;
+pub use listeners::*;
 
 use std::{
     fmt::{self, Debug},
     mem,
 };
 
-use crate::{Decision, Effect, GameData, Outcome, RevEffect};
+use crate::{
+    Decision, DecisionObject, Effect, GameData, GameResult, Outcome, SendDecision, SendGameData,
+};
 
-use self::logging::EventLog;
+use self::{
+    logging::{AsEventLog, Bookmark, DecisionLog, EventLog, SnapshotLog, UndoLog, Variation},
+    meta::{MetaAction, MetaState},
+};
 
 const INTERNAL_ERROR: &str = "Internal error - invalid state";
 
@@ -22,17 +36,85 @@ pub enum CloneError {
     /// Cloning is not possible in pending effect state,
     /// the state must be either pending decision or finished.
     PendingEffect,
-    /// Cloning is not possible for a pending follow-up decision,
-    /// the state must be either a pending top-level decision or finished.
+    /// Not returned anymore, since pending follow-up decisions are reconstructed.
+    #[deprecated(note = "follow-up decisions can be cloned")]
     FollowUp,
 }
 
+/// Error for invalid input to the engine, which leaves the engine unchanged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngineError {
+    /// The index is not valid for the pending decision.
+    InvalidOption { index: usize, option_count: usize },
+    /// Retracting from n subdecisions requires n <= level in the decision chain.
+    InvalidRetraction { n: usize, level_in_chain: usize },
+    /// No option can be selected while the game is paused (see `MetaAction::Pause`).
+    Paused,
+}
+
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EngineError::InvalidOption {
+                index,
+                option_count,
+            } => write!(
+                f,
+                "invalid option {index} for decision with {option_count} options"
+            ),
+            EngineError::InvalidRetraction { n, level_in_chain } => write!(
+                f,
+                "can not retract from {n} subdecisions at level {level_in_chain}"
+            ),
+            EngineError::Paused => write!(f, "the game is paused"),
+        }
+    }
+}
+
+/// Receives the events of an engine, e.g. to log the decisions.
+///
+/// Several listeners can be combined as a tuple, a `Vec<Box<dyn EventListener<T>>>` or a
+/// `ListenerChain`. Since effects are passed by value, only the first member of such a
+/// composite listener receives them via `effect_applied`. All other members are notified
+/// via `effect_observed` instead, so both must be handled by listeners that track effects.
 pub trait EventListener<T: GameData> {
     fn effect_applied(&mut self, effect: Box<T::EffectType>);
 
+    /// Called instead of `effect_applied` if another member of a composite listener
+    /// takes ownership of the effect (see `ListenerChain`). Listeners that require
+    /// ownership of the effects (e.g. `EventLog`) panic.
+    fn effect_observed(&mut self, effect: &T::EffectType);
+
     fn option_selected(&mut self, index: usize, player: usize);
 
     fn retracted_by_n(&mut self, n: usize);
+
+    /// Called after all effects resulting from a decision are applied.
+    fn effects_completed(&mut self, _data: &T) {}
+
+    /// Called whenever a decision becomes pending. This includes follow-up decisions
+    /// as well as decisions that are pending again after a retraction or an undo.
+    fn decision_pending(
+        &mut self,
+        _data: &T,
+        _player: usize,
+        _option_count: usize,
+        _level_in_chain: usize,
+    ) {
+    }
+
+    /// Called after the last decision was undone.
+    fn decision_undone(&mut self, _data: &T) {}
+
+    /// Called after a decision (including its effects) was redone.
+    fn decision_redone(&mut self, _data: &T) {}
+
+    /// Called when the game is finished after applying a decision or a meta action.
+    fn game_finished(&mut self, _data: &T, _result: Option<&GameResult>) {}
+
+    /// Called after a meta action was applied (see `Engine::apply_meta_action`).
+    /// If the action ends the game, `game_finished` is called afterwards.
+    fn meta_action_applied(&mut self, _action: &MetaAction) {}
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -41,28 +123,45 @@ pub struct NotListening {}
 impl<T: GameData> EventListener<T> for NotListening {
     fn effect_applied(&mut self, _effect: Box<T::EffectType>) {}
 
+    fn effect_observed(&mut self, _effect: &T::EffectType) {}
+
     fn option_selected(&mut self, _index: usize, _player: usize) {}
 
     fn retracted_by_n(&mut self, _n: usize) {}
 }
 
-enum InternalState<T: GameData> {
-    PEffect(Box<T::EffectType>),
-    PDecision(Box<dyn Decision<T>>, Vec<Box<dyn Decision<T>>>),
+enum InternalState<T: GameData, D: ?Sized> {
+    /// Pending effect and the number of selected options whose effects were not applied yet.
+    PEffect(Box<T::EffectType>, usize),
+    /// Top-level decision and the follow-up decisions, each with the index selected in its parent.
+    PDecision(Box<D>, Vec<(usize, Box<D>)>),
     Finished,
     Invalid,
 }
 
-pub struct Engine<T: GameData, L: EventListener<T> = NotListening> {
-    state: InternalState<T>,
+/// `D` is the type of the stored decisions, which is `dyn SendDecision<T>` for a `SendEngine`.
+pub struct Engine<
+    T: GameData,
+    L: EventListener<T> = NotListening,
+    D: DecisionObject<T> + ?Sized = dyn Decision<T>,
+> {
+    state: InternalState<T, D>,
     // TODO: use mutable reference instead?
     data: T,
     listener: L,
     num_players: usize,
+    /// Number of completed decisions (chains whose effects were applied).
+    decision_count: usize,
+    meta: MetaState,
 }
 
 pub type LoggingEngine<T> = Engine<T, EventLog<T>>;
 
+pub type SnapshotEngine<T> = Engine<T, SnapshotLog<T>>;
+
+/// An engine that can be sent to another thread, see `SendGameData`.
+pub type SendEngine<T, L = NotListening> = Engine<T, L, dyn SendDecision<T>>;
+
 impl<T: GameData> Engine<T> {
     pub fn new(num_players: usize, data: T) -> Self {
         Self::with_listener(num_players, data, NotListening {})
@@ -75,15 +174,43 @@ impl<T: GameData> Engine<T, EventLog<T>> {
     }
 }
 
+impl<T: GameData + Clone> Engine<T, SnapshotLog<T>> {
+    pub fn new_snapshot_logging(num_players: usize, data: T) -> Self {
+        let log = SnapshotLog::new(&data);
+        Self::with_listener(num_players, data, log)
+    }
+}
+
 impl<T: GameData, L: EventListener<T>> Engine<T, L> {
     pub fn with_listener(num_players: usize, data: T, listener: L) -> Self {
+        Self::create(num_players, data, listener)
+    }
+}
+
+impl<T: SendGameData> SendEngine<T> {
+    pub fn new_send(num_players: usize, data: T) -> Self {
+        Self::create(num_players, data, NotListening {})
+    }
+}
+
+impl<T: SendGameData, L: EventListener<T>> SendEngine<T, L> {
+    pub fn send_with_listener(num_players: usize, data: T, listener: L) -> Self {
+        Self::create(num_players, data, listener)
+    }
+}
+
+impl<T: GameData, L: EventListener<T>, D: DecisionObject<T> + ?Sized> Engine<T, L, D> {
+    fn create(num_players: usize, data: T, listener: L) -> Self {
         let mut result = Self {
             state: InternalState::Invalid,
             data,
             listener,
             num_players,
+            decision_count: 0,
+            meta: MetaState::default(),
         };
         result.state = result.fetch_next_state();
+        result.notify_pending();
         result
     }
 
@@ -94,10 +221,25 @@ impl<T: GameData, L: EventListener<T>> Engine<T, L> {
             _ => false,
         }
     }
+
+    /// Number of completed decisions, i.e. decision chains whose effects were applied.
+    /// Undone decisions are not counted.
+    pub fn decision_count(&self) -> usize {
+        self.decision_count
+    }
+
+    /// Indices of the options selected in the pending decision chain,
+    /// starting with the top-level decision (empty if there is none).
+    pub fn pending_chain(&self) -> Vec<usize> {
+        match &self.state {
+            InternalState::PDecision(_, stack) => stack.iter().map(|&(index, _)| index).collect(),
+            _ => Vec::new(),
+        }
+    }
 }
 
-impl<T: GameData + Clone, L: EventListener<T>> Engine<T, L> {
-    pub fn try_clone_data(&self) -> Result<Engine<T>, CloneError> {
+impl<T: GameData + Clone, L: EventListener<T>, D: DecisionObject<T> + ?Sized> Engine<T, L, D> {
+    pub fn try_clone_data(&self) -> Result<Engine<T, NotListening, D>, CloneError> {
         self.try_clone_with_listener(NotListening {})
     }
 
@@ -105,40 +247,105 @@ impl<T: GameData + Clone, L: EventListener<T>> Engine<T, L> {
     where
         L: Clone,
     {
-        self.try_clone_with_listener(self.listener.clone())
+        // the cloned listener already knows about the pending decision chain
+        self.clone_impl(self.listener.clone(), false)
     }
 
+    /// Clones the engine with a new listener. A pending decision chain is reconstructed
+    /// by replaying the selected options, which are reported to the listener together
+    /// with the restored follow-up decision.
     pub fn try_clone_with_listener<M: EventListener<T>>(
         &self,
         listener: M,
-    ) -> Result<Engine<T, M>, CloneError> {
-        let state = match &self.state {
-            InternalState::PEffect(_) => {
-                return Err(CloneError::PendingEffect);
-            }
-            InternalState::PDecision(_, stack) => {
-                if stack.is_empty() {
-                    self.fetch_next_state()
-                } else {
-                    return Err(CloneError::FollowUp);
-                }
-            }
-            InternalState::Finished => InternalState::Finished,
-            InternalState::Invalid => panic!("{}", INTERNAL_ERROR),
-        };
-        Ok(Engine {
-            state,
+    ) -> Result<Engine<T, M, D>, CloneError> {
+        self.clone_impl(listener, true)
+    }
+
+    fn clone_impl<M: EventListener<T>>(
+        &self,
+        listener: M,
+        notify: bool,
+    ) -> Result<Engine<T, M, D>, CloneError> {
+        let (finished, chain) = self.reconstruction_info()?;
+        let mut engine = Engine {
+            state: InternalState::Invalid,
             data: self.data.clone(),
             listener,
             num_players: self.num_players,
-        })
+            decision_count: self.decision_count,
+            meta: self.meta.clone(),
+        };
+        engine.reconstruct_state(finished, &chain, notify);
+        Ok(engine)
     }
 }
 
-impl<T: GameData + Debug, L: EventListener<T> + Debug> Debug for Engine<T, L> {
+impl<T: GameData, L: EventListener<T>, D: DecisionObject<T> + ?Sized> Engine<T, L, D> {
+    /// Converts the engine to an engine that stores decisions of type `E`, e.g. an engine
+    /// restored from a saved game to a `SendEngine` or the other way round. The pending
+    /// decision is fetched again and a pending decision chain is reconstructed by replaying
+    /// the selected options. Fails in pending effect state, returning the unchanged engine.
+    pub fn try_convert<E: DecisionObject<T> + ?Sized>(self) -> Result<Engine<T, L, E>, Box<Self>> {
+        let Ok((finished, chain)) = self.reconstruction_info() else {
+            return Err(Box::new(self));
+        };
+        let mut engine = Engine {
+            state: InternalState::Invalid,
+            data: self.data,
+            listener: self.listener,
+            num_players: self.num_players,
+            decision_count: self.decision_count,
+            meta: self.meta,
+        };
+        // the listener already knows about the pending decision chain
+        engine.reconstruct_state(finished, &chain, false);
+        Ok(engine)
+    }
+
+    /// Whether the game is finished and the pending decision chain, which is
+    /// required to reconstruct the state with newly fetched decisions.
+    fn reconstruction_info(&self) -> Result<(bool, Vec<usize>), CloneError> {
+        match &self.state {
+            InternalState::PEffect(..) => Err(CloneError::PendingEffect),
+            InternalState::PDecision(..) => Ok((false, self.pending_chain())),
+            InternalState::Finished => Ok((true, Vec::new())),
+            InternalState::Invalid => panic!("{}", INTERNAL_ERROR),
+        }
+    }
+
+    fn reconstruct_state(&mut self, finished: bool, chain: &[usize], notify: bool) {
+        self.state = if finished {
+            InternalState::Finished
+        } else {
+            self.fetch_next_state()
+        };
+        // reconstruct the pending follow-up decisions by replaying the selected options
+        for &index in chain {
+            let player = self.decision().player();
+            self.select_and_apply_option(index);
+            assert!(
+                matches!(self.state, InternalState::PDecision(..)),
+                "Replaying the pending decision chain must result in the same follow-up decisions"
+            );
+            if notify {
+                self.listener.option_selected(index, player);
+            }
+        }
+        if notify && !chain.is_empty() {
+            self.notify_pending();
+        }
+    }
+}
+
+impl<T, L, D> Debug for Engine<T, L, D>
+where
+    T: GameData + Debug,
+    L: EventListener<T> + Debug,
+    D: DecisionObject<T> + ?Sized,
+{
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let state_str = match self.state {
-            InternalState::PEffect(_) => "PendingEffect",
+            InternalState::PEffect(..) => "PendingEffect",
             InternalState::PDecision(_, _) => "PendingDecision",
             InternalState::Finished => "Finished",
             InternalState::Invalid => "INVALID",
@@ -172,14 +379,40 @@ trait PDecisionState {
     fn retract_n(&mut self, n: usize) -> bool;
 
     fn retract_all(&mut self);
+
+    fn is_paused(&self) -> bool;
+
+    fn check_option(&self, index: usize) -> Result<(), EngineError> {
+        let option_count = self.option_count();
+        if self.is_paused() {
+            Err(EngineError::Paused)
+        } else if index < option_count {
+            Ok(())
+        } else {
+            Err(EngineError::InvalidOption {
+                index,
+                option_count,
+            })
+        }
+    }
+
+    fn try_retract_n(&mut self, n: usize) -> Result<(), EngineError> {
+        let level_in_chain = self.level_in_chain();
+        if self.retract_n(n) {
+            Ok(())
+        } else {
+            Err(EngineError::InvalidRetraction { n, level_in_chain })
+        }
+    }
 }
 
-impl<T: GameData, L: EventListener<T>> Engine<T, L> {
-    fn fetch_next_state(&self) -> InternalState<T> {
-        match self.data.next_decision() {
+impl<T: GameData, L: EventListener<T>, D: DecisionObject<T> + ?Sized> Engine<T, L, D> {
+    fn fetch_next_state(&self) -> InternalState<T, D> {
+        match D::next_decision(&self.data) {
             Some(decision) => {
+                // the player of a chance decision does not need to be a player of the game
                 assert!(
-                    decision.player() < self.num_players,
+                    decision.player() < self.num_players || decision.chance_weights().is_some(),
                     "Illegal player for decision: {:?}",
                     decision.player()
                 );
@@ -193,47 +426,76 @@ impl<T: GameData, L: EventListener<T>> Engine<T, L> {
         }
     }
 
+    /// Continues with the next state after all effects of a decision are applied.
+    fn complete_effects(&mut self) {
+        self.decision_count += 1;
+        self.meta.decision_completed();
+        self.listener.effects_completed(&self.data);
+        self.enter_next_state();
+    }
+
+    fn enter_next_state(&mut self) {
+        self.state = self.fetch_next_state();
+        if let InternalState::Finished = self.state {
+            self.listener
+                .game_finished(&self.data, self.data.result().as_ref());
+        } else {
+            self.notify_pending();
+        }
+    }
+
+    fn notify_pending(&mut self) {
+        if let InternalState::PDecision(_, stack) = &self.state {
+            let level = stack.len();
+            let decision = self.decision();
+            let (player, option_count) = (decision.player(), decision.option_count());
+            self.listener
+                .decision_pending(&self.data, player, option_count, level);
+        }
+    }
+
     fn take_effect(&mut self) -> Box<T::EffectType> {
         let state = mem::replace(&mut self.state, InternalState::Invalid);
         match state {
-            InternalState::PEffect(effect) => effect,
+            InternalState::PEffect(effect, _) => effect,
             _ => panic!("{}", INTERNAL_ERROR),
         }
     }
 
     fn select_and_apply_option(&mut self, index: usize) {
-        match self.decision().select_option(&self.data, index) {
+        let chain_len = self.decision_stack().len() + 1;
+        match self.decision().select_outcome(&self.data, index) {
             Outcome::Effect(effect) => {
-                self.state = InternalState::PEffect(effect);
+                self.state = InternalState::PEffect(effect, chain_len);
             }
             Outcome::FollowUp(decision) => {
-                self.decision_stack_mut().push(decision);
+                self.decision_stack_mut().push((index, decision));
             }
         }
     }
 
-    fn decision_stack(&self) -> &Vec<Box<dyn Decision<T>>> {
+    fn decision_stack(&self) -> &Vec<(usize, Box<D>)> {
         match &self.state {
             InternalState::PDecision(_, stack) => stack,
             _ => panic!("{}", INTERNAL_ERROR),
         }
     }
 
-    fn decision_stack_mut(&mut self) -> &mut Vec<Box<dyn Decision<T>>> {
+    fn decision_stack_mut(&mut self) -> &mut Vec<(usize, Box<D>)> {
         match &mut self.state {
             InternalState::PDecision(_, stack) => stack,
             _ => panic!("{}", INTERNAL_ERROR),
         }
     }
 
-    fn decision(&self) -> &dyn Decision<T> {
+    fn decision(&self) -> &D {
         self.get_decision().expect(INTERNAL_ERROR)
     }
 
-    fn get_decision(&self) -> Option<&dyn Decision<T>> {
+    fn get_decision(&self) -> Option<&D> {
         match &self.state {
             InternalState::PDecision(bottom, stack) => match stack.last() {
-                Some(decision) => Some(decision),
+                Some((_, decision)) => Some(decision),
                 None => Some(bottom),
             }
             .map(Box::as_ref),
@@ -244,35 +506,76 @@ impl<T: GameData, L: EventListener<T>> Engine<T, L> {
     fn context(&self) -> T::Context {
         self.decision().context(&self.data)
     }
+
+    /// Number of selected options of a pending decision chain (zero if there is none).
+    fn pending_chain_len(&self) -> usize {
+        match &self.state {
+            InternalState::PDecision(_, stack) => stack.len(),
+            InternalState::PEffect(_, chain_len) => *chain_len,
+            _ => 0,
+        }
+    }
+
+    /// Retracts the options of the pending decision chain, including selected
+    /// options whose effects were not applied yet.
+    fn retract_pending_chain(&mut self) {
+        let chain_len = self.pending_chain_len();
+        if chain_len == 0 {
+            return;
+        }
+        if let InternalState::PEffect(..) = self.state {
+            self.listener.retracted_by_n(chain_len);
+            self.state = self.fetch_next_state();
+            self.notify_pending();
+        } else {
+            self.retract_all();
+        }
+    }
 }
 
-impl<T: GameData, L: EventListener<T>> PEffectState for Engine<T, L> {
+impl<T, L, D> PEffectState for Engine<T, L, D>
+where
+    T: GameData,
+    L: EventListener<T>,
+    D: DecisionObject<T> + ?Sized,
+{
     fn next_effect(&mut self) -> Option<&mut dyn PEffectState> {
         let effect = self.take_effect();
         let next = effect.apply(&mut self.data);
         self.listener.effect_applied(effect);
 
         if let Some(effect) = next {
-            self.state = InternalState::PEffect(effect);
+            self.state = InternalState::PEffect(effect, 0);
             Some(self)
         } else {
-            self.state = self.fetch_next_state();
+            self.complete_effects();
             None
         }
     }
 }
 
-impl<T: GameData, L: EventListener<T>> PDecisionState for Engine<T, L> {
+impl<T, L, D> PDecisionState for Engine<T, L, D>
+where
+    T: GameData,
+    L: EventListener<T>,
+    D: DecisionObject<T> + ?Sized,
+{
     fn select_option(&mut self, index: usize) {
+        assert!(!self.meta.is_paused(), "The game is paused!");
         let player = self.decision().player();
         self.select_and_apply_option(index);
         self.listener.option_selected(index, player);
+        self.notify_pending();
     }
 
     fn option_count(&self) -> usize {
         self.decision().option_count()
     }
 
+    fn is_paused(&self) -> bool {
+        self.meta.is_paused()
+    }
+
     fn player(&self) -> usize {
         self.decision().player()
     }
@@ -286,6 +589,7 @@ impl<T: GameData, L: EventListener<T>> PDecisionState for Engine<T, L> {
         if n <= len {
             self.decision_stack_mut().truncate(len - n);
             self.listener.retracted_by_n(n);
+            self.notify_pending();
             true
         } else {
             false
@@ -295,20 +599,40 @@ impl<T: GameData, L: EventListener<T>> PDecisionState for Engine<T, L> {
     fn retract_all(&mut self) {
         self.listener.retracted_by_n(self.decision_stack().len());
         self.decision_stack_mut().clear();
+        self.notify_pending();
     }
 }
 
-impl<T: GameData> Engine<T, EventLog<T>> {
+impl<T: GameData, L: DecisionLog<T>, D: DecisionObject<T> + ?Sized> Engine<T, L, D> {
+    /// Undoes the last decision whose effects were applied, together with the meta actions
+    /// after it. If there is no decision to undo, the logged meta actions are undone instead
+    /// (e.g. a resignation before the first decision).
+    ///
+    /// A pending decision chain is retracted first, even if there is nothing to undo.
+    /// If the effects of a decision were only partly applied, the remaining effects are
+    /// applied first, so that the decision is undone as a whole.
+    /// Returns whether a decision or meta action was undone.
     pub fn undo_last_decision(&mut self) -> bool
     where
-        T::EffectType: RevEffect<T>,
+        L: UndoLog<T>,
     {
+        if let InternalState::PEffect(_, 0) = self.state {
+            while self.next_effect().is_some() {}
+        }
+        self.retract_pending_chain();
         if self.listener.undo_last_decision(&mut self.data) {
-            self.state = self.fetch_next_state();
-            true
+            self.decision_count -= 1;
+            self.meta
+                .decision_undone(self.num_players, self.decision_count);
+            self.listener.decision_undone(&self.data);
+        } else if self.listener.undo_meta_actions() {
+            self.meta
+                .actions_undone(self.num_players, self.decision_count);
         } else {
-            false
+            return false;
         }
+        self.enter_next_state();
+        true
     }
 
     pub fn redo_decision(&mut self) -> bool {
@@ -319,11 +643,11 @@ impl<T: GameData> Engine<T, EventLog<T>> {
         }
     }
 
-    pub fn log(&self) -> &EventLog<T> {
+    pub fn log(&self) -> &L {
         &self.listener
     }
 
-    pub fn log_mut(&mut self) -> &mut EventLog<T> {
+    pub fn log_mut(&mut self) -> &mut L {
         &mut self.listener
     }
 
@@ -331,3 +655,538 @@ impl<T: GameData> Engine<T, EventLog<T>> {
         self.listener.serialized()
     }
 }
+
+impl<T, L, D> Engine<T, L, D>
+where
+    T: GameData,
+    L: DecisionLog<T> + AsEventLog<T>,
+    D: DecisionObject<T> + ?Sized,
+{
+    /// The event log, which might be part of a composite listener.
+    pub fn event_log(&self) -> &EventLog<T> {
+        self.listener.event_log()
+    }
+
+    pub fn event_log_mut(&mut self) -> &mut EventLog<T> {
+        self.listener.event_log_mut()
+    }
+
+    /// Undoes or redoes decisions until the bookmarked position is reached.
+    /// Returns false (without any effect) if the bookmark is invalid.
+    pub fn reset_to(&mut self, bookmark: Bookmark) -> bool
+    where
+        L: UndoLog<T>,
+    {
+        if !self.event_log().is_valid(bookmark) {
+            return false;
+        }
+        if matches!(&self.state, InternalState::PDecision(_, stack) if !stack.is_empty()) {
+            self.retract_all();
+        }
+        while self.event_log().position() > bookmark.position() {
+            assert!(self.undo_last_decision(), "{}", INTERNAL_ERROR);
+        }
+        while self.event_log().position() < bookmark.position() {
+            assert!(self.redo_decision(), "{}", INTERNAL_ERROR);
+        }
+        true
+    }
+
+    /// Available variations at the current position, see `EventLog::variations`.
+    pub fn variations(&self) -> &[Variation] {
+        self.event_log().variations()
+    }
+
+    /// Redoes the first decision of the variation with the given index.
+    pub fn redo_variation(&mut self, index: usize) -> bool {
+        match &self.state {
+            InternalState::PDecision(_, stack) if stack.is_empty() => {}
+            _ => return false,
+        }
+        self.event_log_mut().start_variation(index) && self.redo_decision()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        new_rev_effect,
+        plain_decision::PlainDecision,
+        test::{play, CountingGame, OrderedGame, RevCountingGame, Value},
+        vec_decision::VecDecision,
+        Decision, GameData, GameResult, OptionId, RevEffect, SendDecision,
+    };
+
+    use super::{
+        abstract_engine::{AbstractEngine, AbstractState},
+        logging::{EventLog, UndoLog},
+        Engine, EngineError, EventListener, FollowUpDecision, GameState, LoggingEngine,
+        NotListening, SendEngine,
+    };
+
+    /// Records all lifecycle events (except for effects) as strings.
+    #[derive(Default)]
+    struct TraceListener {
+        events: Vec<String>,
+    }
+
+    impl<T: GameData> EventListener<T> for TraceListener {
+        fn effect_applied(&mut self, _effect: Box<T::EffectType>) {}
+
+        fn effect_observed(&mut self, _effect: &T::EffectType) {}
+
+        fn option_selected(&mut self, index: usize, player: usize) {
+            self.events.push(format!("selected {index} by {player}"));
+        }
+
+        fn retracted_by_n(&mut self, n: usize) {
+            self.events.push(format!("retracted {n}"));
+        }
+
+        fn effects_completed(&mut self, _data: &T) {
+            self.events.push("completed".to_string());
+        }
+
+        fn decision_pending(
+            &mut self,
+            _data: &T,
+            player: usize,
+            option_count: usize,
+            level_in_chain: usize,
+        ) {
+            self.events.push(format!(
+                "pending for {player} with {option_count} at {level_in_chain}"
+            ));
+        }
+
+        fn decision_undone(&mut self, _data: &T) {
+            self.events.push("undone".to_string());
+        }
+
+        fn decision_redone(&mut self, _data: &T) {
+            self.events.push("redone".to_string());
+        }
+
+        fn game_finished(&mut self, _data: &T, _result: Option<&GameResult>) {
+            self.events.push("finished".to_string());
+        }
+    }
+
+    #[derive(Default)]
+    struct ResultListener {
+        results: Vec<Option<GameResult>>,
+    }
+
+    impl<T: GameData> EventListener<T> for ResultListener {
+        fn effect_applied(&mut self, _effect: Box<T::EffectType>) {}
+
+        fn effect_observed(&mut self, _effect: &T::EffectType) {}
+
+        fn option_selected(&mut self, _index: usize, _player: usize) {}
+
+        fn retracted_by_n(&mut self, _n: usize) {}
+
+        fn game_finished(&mut self, _data: &T, result: Option<&GameResult>) {
+            self.results.push(result.cloned());
+        }
+    }
+
+    #[test]
+    fn option_id_test() {
+        for descending in [false, true] {
+            let data = OrderedGame {
+                descending,
+                taken: Vec::new(),
+            };
+            let mut engine = Engine::new(2, data);
+            for id in [OptionId(3), OptionId(1)] {
+                match engine.pull() {
+                    GameState::PendingDecision(dec) => {
+                        let index = dec.option_index(id).unwrap();
+                        assert_eq!(dec.option_id(index), Some(id));
+                        dec.apply_option(index);
+                    }
+                    _ => panic!("expected pending decision"),
+                }
+            }
+            assert_eq!(engine.data().taken, [3, 1]);
+        }
+
+        // the options of the decision are not identified
+        let mut engine = Engine::new(2, CountingGame::new(10));
+        match engine.pull() {
+            GameState::PendingDecision(dec) => {
+                assert_eq!(dec.option_id(0), None);
+                assert_eq!(dec.option_index(OptionId(0)), None);
+            }
+            _ => panic!("expected pending decision"),
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "not unique")]
+    fn duplicate_option_id_test() {
+        let data = OrderedGame {
+            descending: false,
+            taken: Vec::new(),
+        };
+        let mut dec: VecDecision<OrderedGame, Value> = VecDecision::new(0);
+        for value in [1, 2, 1] {
+            dec.add_effect(|_: &mut OrderedGame| None, Value(value));
+        }
+        dec.identify_options();
+        dec.option_index(&data, OptionId(1));
+    }
+
+    #[test]
+    fn lifecycle_test() {
+        let listener = (EventLog::new(), TraceListener::default());
+        let mut engine = Engine::with_listener(2, RevCountingGame::new(3), listener);
+        play(&mut engine, &[1]);
+        match engine.pull() {
+            GameState::PendingDecision(dec) => dec.into_follow_up_decision().unwrap().retract(),
+            _ => panic!("expected pending decision"),
+        }
+        play(&mut engine, &[0]);
+        assert!(engine.undo_last_decision());
+        assert!(engine.redo_decision());
+        play(&mut engine, &[1, 0]);
+        let events = &engine.listener.1.events;
+        assert_eq!(
+            events,
+            &[
+                "pending for 0 with 2 at 0",
+                "selected 1 by 0",
+                "pending for 0 with 2 at 1",
+                "retracted 1",
+                "pending for 0 with 2 at 0",
+                "selected 0 by 0",
+                "completed",
+                "pending for 1 with 2 at 0",
+                "undone",
+                "pending for 0 with 2 at 0",
+                "selected 0 by 0",
+                "completed",
+                "redone",
+                "pending for 1 with 2 at 0",
+                "selected 1 by 1",
+                "pending for 1 with 2 at 1",
+                "selected 0 by 1",
+                "completed",
+                "finished",
+            ]
+        );
+    }
+
+    #[test]
+    fn try_api_test() {
+        let mut engine = Engine::new(2, CountingGame::new(10));
+        let invalid = EngineError::InvalidOption {
+            index: 2,
+            option_count: 2,
+        };
+        match engine.pull() {
+            GameState::PendingDecision(dec) => assert_eq!(dec.try_apply_option(2), Err(invalid)),
+            _ => panic!("expected pending decision"),
+        }
+        match engine.pull() {
+            GameState::PendingDecision(dec) => assert_eq!(dec.try_select_option(1), Ok(())),
+            _ => panic!("expected pending decision"),
+        }
+        fn follow_up(
+            engine: &mut Engine<CountingGame>,
+        ) -> FollowUpDecision<'_, CountingGame, NotListening> {
+            match engine.pull() {
+                GameState::PendingDecision(dec) => dec.into_follow_up_decision().unwrap(),
+                _ => panic!("expected pending decision"),
+            }
+        }
+        assert_eq!(follow_up(&mut engine).try_select_option(2), Err(invalid));
+        assert_eq!(
+            follow_up(&mut engine).try_retract_n(2),
+            Err(EngineError::InvalidRetraction {
+                n: 2,
+                level_in_chain: 1
+            })
+        );
+        assert_eq!(follow_up(&mut engine).try_retract(), Ok(()));
+        match engine.pull_abstract() {
+            AbstractState::PendingDecision(dec) => assert!(dec.try_select_option(5).is_err()),
+            _ => panic!("expected pending decision"),
+        }
+        match engine.pull() {
+            GameState::PendingDecision(dec) => assert_eq!(dec.try_apply_option(0), Ok(true)),
+            _ => panic!("expected pending decision"),
+        }
+        assert_eq!(engine.data().history, vec![1]);
+    }
+
+    #[test]
+    fn game_result_test() {
+        let mut engine = Engine::with_listener(2, CountingGame::new(5), ResultListener::default());
+        play(&mut engine, &[0, 1, 1]);
+        assert!(engine.listener.results.is_empty());
+        match engine.pull() {
+            GameState::PendingDecision(dec) => dec.select_option(0),
+            _ => panic!("expected pending decision"),
+        }
+        match engine.pull() {
+            GameState::PendingEffect(eff) => eff.next_effect(),
+            _ => panic!("expected pending effect"),
+        }
+        let expected = GameResult::win(0).with_scores(vec![5, 5]);
+        match engine.pull() {
+            GameState::Finished(finished) => assert_eq!(finished.result(), Some(expected.clone())),
+            _ => panic!("expected finished game"),
+        }
+        assert_eq!(engine.listener.results, vec![Some(expected.clone())]);
+
+        let mut engine = Engine::new(2, CountingGame::new(1));
+        play(&mut engine, &[0]);
+        match engine.pull_abstract() {
+            AbstractState::Finished(finished) => {
+                assert_eq!(
+                    finished.result(),
+                    Some(&GameResult::win(0).with_scores(vec![1, 1]))
+                );
+                assert!(finished.result().unwrap().is_winner(0));
+            }
+            _ => panic!("expected finished game"),
+        }
+    }
+
+    fn undo_in_chain<L: UndoLog<RevCountingGame>>(engine: &mut Engine<RevCountingGame, L>) {
+        // follow-up decision without previous decision
+        play(engine, &[1]);
+        match engine.pull() {
+            GameState::PendingDecision(dec) => {
+                let mut follow_up = dec.into_follow_up_decision().unwrap();
+                assert!(!follow_up.undo_last_decision());
+            }
+            _ => panic!("expected pending decision"),
+        }
+        assert!(engine.data().0.history.is_empty());
+        assert_eq!(engine.pending_chain_len(), 0);
+        assert!(engine.log().serialized().log.is_empty());
+
+        // follow-up decision
+        play(engine, &[0, 1, 0]);
+        let log = engine.log().serialized();
+        play(engine, &[1]);
+        match engine.pull() {
+            GameState::PendingDecision(dec) => {
+                let mut follow_up = dec.into_follow_up_decision().unwrap();
+                assert!(follow_up.undo_last_decision());
+            }
+            _ => panic!("expected pending decision"),
+        }
+        assert_eq!(engine.data().0.history, [1]);
+        assert_eq!(engine.pending_chain_len(), 0);
+        assert!(engine.redo_decision());
+        assert_eq!(engine.data().0.history, [1, 2]);
+        assert_eq!(engine.log().serialized(), log);
+
+        // pending effect
+        match engine.pull() {
+            GameState::PendingDecision(dec) => dec.select_option(0),
+            _ => panic!("expected pending decision"),
+        }
+        match engine.pull() {
+            GameState::PendingEffect(mut eff) => assert!(eff.undo_last_decision()),
+            _ => panic!("expected pending effect"),
+        }
+        assert_eq!(engine.data().0.history, [1]);
+        assert_eq!(engine.pending_chain_len(), 0);
+        assert!(engine.redo_decision());
+        assert!(!engine.redo_decision());
+        assert_eq!(engine.log().serialized(), log);
+
+        // finished game
+        play(engine, &[0]);
+        let log = engine.log().serialized();
+        match engine.pull() {
+            GameState::Finished(mut fin) => assert!(fin.undo_last_decision()),
+            _ => panic!("expected finished game"),
+        }
+        assert_eq!(engine.data().0.history, [1, 2]);
+        assert!(engine.redo_decision());
+        assert!(matches!(engine.pull(), GameState::Finished(_)));
+        assert_eq!(engine.log().serialized(), log);
+    }
+
+    #[test]
+    fn undo_in_chain_test() {
+        let listener = (EventLog::new(), TraceListener::default());
+        let mut engine = Engine::with_listener(2, RevCountingGame::new(4), listener);
+        undo_in_chain(&mut engine);
+        let events = &engine.listener.1.events;
+        assert_eq!(events.iter().filter(|e| *e == "retracted 1").count(), 3);
+        assert_eq!(events.iter().filter(|e| *e == "undone").count(), 3);
+
+        let mut engine = Engine::new_snapshot_logging(2, RevCountingGame::new(4));
+        undo_in_chain(&mut engine);
+    }
+
+    /// Each decision pushes 1 and 2 to the values, using two chained effects.
+    #[derive(Debug, Clone, Default)]
+    struct ChainedGame(Vec<u32>);
+
+    impl GameData for ChainedGame {
+        type Context = ();
+        type EffectType = dyn RevEffect<Self>;
+
+        fn next_decision(&self) -> Option<Box<dyn Decision<Self>>> {
+            if self.0.len() >= 4 {
+                return None;
+            }
+            let mut dec = PlainDecision::new(self.0.len() / 2 % 2);
+            dec.add_rev_effect(
+                |data: &mut ChainedGame| {
+                    data.0.push(1);
+                    Some(new_rev_effect(
+                        |data: &mut ChainedGame| {
+                            data.0.push(2);
+                            None
+                        },
+                        |data: &mut ChainedGame| {
+                            data.0.pop();
+                        },
+                    ))
+                },
+                |data: &mut ChainedGame| {
+                    data.0.pop();
+                },
+            );
+            Some(Box::new(dec))
+        }
+    }
+
+    /// Selects the option of the pending decision and applies only the first effect.
+    fn apply_first_effect<L: UndoLog<ChainedGame>>(engine: &mut Engine<ChainedGame, L>) {
+        match engine.pull() {
+            GameState::PendingDecision(dec) => dec.select_option(0),
+            _ => panic!("expected pending decision"),
+        }
+        match engine.pull() {
+            GameState::PendingEffect(eff) => eff.next_effect(),
+            _ => panic!("expected pending effect"),
+        }
+    }
+
+    fn undo_partly_applied<L: UndoLog<ChainedGame>>(engine: &mut Engine<ChainedGame, L>) {
+        // the remaining effect is applied before the decision is undone
+        apply_first_effect(engine);
+        assert_eq!(engine.data().0, [1]);
+        assert!(engine.undo_last_decision());
+        assert!(engine.data().0.is_empty());
+        assert!(!engine.undo_last_decision());
+
+        play(engine, &[0]);
+        apply_first_effect(engine);
+        assert_eq!(engine.data().0, [1, 2, 1]);
+        assert!(engine.undo_last_decision());
+        assert_eq!(engine.data().0, [1, 2]);
+        assert!(engine.redo_decision());
+        assert_eq!(engine.data().0, [1, 2, 1, 2]);
+        assert!(matches!(engine.pull(), GameState::Finished(_)));
+    }
+
+    #[test]
+    fn undo_partly_applied_test() {
+        let listener = (EventLog::new(), TraceListener::default());
+        let mut engine = Engine::with_listener(2, ChainedGame::default(), listener);
+        undo_partly_applied(&mut engine);
+        let events = &engine.listener.1.events;
+        assert_eq!(events.iter().filter(|e| *e == "undone").count(), 2);
+        assert_eq!(engine.decision_count, 2);
+
+        let mut engine = Engine::new_snapshot_logging(2, ChainedGame::default());
+        undo_partly_applied(&mut engine);
+        assert_eq!(engine.decision_count, 2);
+    }
+
+    #[test]
+    fn clone_follow_up_test() {
+        let mut engine = Engine::new_logging(2, RevCountingGame::new(100));
+        play(&mut engine, &[0, 1]);
+        assert_eq!(engine.pending_chain(), [1]);
+
+        let mut cloned = engine.try_clone_with_listener(EventLog::new()).unwrap();
+        assert_eq!(cloned.pending_chain(), [1]);
+        assert_eq!(cloned.serialized_log().log, [(1, 1)]);
+        play(&mut cloned, &[1]);
+        assert_eq!(cloned.data().0.history, [1, 3]);
+        assert!(cloned.undo_last_decision());
+        assert_eq!(cloned.data().0.history, [1]);
+
+        // the original engine is not affected
+        assert_eq!(engine.pending_chain(), [1]);
+
+        // the new listener is notified about the restored follow-up decision
+        let traced = engine
+            .try_clone_with_listener(TraceListener::default())
+            .unwrap();
+        assert_eq!(
+            traced.listener.events,
+            ["selected 1 by 1", "pending for 1 with 2 at 1"]
+        );
+
+        play(&mut engine, &[0]);
+        assert_eq!(engine.data().0.history, [1, 2]);
+
+        // a cloned listener already knows about the pending options
+        let mut engine = Engine::new_snapshot_logging(2, RevCountingGame::new(100));
+        play(&mut engine, &[0, 1]);
+        let mut cloned = engine.try_clone().unwrap();
+        assert_eq!(cloned.serialized_log(), engine.serialized_log());
+        match cloned.pull() {
+            GameState::PendingDecision(dec) => dec.into_follow_up_decision().unwrap().retract(),
+            _ => panic!("expected pending decision"),
+        }
+        assert!(cloned.pending_chain().is_empty());
+        assert!(cloned.undo_last_decision());
+        assert!(cloned.data().0.history.is_empty());
+    }
+
+    #[test]
+    fn send_engine_test() {
+        let mut engine =
+            SendEngine::send_with_listener(2, RevCountingGame::new(10), EventLog::new());
+        play(&mut engine, &[0, 1]);
+        // the engine is moved to another thread within a follow-up decision
+        let engine = std::thread::spawn(move || {
+            play(&mut engine, &[1, 0]);
+            engine
+        })
+        .join()
+        .unwrap();
+        assert_eq!(engine.data().0.history, [1, 3, 1]);
+    }
+
+    #[test]
+    fn convert_test() {
+        let mut engine = Engine::new_logging(2, RevCountingGame::new(10));
+        play(&mut engine, &[0, 1]);
+        let mut engine: SendEngine<_, _> = engine.try_convert().unwrap();
+        assert_eq!(engine.pending_chain(), [1]);
+        engine = std::thread::spawn(move || {
+            play(&mut engine, &[1, 0]);
+            engine
+        })
+        .join()
+        .unwrap();
+        assert_eq!(engine.data().0.history, [1, 3, 1]);
+        assert_eq!(engine.decision_count(), 3);
+
+        let mut engine: LoggingEngine<_> = engine.try_convert().unwrap();
+        engine.undo_last_decision();
+        assert_eq!(engine.data().0.history, [1, 3]);
+
+        match engine.pull() {
+            GameState::PendingDecision(dec) => dec.select_option(0),
+            _ => panic!("expected pending decision"),
+        }
+        assert!(engine.try_convert::<dyn SendDecision<_>>().is_err());
+    }
+}
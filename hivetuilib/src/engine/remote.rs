@@ -0,0 +1,742 @@
+//! Line-based protocol for players running in a separate process, e.g. connected
+//! via stdin/stdout pipes or a local TCP socket.
+//!
+//! For each pending decision of the remote player, the server sends
+//! ```text
+//! position
+//! <header, initial state, number of players and log in the save format>
+//! decision <option count> <player> <level in chain>
+//! ```
+//! and the client answers with `select <index>` or `retract <n>`. If the decision
+//! identifies its options (see `Decision::option_id`), the client answers with
+//! `select-id <id>` instead, which doesn't depend on the order of the options.
+//! At the end of the game, the server sends `finished`.
+//!
+//! For games with hidden information (see `ObservableGameData`), the server sends the
+//! view of the player instead of the position. The encoded view must not contain
+//! lines starting with `decision`.
+//! ```text
+//! view
+//! <encoded view>
+//! decision <option count> <player> <level in chain>
+//! ```
+
+use std::{
+    fmt::{self, Display},
+    io::{self, BufRead, Write},
+};
+
+use crate::{DecisionObject, GameData, GameResult, ObservableGameData, OptionId};
+
+use super::{
+    io::{
+        parse_saved_game, restore_with_log, save_game, CompatibilityPolicy, LoadGameError,
+        SerializedLog,
+    },
+    logging::{DecisionLog, EventLog},
+    runner::{DecisionView, Player, PlayerAction, ViewPlayer},
+    GameState, LoggingEngine, PendingDecision,
+};
+
+const POSITION: &str = "position";
+const VIEW: &str = "view";
+const DECISION: &str = "decision";
+const FINISHED: &str = "finished";
+const SELECT: &str = "select";
+const SELECT_ID: &str = "select-id";
+const RETRACT: &str = "retract";
+
+#[derive(Debug)]
+pub enum RemoteError {
+    IO(io::Error),
+    /// The message does not conform to the protocol.
+    InvalidMessage {
+        msg: String,
+    },
+    /// The position can not be restored.
+    InvalidPosition(LoadGameError),
+    /// The client failed to choose an action.
+    DecisionFailed {
+        msg: String,
+    },
+}
+
+impl RemoteError {
+    fn invalid_message<S: ToString>(msg: S) -> Self {
+        Self::InvalidMessage {
+            msg: msg.to_string(),
+        }
+    }
+}
+
+impl From<io::Error> for RemoteError {
+    fn from(value: io::Error) -> Self {
+        RemoteError::IO(value)
+    }
+}
+
+impl From<LoadGameError> for RemoteError {
+    fn from(value: LoadGameError) -> Self {
+        RemoteError::InvalidPosition(value)
+    }
+}
+
+impl Display for RemoteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RemoteError::IO(e) => write!(f, "{e}"),
+            RemoteError::InvalidMessage { msg } => write!(f, "invalid message: {msg}"),
+            RemoteError::InvalidPosition(e) => write!(f, "invalid position: {e}"),
+            RemoteError::DecisionFailed { msg } => write!(f, "decision failed: {msg}"),
+        }
+    }
+}
+
+/// A pending decision together with the position of the game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteRequest {
+    pub initial_state: Vec<(String, String)>,
+    pub num_players: usize,
+    /// Decisions leading to the position, including the options selected in the pending chain.
+    pub log: SerializedLog,
+    pub option_count: usize,
+    pub player: usize,
+    pub level_in_chain: usize,
+}
+
+pub fn write_request<W: Write, H: AsRef<str>>(
+    mut writer: W,
+    header: H,
+    version: [u32; 2],
+    request: &RemoteRequest,
+) -> Result<(), io::Error> {
+    writeln!(writer, "{POSITION}")?;
+    let log = SerializedLog {
+        log: request.log.log.clone(),
+        redo_stack: Vec::new(),
+        variations: Vec::new(),
+        hashes: request.log.hashes.clone(),
+        meta: request.log.meta.clone(),
+    };
+    save_game(
+        &mut writer,
+        header,
+        version,
+        request.initial_state.iter().cloned(),
+        request.num_players,
+        log,
+    )?;
+    writeln!(
+        writer,
+        "{DECISION} {} {} {}",
+        request.option_count, request.player, request.level_in_chain
+    )?;
+    writer.flush()
+}
+
+/// Reads the next request. Returns `None` if the game is finished or the stream is closed.
+pub fn read_request<R: BufRead, H: AsRef<str>>(
+    mut reader: R,
+    expected_header: H,
+    version: [u32; 2],
+    compatibility_policy: CompatibilityPolicy,
+) -> Result<Option<RemoteRequest>, RemoteError> {
+    let mut line = String::new();
+    if !read_message(&mut reader, &mut line)? || line == FINISHED {
+        return Ok(None);
+    }
+    if line != POSITION {
+        return Err(RemoteError::invalid_message(format!(
+            "{line}, expected: {POSITION}"
+        )));
+    }
+    let (position, numbers) = read_until_decision(&mut reader, &mut line, POSITION)?;
+    let (initial_state, num_players, log) = parse_saved_game(
+        position.as_bytes(),
+        expected_header,
+        version,
+        compatibility_policy,
+    )?;
+    Ok(Some(RemoteRequest {
+        initial_state,
+        num_players,
+        log,
+        option_count: numbers[0],
+        player: numbers[1],
+        level_in_chain: numbers[2],
+    }))
+}
+
+/// A pending decision together with the encoded view of the deciding player.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ViewRequest {
+    pub view: String,
+    pub option_count: usize,
+    pub player: usize,
+    pub level_in_chain: usize,
+}
+
+pub fn write_view_request<W: Write>(mut writer: W, request: &ViewRequest) -> Result<(), io::Error> {
+    writeln!(writer, "{VIEW}")?;
+    for line in request.view.lines() {
+        writeln!(writer, "{line}")?;
+    }
+    writeln!(
+        writer,
+        "{DECISION} {} {} {}",
+        request.option_count, request.player, request.level_in_chain
+    )?;
+    writer.flush()
+}
+
+/// Reads the next view request. Returns `None` if the game is finished or the stream is closed.
+pub fn read_view_request<R: BufRead>(mut reader: R) -> Result<Option<ViewRequest>, RemoteError> {
+    let mut line = String::new();
+    if !read_message(&mut reader, &mut line)? || line == FINISHED {
+        return Ok(None);
+    }
+    if line != VIEW {
+        return Err(RemoteError::invalid_message(format!(
+            "{line}, expected: {VIEW}"
+        )));
+    }
+    let (view, numbers) = read_until_decision(&mut reader, &mut line, VIEW)?;
+    Ok(Some(ViewRequest {
+        view,
+        option_count: numbers[0],
+        player: numbers[1],
+        level_in_chain: numbers[2],
+    }))
+}
+
+/// Answer of the client to a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoteAction {
+    Select(usize),
+    /// Selects the option with the given id, see `Decision::option_id`.
+    SelectId(OptionId),
+    Retract(usize),
+}
+
+impl From<PlayerAction> for RemoteAction {
+    fn from(value: PlayerAction) -> Self {
+        match value {
+            PlayerAction::Select(index) => RemoteAction::Select(index),
+            PlayerAction::Retract(n) => RemoteAction::Retract(n),
+        }
+    }
+}
+
+pub fn write_action<W: Write>(mut writer: W, action: RemoteAction) -> Result<(), io::Error> {
+    match action {
+        RemoteAction::Select(index) => writeln!(writer, "{SELECT} {index}")?,
+        RemoteAction::SelectId(OptionId(id)) => writeln!(writer, "{SELECT_ID} {id}")?,
+        RemoteAction::Retract(n) => writeln!(writer, "{RETRACT} {n}")?,
+    }
+    writer.flush()
+}
+
+pub fn read_action<R: BufRead>(mut reader: R) -> Result<RemoteAction, RemoteError> {
+    let mut line = String::new();
+    if !read_message(&mut reader, &mut line)? {
+        return Err(RemoteError::invalid_message("connection closed"));
+    }
+    let invalid = || {
+        RemoteError::invalid_message(format!(
+            "{line}, expected: {SELECT} <index>, {SELECT_ID} <id> or {RETRACT} <n>"
+        ))
+    };
+    let (command, number) = line.split_once(' ').ok_or_else(invalid)?;
+    match command {
+        SELECT => Ok(RemoteAction::Select(number.parse().map_err(|_| invalid())?)),
+        SELECT_ID => Ok(RemoteAction::SelectId(OptionId(
+            number.parse().map_err(|_| invalid())?,
+        ))),
+        RETRACT => Ok(RemoteAction::Retract(
+            number.parse().map_err(|_| invalid())?,
+        )),
+        _ => Err(invalid()),
+    }
+}
+
+/// Reads the lines of a position or view block and the terminating decision line.
+fn read_until_decision<R: BufRead>(
+    reader: &mut R,
+    line: &mut String,
+    block: &str,
+) -> Result<(String, [usize; 3]), RemoteError> {
+    let mut content = String::new();
+    let decision = loop {
+        if !read_message(reader, line)? {
+            return Err(RemoteError::invalid_message(format!(
+                "unterminated {block}"
+            )));
+        }
+        if let Some(decision) = line
+            .strip_prefix(DECISION)
+            .and_then(|d| d.strip_prefix(' '))
+        {
+            break decision.to_string();
+        }
+        content.push_str(line);
+        content.push('\n');
+    };
+    let numbers = decision
+        .split(' ')
+        .map(str::parse)
+        .collect::<Result<Vec<usize>, _>>()
+        .ok()
+        .and_then(|numbers| numbers.try_into().ok())
+        .ok_or_else(|| {
+            RemoteError::invalid_message(format!(
+                "{DECISION} {decision}, expected: {DECISION} <option count> <player> <level>"
+            ))
+        })?;
+    Ok((content, numbers))
+}
+
+/// Reads a line without the line break. Returns `false` at the end of the stream.
+fn read_message<R: BufRead>(reader: &mut R, buf: &mut String) -> Result<bool, io::Error> {
+    buf.clear();
+    if reader.read_line(buf)? == 0 {
+        return Ok(false);
+    }
+    let len = buf.trim_end_matches(['\n', '\r']).len();
+    buf.truncate(len);
+    Ok(true)
+}
+
+/// Server side of a remote player: sends the position for each decision of the player
+/// and reads the answer.
+pub struct RemotePlayer<R: BufRead, W: Write> {
+    reader: R,
+    writer: W,
+    header: String,
+    version: [u32; 2],
+    initial_state: Vec<(String, String)>,
+}
+
+impl<R: BufRead, W: Write> RemotePlayer<R, W> {
+    pub fn new<H: Into<String>>(
+        reader: R,
+        writer: W,
+        header: H,
+        version: [u32; 2],
+        initial_state: Vec<(String, String)>,
+    ) -> Self {
+        Self {
+            reader,
+            writer,
+            header: header.into(),
+            version,
+            initial_state,
+        }
+    }
+}
+
+impl<T, L, D, R, W> Player<T, L, D> for RemotePlayer<R, W>
+where
+    T: GameData,
+    L: DecisionLog<T>,
+    D: DecisionObject<T> + ?Sized,
+    R: BufRead,
+    W: Write,
+{
+    fn decide(&mut self, decision: &PendingDecision<T, L, D>) -> Result<PlayerAction, String> {
+        let engine = decision.engine();
+        let request = RemoteRequest {
+            initial_state: self.initial_state.clone(),
+            num_players: engine.num_players,
+            log: engine.serialized_log(),
+            option_count: decision.option_count(),
+            player: decision.player(),
+            level_in_chain: decision.level_in_chain(),
+        };
+        write_request(&mut self.writer, &self.header, self.version, &request)
+            .map_err(|e| e.to_string())?;
+        match read_action(&mut self.reader).map_err(|e| e.to_string())? {
+            RemoteAction::Select(index) => Ok(PlayerAction::Select(index)),
+            RemoteAction::SelectId(id) => decision
+                .option_index(id)
+                .map(PlayerAction::Select)
+                .ok_or_else(|| format!("unknown option id {}", id.0)),
+            RemoteAction::Retract(n) => Ok(PlayerAction::Retract(n)),
+        }
+    }
+
+    fn game_finished(&mut self, _data: &T, _result: Option<&GameResult>) {
+        // the client might already be gone, which is not an error at this point
+        let _ = writeln!(self.writer, "{FINISHED}").and_then(|_| self.writer.flush());
+    }
+}
+
+/// Server side of a remote player for games with hidden information: sends the encoded
+/// view for each decision of the player and reads the answer. Use with `ObservingPlayer`.
+pub struct RemoteViewPlayer<R: BufRead, W: Write, F> {
+    reader: R,
+    writer: W,
+    encode_view: F,
+}
+
+impl<R: BufRead, W: Write, F> RemoteViewPlayer<R, W, F> {
+    pub fn new(reader: R, writer: W, encode_view: F) -> Self {
+        Self {
+            reader,
+            writer,
+            encode_view,
+        }
+    }
+}
+
+impl<T, R, W, F> ViewPlayer<T> for RemoteViewPlayer<R, W, F>
+where
+    T: ObservableGameData,
+    R: BufRead,
+    W: Write,
+    F: Fn(&T::View) -> String,
+{
+    fn decide(&mut self, decision: &DecisionView<T>) -> Result<PlayerAction, String> {
+        let request = ViewRequest {
+            view: (self.encode_view)(&decision.view),
+            option_count: decision.option_count,
+            player: decision.player,
+            level_in_chain: decision.level_in_chain,
+        };
+        write_view_request(&mut self.writer, &request).map_err(|e| e.to_string())?;
+        match read_action(&mut self.reader).map_err(|e| e.to_string())? {
+            RemoteAction::Select(index) => Ok(PlayerAction::Select(index)),
+            RemoteAction::SelectId(_) => Err("option ids are not supported for views".to_string()),
+            RemoteAction::Retract(n) => Ok(PlayerAction::Retract(n)),
+        }
+    }
+
+    fn game_finished(&mut self, _view: &T::View, _result: Option<&GameResult>) {
+        // the client might already be gone, which is not an error at this point
+        let _ = writeln!(self.writer, "{FINISHED}").and_then(|_| self.writer.flush());
+    }
+}
+
+/// Client side of the protocol: restores the position of each request and answers with
+/// the action chosen by `decide`. Returns when the game is finished or the stream is closed.
+/// An error of `decide` is returned as `RemoteError::DecisionFailed`.
+///
+/// A selected option is sent by its id if the decision identifies its options.
+pub fn run_client<T, R, W, H, F, D>(
+    mut reader: R,
+    mut writer: W,
+    expected_header: H,
+    version: [u32; 2],
+    compatibility_policy: CompatibilityPolicy,
+    parse_initial_state: F,
+    mut decide: D,
+) -> Result<(), RemoteError>
+where
+    T: GameData,
+    R: BufRead,
+    W: Write,
+    H: AsRef<str>,
+    F: Fn(&[(String, String)]) -> Result<T, String>,
+    D: FnMut(&mut LoggingEngine<T>) -> Result<PlayerAction, String>,
+{
+    while let Some(request) = read_request(
+        &mut reader,
+        expected_header.as_ref(),
+        version,
+        compatibility_policy,
+    )? {
+        // fingerprints are ignored, they are recorded by the log of the server
+        let mut engine = restore_with_log(
+            request.num_players,
+            || parse_initial_state(&request.initial_state),
+            request.log,
+            EventLog::new(),
+            None,
+        )?;
+        let matches = match engine.pull() {
+            GameState::PendingDecision(dec) => {
+                dec.option_count() == request.option_count
+                    && dec.player() == request.player
+                    && dec.level_in_chain() == request.level_in_chain
+            }
+            _ => false,
+        };
+        if !matches {
+            return Err(RemoteError::invalid_message(
+                "position does not match the pending decision",
+            ));
+        }
+        let action = decide(&mut engine).map_err(|msg| RemoteError::DecisionFailed { msg })?;
+        let action = match (action, engine.pull()) {
+            (PlayerAction::Select(index), GameState::PendingDecision(dec)) => dec
+                .option_id(index)
+                .map_or(RemoteAction::Select(index), RemoteAction::SelectId),
+            (action, _) => action.into(),
+        };
+        write_action(&mut writer, action)?;
+    }
+    Ok(())
+}
+
+/// Client side of the protocol for games with hidden information: parses the view of each
+/// request and answers with the action chosen by `decide`.
+/// Returns when the game is finished or the stream is closed.
+pub fn run_view_client<V, R, W, F, D>(
+    mut reader: R,
+    mut writer: W,
+    parse_view: F,
+    mut decide: D,
+) -> Result<(), RemoteError>
+where
+    R: BufRead,
+    W: Write,
+    F: Fn(&str) -> Result<V, String>,
+    D: FnMut(&V, &ViewRequest) -> Result<PlayerAction, String>,
+{
+    while let Some(request) = read_view_request(&mut reader)? {
+        let view = parse_view(&request.view)
+            .map_err(|e| RemoteError::invalid_message(format!("invalid view: {e}")))?;
+        let action = decide(&view, &request).map_err(|msg| RemoteError::DecisionFailed { msg })?;
+        write_action(&mut writer, action.into())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        io::{BufReader, BufWriter},
+        net::{TcpListener, TcpStream},
+        thread,
+    };
+
+    use crate::{
+        engine::{
+            io::CompatibilityPolicy,
+            runner::{GameRunner, ObservingPlayer, Player, PlayerAction, ScriptedPlayer},
+            Engine, GameState, LoggingEngine,
+        },
+        test::{CountingGame, OrderedGame},
+        OptionId,
+    };
+
+    use super::{
+        read_action, read_request, read_view_request, run_client, run_view_client, write_action,
+        write_request, RemoteAction, RemoteError, RemotePlayer, RemoteRequest, RemoteViewPlayer,
+    };
+
+    fn parse_state(state: &[(String, String)]) -> Result<CountingGame, String> {
+        let (_, end) = state.first().ok_or("missing state")?;
+        Ok(CountingGame::new(end.parse().map_err(|_| "invalid end")?))
+    }
+
+    #[test]
+    fn tcp_test() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let client = thread::spawn(move || {
+            let stream = TcpStream::connect(address).unwrap();
+            let reader = BufReader::new(stream.try_clone().unwrap());
+            // selects the last option, but retracts the first follow-up decision once
+            let mut retracted = false;
+            let decide = move |engine: &mut LoggingEngine<CountingGame>| match engine.pull() {
+                GameState::PendingDecision(dec) if dec.level_in_chain() > 0 && !retracted => {
+                    retracted = true;
+                    Ok(PlayerAction::Retract(1))
+                }
+                GameState::PendingDecision(dec) => Ok(PlayerAction::Select(dec.option_count() - 1)),
+                _ => Err("expected pending decision".to_string()),
+            };
+            run_client(
+                reader,
+                stream,
+                "Counting",
+                [1, 0],
+                CompatibilityPolicy::MinorEqual,
+                parse_state,
+                decide,
+            )
+        });
+
+        let (stream, _) = listener.accept().unwrap();
+        let remote = RemotePlayer::new(
+            BufReader::new(stream.try_clone().unwrap()),
+            BufWriter::new(stream),
+            "Counting",
+            [1, 0],
+            vec![("end".to_string(), "8".to_string())],
+        );
+        let players: Vec<Box<dyn Player<_, _>>> = vec![
+            Box::new(ScriptedPlayer::selecting([0, 0, 0])),
+            Box::new(remote),
+        ];
+        let engine = LoggingEngine::new_logging(2, CountingGame::new(8));
+        let mut runner = GameRunner::new(engine, players);
+        runner.run().unwrap();
+        client.join().unwrap().unwrap();
+        assert_eq!(runner.engine().data().history, [1, 3, 1, 3]);
+        assert_eq!(
+            runner.engine().serialized_log().log,
+            [(0, 0), (1, 1), (1, 1), (0, 0), (1, 1), (1, 1)]
+        );
+    }
+
+    #[test]
+    fn view_tcp_test() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let client = thread::spawn(move || {
+            let stream = TcpStream::connect(address).unwrap();
+            let reader = BufReader::new(stream.try_clone().unwrap());
+            let parse_view = |view: &str| {
+                view.split_whitespace()
+                    .map(|val| val.parse::<u32>().map_err(|e| e.to_string()))
+                    .collect::<Result<Vec<_>, _>>()
+            };
+            let mut views = Vec::new();
+            run_view_client(reader, stream, parse_view, |view, request| {
+                views.push(view.clone());
+                Ok(PlayerAction::Select(request.option_count - 1))
+            })
+            .map(|_| views)
+        });
+
+        let (stream, _) = listener.accept().unwrap();
+        let encode_view = |view: &Vec<u32>| {
+            view.iter()
+                .map(u32::to_string)
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        let remote = RemoteViewPlayer::new(
+            BufReader::new(stream.try_clone().unwrap()),
+            BufWriter::new(stream),
+            encode_view,
+        );
+        let players: Vec<Box<dyn Player<_>>> = vec![
+            Box::new(ScriptedPlayer::selecting([0, 0])),
+            Box::new(ObservingPlayer::new(1, remote)),
+        ];
+        let mut runner = GameRunner::new(Engine::new(2, CountingGame::new(8)), players);
+        runner.run().unwrap();
+        let views = client.join().unwrap().unwrap();
+        assert_eq!(runner.engine().data().history, [1, 3, 1, 3]);
+        assert_eq!(views, [vec![], vec![], vec![3], vec![3]]);
+    }
+
+    #[test]
+    fn message_test() {
+        let mut buffer = Vec::new();
+        write_action(&mut buffer, RemoteAction::Retract(2)).unwrap();
+        assert_eq!(buffer, b"retract 2\n");
+        assert_eq!(
+            read_action(buffer.as_slice()).unwrap(),
+            RemoteAction::Retract(2)
+        );
+        let mut buffer = Vec::new();
+        write_action(&mut buffer, RemoteAction::SelectId(OptionId(7))).unwrap();
+        assert_eq!(buffer, b"select-id 7\n");
+        assert_eq!(
+            read_action(buffer.as_slice()).unwrap(),
+            RemoteAction::SelectId(OptionId(7))
+        );
+        assert!(matches!(
+            read_action("select two\n".as_bytes()),
+            Err(RemoteError::InvalidMessage { .. })
+        ));
+
+        let read = |input: &str| {
+            read_request(
+                input.as_bytes(),
+                "Counting",
+                [1, 0],
+                CompatibilityPolicy::MinorEqual,
+            )
+        };
+        assert!(read("finished\n").unwrap().is_none());
+        assert!(read("").unwrap().is_none());
+        let request = read("position\nCounting v1.0\nend 4\n2\n1P0\nC\ndecision 2 0 1\n")
+            .unwrap()
+            .unwrap();
+        assert_eq!(request.log.log, [(1, 0)]);
+        assert_eq!(
+            (request.option_count, request.player, request.level_in_chain),
+            (2, 0, 1)
+        );
+        assert!(matches!(
+            read("position\nCounting v1.0\nend 4\n2\nC\n"),
+            Err(RemoteError::InvalidMessage { .. })
+        ));
+        assert!(matches!(
+            read("position\nCounting v2.0\nend 4\n2\nC\ndecision 2 0 0\n"),
+            Err(RemoteError::InvalidPosition(_))
+        ));
+
+        let request = read_view_request("view\n1 2\n\ndecision 3 1 0\n".as_bytes())
+            .unwrap()
+            .unwrap();
+        assert_eq!(request.view, "1 2\n\n");
+        assert_eq!(
+            (request.option_count, request.player, request.level_in_chain),
+            (3, 1, 0)
+        );
+        assert!(matches!(
+            read_view_request("position\n".as_bytes()),
+            Err(RemoteError::InvalidMessage { .. })
+        ));
+
+        // a failing client is reported instead of answering
+        let mut output = Vec::new();
+        let result = run_view_client(
+            "view\n1\ndecision 2 0 0\n".as_bytes(),
+            &mut output,
+            |view| Ok(view.to_string()),
+            |_, _| Err("no option".to_string()),
+        );
+        assert!(matches!(result, Err(RemoteError::DecisionFailed { .. })));
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn option_id_test() {
+        let ordered = |descending| OrderedGame {
+            descending,
+            taken: Vec::new(),
+        };
+        let mut request = Vec::new();
+        let log = LoggingEngine::new_logging(2, ordered(true)).serialized_log();
+        let position = RemoteRequest {
+            initial_state: Vec::new(),
+            num_players: 2,
+            log,
+            option_count: 3,
+            player: 0,
+            level_in_chain: 0,
+        };
+        write_request(&mut request, "Ordered", [1, 0], &position).unwrap();
+
+        // the client enumerates the values in descending order and selects the largest one
+        let mut answer = Vec::new();
+        run_client(
+            request.as_slice(),
+            &mut answer,
+            "Ordered",
+            [1, 0],
+            CompatibilityPolicy::MinorEqual,
+            |_| Ok(ordered(true)),
+            |_| Ok(PlayerAction::Select(0)),
+        )
+        .unwrap();
+        assert_eq!(answer, b"select-id 3\n");
+
+        // the server enumerates the values in ascending order
+        let mut remote =
+            RemotePlayer::new(answer.as_slice(), Vec::new(), "Ordered", [1, 0], Vec::new());
+        let mut engine = LoggingEngine::new_logging(2, ordered(false));
+        match engine.pull() {
+            GameState::PendingDecision(dec) => {
+                assert_eq!(remote.decide(&dec), Ok(PlayerAction::Select(2)));
+            }
+            _ => panic!("expected pending decision"),
+        }
+    }
+}
@@ -0,0 +1,385 @@
+//! Stepping forwards and backwards through a saved game, e.g. to review it.
+
+use std::ops::Range;
+
+use crate::{GameData, RevEffect};
+
+use super::{
+    io::{replay_with_meta, restore_game_state_impl, LoadGameError, SerializedLog},
+    meta::MetaAction,
+    GameState, LoggingEngine, INTERNAL_ERROR,
+};
+
+/// Strategy of a `ReplayCursor` to go back to an earlier position.
+pub trait Rewind<T: GameData> {
+    /// Called for each position that is reached by stepping forward.
+    fn visit(&mut self, _position: usize, _data: &T) {}
+
+    /// Resets the engine from `position` to the earlier position `target`. The `log`
+    /// contains the selected options of all decisions, which end at the `decision_ends`.
+    /// The `meta` actions are applied after the given number of options (as in a
+    /// `SerializedLog`), including the actions at the target position.
+    fn rewind(
+        &mut self,
+        engine: &mut LoggingEngine<T>,
+        position: usize,
+        target: usize,
+        log: &[(usize, usize)],
+        decision_ends: &[usize],
+        meta: &[(usize, MetaAction)],
+    );
+}
+
+/// Goes back by undoing decisions, which requires reversible effects.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Undo;
+
+impl<T: GameData> Rewind<T> for Undo
+where
+    T::EffectType: RevEffect<T>,
+{
+    fn rewind(
+        &mut self,
+        engine: &mut LoggingEngine<T>,
+        position: usize,
+        target: usize,
+        _log: &[(usize, usize)],
+        _decision_ends: &[usize],
+        _meta: &[(usize, MetaAction)],
+    ) {
+        // the meta actions at the target position are kept
+        for _ in target..position {
+            assert!(engine.undo_last_decision(), "{}", INTERNAL_ERROR);
+        }
+    }
+}
+
+/// Goes back by replaying from the closest earlier checkpoint, where a checkpoint
+/// of the data is cached every `interval` decisions.
+#[derive(Debug, Clone)]
+pub struct Checkpoints<T> {
+    interval: usize,
+    /// position and the corresponding data, sorted by the former
+    states: Vec<(usize, T)>,
+}
+
+impl<T> Checkpoints<T> {
+    pub fn new(interval: usize) -> Self {
+        assert!(interval > 0, "Checkpoint interval must be positive");
+        Self {
+            interval,
+            states: Vec::new(),
+        }
+    }
+
+    pub fn interval(&self) -> usize {
+        self.interval
+    }
+}
+
+impl<T: GameData + Clone> Rewind<T> for Checkpoints<T> {
+    fn visit(&mut self, position: usize, data: &T) {
+        let is_new = self.states.last().map_or(true, |&(n, _)| n < position);
+        if position % self.interval == 0 && is_new {
+            self.states.push((position, data.clone()));
+        }
+    }
+
+    fn rewind(
+        &mut self,
+        engine: &mut LoggingEngine<T>,
+        _position: usize,
+        target: usize,
+        log: &[(usize, usize)],
+        decision_ends: &[usize],
+        meta: &[(usize, MetaAction)],
+    ) {
+        let (start, data) = self
+            .states
+            .iter()
+            .rev()
+            .find(|(n, _)| *n <= target)
+            .expect(INTERNAL_ERROR);
+        let decision_end = |n: usize| n.checked_sub(1).map_or(0, |i| decision_ends[i]);
+        let (first, last) = (decision_end(*start), decision_end(target));
+        // earlier actions don't affect the state after the checkpoint: the game
+        // is resumed and a draw offer expires before the next decision
+        let meta = meta
+            .iter()
+            .filter(|(n, _)| (first..=last).contains(n))
+            .map(|&(n, action)| (n - first, action))
+            .collect::<Vec<_>>();
+        *engine = restore_game_state_impl(engine.num_players, data.clone(), [].iter())
+            .expect(INTERNAL_ERROR);
+        replay_with_meta(engine, &log[first..last], &meta, |_, _| Ok(())).expect(INTERNAL_ERROR);
+    }
+}
+
+/// A cursor over the decisions of a saved game. The line of the game consists of the log
+/// and the redo stack, where an incomplete decision chain at the end is ignored. Meta actions
+/// of the log are applied at the position they belong to, e.g. a resignation at the end.
+///
+/// A position is the number of decisions applied to the initial state.
+pub struct ReplayCursor<T: GameData, R: Rewind<T>> {
+    engine: LoggingEngine<T>,
+    /// selected options of all decisions
+    log: Vec<(usize, usize)>,
+    /// length of the log after each decision
+    decision_ends: Vec<usize>,
+    /// meta actions and the length of the log before them
+    meta: Vec<(usize, MetaAction)>,
+    position: usize,
+    rewind: R,
+}
+
+impl<T: GameData> ReplayCursor<T, Undo>
+where
+    T::EffectType: RevEffect<T>,
+{
+    pub fn new(num_players: usize, data: T, log: &SerializedLog) -> Result<Self, LoadGameError> {
+        Self::with_rewind(num_players, data, log, Undo)
+    }
+}
+
+impl<T: GameData + Clone> ReplayCursor<T, Checkpoints<T>> {
+    pub fn with_checkpoints(
+        num_players: usize,
+        data: T,
+        log: &SerializedLog,
+        interval: usize,
+    ) -> Result<Self, LoadGameError> {
+        Self::with_rewind(num_players, data, log, Checkpoints::new(interval))
+    }
+}
+
+impl<T: GameData, R: Rewind<T>> ReplayCursor<T, R> {
+    /// Verifies the log by replaying it once. The cursor starts at the initial state.
+    pub fn with_rewind(
+        num_players: usize,
+        data: T,
+        log: &SerializedLog,
+        mut rewind: R,
+    ) -> Result<Self, LoadGameError> {
+        let mut options = log.log.clone();
+        options.extend(log.redo_stack.iter().rev());
+        let mut engine = restore_game_state_impl(num_players, data, [].iter())?;
+        let mut decision_ends = Vec::new();
+        rewind.visit(0, engine.data());
+        replay_with_meta(&mut engine, &options, &log.meta, |i, engine| {
+            if engine.decision_count() > decision_ends.len() {
+                decision_ends.push(i + 1);
+                rewind.visit(decision_ends.len(), engine.data());
+            }
+            Ok(())
+        })?;
+        options.truncate(decision_ends.last().copied().unwrap_or(0));
+
+        let mut result = Self {
+            engine,
+            log: options,
+            position: decision_ends.len(),
+            decision_ends,
+            meta: log.meta.clone(),
+            rewind,
+        };
+        result.seek(0);
+        Ok(result)
+    }
+
+    /// Number of decisions of the line.
+    pub fn len(&self) -> usize {
+        self.decision_ends.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.decision_ends.is_empty()
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Moves to the given position. Returns false (without any effect) if it is out of bounds.
+    pub fn seek(&mut self, target: usize) -> bool {
+        if target > self.len() {
+            return false;
+        }
+        if target < self.position {
+            // an incomplete decision chain at the end of the log is still pending
+            self.engine.retract_pending_chain();
+            self.rewind.rewind(
+                &mut self.engine,
+                self.position,
+                target,
+                &self.log,
+                &self.decision_ends,
+                &self.meta,
+            );
+            self.position = target;
+        }
+        while self.position < target {
+            for i in self.decision_range(self.position) {
+                let (index, _) = self.log[i];
+                match self.engine.pull() {
+                    GameState::PendingDecision(decision) => {
+                        decision.apply_option(index);
+                    }
+                    _ => panic!("{}", INTERNAL_ERROR),
+                }
+            }
+            self.position += 1;
+            self.rewind.visit(self.position, self.engine.data());
+            let end = self.decision_ends[self.position - 1];
+            for &(_, action) in self.meta.iter().filter(|&&(n, _)| n == end) {
+                self.engine.apply_meta_action(action).expect(INTERNAL_ERROR);
+            }
+        }
+        true
+    }
+
+    /// Applies the next decision. Returns false at the end of the line.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> bool {
+        self.seek(self.position + 1)
+    }
+
+    /// Goes back to the previous decision. Returns false at the start of the line.
+    pub fn prev(&mut self) -> bool {
+        self.position > 0 && self.seek(self.position - 1)
+    }
+
+    pub fn data(&self) -> &T {
+        self.engine.data()
+    }
+
+    /// Context of the pending decision at the current position (`None` at the end of the game).
+    pub fn context(&self) -> Option<T::Context> {
+        self.engine.get_decision().map(|_| self.engine.context())
+    }
+
+    /// Selected options (index and player) of the decision at the given position,
+    /// i.e. the decision that leads to the next position. Returns `None` at the end of the
+    /// line or if the position is out of bounds.
+    pub fn decision(&self, position: usize) -> Option<&[(usize, usize)]> {
+        (position < self.len()).then(|| &self.log[self.decision_range(position)])
+    }
+
+    fn decision_range(&self, position: usize) -> Range<usize> {
+        let start = position.checked_sub(1).map_or(0, |i| self.decision_ends[i]);
+        start..self.decision_ends[position]
+    }
+
+    /// The engine at the current position. Note that with `Checkpoints`,
+    /// its log starts at the last checkpoint used for going back.
+    pub fn engine(&self) -> &LoggingEngine<T> {
+        &self.engine
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        engine::{io::LoadGameError, meta::MetaAction, LoggingEngine},
+        test::{play, CountingGame, RevCountingGame},
+        Termination,
+    };
+
+    use super::{ReplayCursor, Rewind};
+
+    #[test]
+    fn undo_cursor_test() {
+        let mut engine = LoggingEngine::new_logging(2, RevCountingGame::new(100));
+        play(&mut engine, &[0, 1, 1, 0, 1, 0]);
+        // the last decision is on the redo stack
+        assert!(engine.undo_last_decision());
+        let mut log = engine.serialized_log();
+        // the last decision chain is incomplete
+        log.redo_stack.insert(0, (1, 0));
+        let mut cursor = ReplayCursor::new(2, RevCountingGame::new(100), &log).unwrap();
+        assert_eq!(cursor.len(), 4);
+        assert_eq!(cursor.position(), 0);
+        assert!(cursor.data().0.history.is_empty());
+        assert_eq!(cursor.decision(1), Some(&[(1, 1), (1, 1)][..]));
+        assert_eq!(cursor.decision(3), Some(&[(1, 1), (0, 1)][..]));
+        assert_eq!(cursor.decision(4), None);
+
+        assert!(cursor.next());
+        assert_eq!(cursor.data().0.history, [1]);
+        assert!(cursor.seek(3));
+        assert_eq!(cursor.data().0.history, [1, 3, 1]);
+        assert!(cursor.prev());
+        assert_eq!(cursor.data().0.history, [1, 3]);
+        assert!(!cursor.seek(5));
+        assert!(cursor.seek(4));
+        assert!(!cursor.next());
+        assert_eq!(cursor.data().0.history, [1, 3, 1, 2]);
+        assert!(cursor.seek(0));
+        assert!(!cursor.prev());
+        assert!(cursor.data().0.history.is_empty());
+    }
+
+    #[test]
+    fn checkpoint_cursor_test() {
+        let mut engine = LoggingEngine::new_logging(2, CountingGame::new(8));
+        play(&mut engine, &[0, 1, 1, 0, 1, 0, 0]);
+        assert!(engine.is_finished());
+        let log = engine.serialized_log();
+        let mut cursor = ReplayCursor::with_checkpoints(2, CountingGame::new(8), &log, 2).unwrap();
+        assert_eq!(cursor.len(), 5);
+        assert_eq!(cursor.context(), Some(()));
+
+        assert!(cursor.seek(5));
+        assert_eq!(cursor.data().history, [1, 3, 1, 2, 1]);
+        assert_eq!(cursor.context(), None);
+        assert!(cursor.prev());
+        assert_eq!(cursor.data().history, [1, 3, 1, 2]);
+        assert!(cursor.seek(1));
+        assert_eq!(cursor.data().history, [1]);
+        assert!(cursor.next());
+        assert_eq!(cursor.data().history, [1, 3]);
+        assert!(cursor.seek(0));
+        assert!(cursor.data().history.is_empty());
+    }
+
+    fn check_meta<R: Rewind<RevCountingGame>>(mut cursor: ReplayCursor<RevCountingGame, R>) {
+        assert_eq!(cursor.len(), 2);
+        assert_eq!(cursor.engine().meta_state().draw_offer(), None);
+        assert!(cursor.next());
+        assert_eq!(cursor.engine().meta_state().draw_offer(), Some(1));
+        assert!(cursor.next());
+        assert!(cursor.engine().is_finished());
+        assert_eq!(
+            cursor.engine().result().unwrap().termination,
+            Termination::Resignation
+        );
+        assert!(cursor.prev());
+        assert!(!cursor.engine().is_finished());
+        assert_eq!(cursor.engine().meta_state().draw_offer(), Some(1));
+        assert!(cursor.seek(0));
+        assert_eq!(cursor.engine().meta_state().draw_offer(), None);
+    }
+
+    #[test]
+    fn meta_cursor_test() {
+        let mut engine = LoggingEngine::new_logging(2, RevCountingGame::new(100));
+        play(&mut engine, &[0]);
+        engine.apply_meta_action(MetaAction::OfferDraw(1)).unwrap();
+        play(&mut engine, &[0]);
+        engine.apply_meta_action(MetaAction::Resign(0)).unwrap();
+        let log = engine.serialized_log();
+        check_meta(ReplayCursor::new(2, RevCountingGame::new(100), &log).unwrap());
+        check_meta(ReplayCursor::with_checkpoints(2, RevCountingGame::new(100), &log, 2).unwrap());
+    }
+
+    #[test]
+    fn invalid_log_test() {
+        let mut engine = LoggingEngine::new_logging(2, CountingGame::new(8));
+        play(&mut engine, &[0, 0]);
+        let mut log = engine.serialized_log();
+        log.log[1].0 = 5;
+        assert!(matches!(
+            ReplayCursor::with_checkpoints(2, CountingGame::new(8), &log, 2),
+            Err(LoadGameError::InvalidDecisionIndex { decision_nr: 1, .. })
+        ));
+    }
+}
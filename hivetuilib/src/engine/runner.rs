@@ -0,0 +1,396 @@
+use std::{
+    collections::VecDeque,
+    fmt::{self, Display},
+};
+
+use crate::{Decision, DecisionObject, GameData, GameResult, ObservableGameData};
+
+use super::{Engine, EngineError, EventListener, GameState, NotListening, PendingDecision};
+
+/// Action of a player for a pending decision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayerAction {
+    /// Selects the option with the given index.
+    Select(usize),
+    /// Retracts from the given number of subdecisions of the pending decision chain.
+    Retract(usize),
+}
+
+/// A participant of the game, e.g. a human, a script, an AI or a remote player.
+/// `D` is the decision type of the engine, e.g. `dyn SendDecision<T>` for a `SendEngine`.
+pub trait Player<
+    T: GameData,
+    L: EventListener<T> = NotListening,
+    D: DecisionObject<T> + ?Sized = dyn Decision<T>,
+>
+{
+    /// Chooses an action for the pending decision, which belongs to this player.
+    /// An error ends the game run.
+    fn decide(&mut self, decision: &PendingDecision<T, L, D>) -> Result<PlayerAction, String>;
+
+    /// Called for each action of any player (including this one),
+    /// after the resulting effects are applied.
+    fn action_applied(&mut self, _data: &T, _player: usize, _action: PlayerAction) {}
+
+    fn game_finished(&mut self, _data: &T, _result: Option<&GameResult>) {}
+}
+
+impl<T, L, D, F> Player<T, L, D> for F
+where
+    T: GameData,
+    L: EventListener<T>,
+    D: DecisionObject<T> + ?Sized,
+    F: FnMut(&PendingDecision<T, L, D>) -> Result<PlayerAction, String>,
+{
+    fn decide(&mut self, decision: &PendingDecision<T, L, D>) -> Result<PlayerAction, String> {
+        self(decision)
+    }
+}
+
+/// Player that performs a fixed sequence of actions.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptedPlayer {
+    actions: VecDeque<PlayerAction>,
+}
+
+impl ScriptedPlayer {
+    pub fn new<I: IntoIterator<Item = PlayerAction>>(actions: I) -> Self {
+        Self {
+            actions: actions.into_iter().collect(),
+        }
+    }
+
+    /// Player that selects the given options.
+    pub fn selecting<I: IntoIterator<Item = usize>>(indices: I) -> Self {
+        Self::new(indices.into_iter().map(PlayerAction::Select))
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.actions.len()
+    }
+}
+
+impl<T, L, D> Player<T, L, D> for ScriptedPlayer
+where
+    T: GameData,
+    L: EventListener<T>,
+    D: DecisionObject<T> + ?Sized,
+{
+    fn decide(&mut self, _decision: &PendingDecision<T, L, D>) -> Result<PlayerAction, String> {
+        self.actions
+            .pop_front()
+            .ok_or_else(|| "no scripted actions left".to_string())
+    }
+}
+
+/// A pending decision as seen by the deciding player of a game with hidden information.
+#[derive(Debug, Clone)]
+pub struct DecisionView<T: ObservableGameData> {
+    pub view: T::View,
+    pub context: T::Context,
+    pub option_count: usize,
+    pub player: usize,
+    pub level_in_chain: usize,
+}
+
+/// A participant of a game with hidden information, which only sees its own view of the data.
+/// Use `ObservingPlayer` to add it to a `GameRunner`.
+pub trait ViewPlayer<T: ObservableGameData> {
+    fn decide(&mut self, decision: &DecisionView<T>) -> Result<PlayerAction, String>;
+
+    /// Called for each action of any player (including this one), after the resulting
+    /// effects are applied. The selected options of other players might reveal hidden
+    /// information and are therefore not provided.
+    fn view_updated(&mut self, _view: &T::View, _player: usize) {}
+
+    fn game_finished(&mut self, _view: &T::View, _result: Option<&GameResult>) {}
+}
+
+/// Adapter that restricts a `ViewPlayer` to the view of the given player.
+#[derive(Debug, Clone)]
+pub struct ObservingPlayer<P> {
+    player: usize,
+    inner: P,
+}
+
+impl<P> ObservingPlayer<P> {
+    pub fn new(player: usize, inner: P) -> Self {
+        Self { player, inner }
+    }
+
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> P {
+        self.inner
+    }
+}
+
+impl<T, L, D, P> Player<T, L, D> for ObservingPlayer<P>
+where
+    T: ObservableGameData,
+    L: EventListener<T>,
+    D: DecisionObject<T> + ?Sized,
+    P: ViewPlayer<T>,
+{
+    fn decide(&mut self, decision: &PendingDecision<T, L, D>) -> Result<PlayerAction, String> {
+        debug_assert_eq!(decision.player(), self.player);
+        self.inner.decide(&DecisionView {
+            view: decision.view(),
+            context: decision.context(),
+            option_count: decision.option_count(),
+            player: decision.player(),
+            level_in_chain: decision.level_in_chain(),
+        })
+    }
+
+    fn action_applied(&mut self, data: &T, player: usize, _action: PlayerAction) {
+        self.inner.view_updated(&data.view(self.player), player);
+    }
+
+    fn game_finished(&mut self, data: &T, result: Option<&GameResult>) {
+        self.inner.game_finished(&data.view(self.player), result);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunnerError {
+    /// The action of the player is not valid for the pending decision.
+    InvalidAction {
+        player: usize,
+        action: PlayerAction,
+        error: EngineError,
+    },
+    /// The player could not decide on an action.
+    PlayerFailed { player: usize, msg: String },
+}
+
+impl Display for RunnerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunnerError::InvalidAction {
+                player,
+                action,
+                error,
+            } => write!(f, "invalid action {action:?} of player {player}: {error}"),
+            RunnerError::PlayerFailed { player, msg } => write!(f, "player {player} failed: {msg}"),
+        }
+    }
+}
+
+/// Drives an engine by asking the player of each pending decision for an action
+/// and applying all resulting effects.
+pub struct GameRunner<
+    T: GameData,
+    L: EventListener<T> = NotListening,
+    D: DecisionObject<T> + ?Sized = dyn Decision<T>,
+> {
+    engine: Engine<T, L, D>,
+    players: Vec<Box<dyn Player<T, L, D>>>,
+    chance_player: Option<Box<dyn Player<T, L, D>>>,
+}
+
+impl<T: GameData, L: EventListener<T>, D: DecisionObject<T> + ?Sized> GameRunner<T, L, D> {
+    /// Players are indexed by `Decision::player()`, so there must be one per player of the engine.
+    pub fn new(engine: Engine<T, L, D>, players: Vec<Box<dyn Player<T, L, D>>>) -> Self {
+        assert_eq!(
+            players.len(),
+            engine.num_players,
+            "Number of players does not match the engine"
+        );
+        Self {
+            engine,
+            players,
+            chance_player: None,
+        }
+    }
+
+    /// Sets the player for chance decisions whose player is not a player of the engine,
+    /// e.g. a `ChancePlayer`.
+    pub fn with_chance_player(mut self, chance_player: Box<dyn Player<T, L, D>>) -> Self {
+        self.chance_player = Some(chance_player);
+        self
+    }
+
+    pub fn engine(&self) -> &Engine<T, L, D> {
+        &self.engine
+    }
+
+    pub fn engine_mut(&mut self) -> &mut Engine<T, L, D> {
+        &mut self.engine
+    }
+
+    pub fn into_engine(self) -> Engine<T, L, D> {
+        self.engine
+    }
+
+    pub fn player_mut(&mut self, player: usize) -> &mut dyn Player<T, L, D> {
+        self.players[player].as_mut()
+    }
+
+    /// Performs the action of the player of the pending decision.
+    /// Returns `false` without any action if the game is finished.
+    pub fn step(&mut self) -> Result<bool, RunnerError> {
+        self.apply_effects();
+        let GameState::PendingDecision(decision) = self.engine.pull() else {
+            return Ok(false);
+        };
+        let player = decision.player();
+        let deciding = match self.players.get_mut(player) {
+            Some(deciding) => deciding,
+            None => self
+                .chance_player
+                .as_mut()
+                .ok_or_else(|| RunnerError::PlayerFailed {
+                    player,
+                    msg: "no chance player".to_string(),
+                })?,
+        };
+        let action = deciding
+            .decide(&decision)
+            .map_err(|msg| RunnerError::PlayerFailed { player, msg })?;
+        let result = match action {
+            PlayerAction::Select(index) => decision.try_select_option(index),
+            PlayerAction::Retract(n) => match decision.try_into_follow_up_decision() {
+                Ok(follow_up) => follow_up.try_retract_n(n),
+                Err(_) => Err(EngineError::InvalidRetraction {
+                    n,
+                    level_in_chain: 0,
+                }),
+            },
+        };
+        result.map_err(|error| RunnerError::InvalidAction {
+            player,
+            action,
+            error,
+        })?;
+
+        self.apply_effects();
+        for p in self.players.iter_mut().chain(&mut self.chance_player) {
+            p.action_applied(self.engine.data(), player, action);
+        }
+        Ok(true)
+    }
+
+    /// Runs the game until it is finished and returns the result.
+    pub fn run(&mut self) -> Result<Option<GameResult>, RunnerError> {
+        while self.step()? {}
+        let result = self.engine.result();
+        for p in self.players.iter_mut().chain(&mut self.chance_player) {
+            p.game_finished(self.engine.data(), result.as_ref());
+        }
+        Ok(result)
+    }
+
+    fn apply_effects(&mut self) {
+        if let GameState::PendingEffect(effect) = self.engine.pull() {
+            effect.all_effects();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        engine::{Engine, EngineError, NotListening, PendingDecision},
+        test::CountingGame,
+        GameResult,
+    };
+
+    use super::{
+        DecisionView, GameRunner, ObservingPlayer, Player, PlayerAction, RunnerError,
+        ScriptedPlayer, ViewPlayer,
+    };
+
+    /// Always selects the last option.
+    fn greedy(decision: &PendingDecision<CountingGame>) -> Result<PlayerAction, String> {
+        Ok(PlayerAction::Select(decision.option_count() - 1))
+    }
+
+    #[test]
+    fn runner_test() {
+        let engine = Engine::new(2, CountingGame::new(6));
+        let players: Vec<Box<dyn Player<CountingGame, NotListening>>> = vec![
+            Box::new(ScriptedPlayer::new([
+                PlayerAction::Select(1),
+                PlayerAction::Retract(1),
+                PlayerAction::Select(0),
+                PlayerAction::Select(0),
+            ])),
+            Box::new(greedy),
+        ];
+        let mut runner = GameRunner::new(engine, players);
+        let result = runner.run().unwrap();
+        assert_eq!(runner.engine().data().history, [1, 3, 1, 3]);
+        assert_eq!(result, Some(GameResult::win(1).with_scores(vec![8, 8])));
+        assert!(!runner.step().unwrap());
+    }
+
+    #[test]
+    fn runner_error_test() {
+        let engine = Engine::new(2, CountingGame::new(6));
+        let players: Vec<Box<dyn Player<CountingGame, NotListening>>> = vec![
+            Box::new(ScriptedPlayer::new([
+                PlayerAction::Retract(1),
+                PlayerAction::Select(2),
+            ])),
+            Box::new(ScriptedPlayer::default()),
+        ];
+        let mut runner = GameRunner::new(engine, players);
+        assert_eq!(
+            runner.step(),
+            Err(RunnerError::InvalidAction {
+                player: 0,
+                action: PlayerAction::Retract(1),
+                error: EngineError::InvalidRetraction {
+                    n: 1,
+                    level_in_chain: 0
+                },
+            })
+        );
+        assert!(matches!(
+            runner.step(),
+            Err(RunnerError::InvalidAction { player: 0, .. })
+        ));
+        assert!(runner.engine().data().history.is_empty());
+        assert!(matches!(
+            runner.run(),
+            Err(RunnerError::PlayerFailed { player: 0, .. })
+        ));
+    }
+
+    /// Records the views and always selects the first option.
+    struct RecordingPlayer(Rc<RefCell<Vec<Vec<u32>>>>);
+
+    impl ViewPlayer<CountingGame> for RecordingPlayer {
+        fn decide(
+            &mut self,
+            decision: &DecisionView<CountingGame>,
+        ) -> Result<PlayerAction, String> {
+            self.0.borrow_mut().push(decision.view.clone());
+            Ok(PlayerAction::Select(0))
+        }
+
+        fn view_updated(&mut self, view: &Vec<u32>, _player: usize) {
+            self.0.borrow_mut().push(view.clone());
+        }
+    }
+
+    #[test]
+    fn observing_player_test() {
+        let views = Rc::new(RefCell::new(Vec::new()));
+        let engine = Engine::new(2, CountingGame::new(5));
+        let players: Vec<Box<dyn Player<CountingGame, NotListening>>> = vec![
+            Box::new(ScriptedPlayer::selecting([1, 1, 0])),
+            Box::new(ObservingPlayer::new(1, RecordingPlayer(views.clone()))),
+        ];
+        let mut runner = GameRunner::new(engine, players);
+        runner.run().unwrap();
+        assert_eq!(runner.engine().data().history, [3, 1, 1]);
+        // the values of the first player are hidden
+        assert_eq!(*views.borrow(), [vec![], vec![], vec![], vec![1], vec![1]]);
+    }
+}
@@ -7,3 +7,244 @@ pub mod engine;
 pub use decision_impl::*;
 pub use trait_definitions::*;
 pub use utility::*;
+
+#[cfg(test)]
+pub(crate) mod test {
+    use crate::{
+        engine::{Engine, EventListener, GameState},
+        mapped_decision::MappedDecision,
+        plain_decision::PlainDecision,
+        vec_decision::VecDecision,
+        Decision, DecisionObject, Effect, GameData, GameResult, IdentifiableOption,
+        ObservableGameData, OptionId, RevEffect, SendDecision, SendGameData, StateHash,
+    };
+
+    /// Applies the given options, which must all belong to pending decisions.
+    pub(crate) fn play<T: GameData, L: EventListener<T>, D: DecisionObject<T> + ?Sized>(
+        engine: &mut Engine<T, L, D>,
+        indizes: &[usize],
+    ) {
+        for &index in indizes {
+            match engine.pull() {
+                GameState::PendingDecision(dec) => {
+                    dec.apply_option(index);
+                }
+                _ => panic!("expected pending decision"),
+            }
+        }
+    }
+
+    /// A game where two players alternately add numbers to a sum. Adding two
+    /// requires a follow-up decision between adding two or three.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub(crate) struct CountingGame {
+        pub sum: u32,
+        pub history: Vec<u32>,
+        pub player: usize,
+        pub finished_at: u32,
+    }
+
+    impl CountingGame {
+        pub(crate) fn new(finished_at: u32) -> Self {
+            Self {
+                sum: 0,
+                history: Vec::new(),
+                player: 0,
+                finished_at,
+            }
+        }
+
+        fn add(&mut self, val: u32) {
+            self.sum += val;
+            self.history.push(val);
+            self.player = (self.player + 1) % 2;
+        }
+
+        fn remove(&mut self) {
+            self.sum -= self.history.pop().unwrap();
+            self.player = (self.player + 1) % 2;
+        }
+    }
+
+    impl GameData for CountingGame {
+        type Context = ();
+        type EffectType = dyn Effect<Self>;
+
+        fn next_decision(&self) -> Option<Box<dyn Decision<Self>>> {
+            if self.sum >= self.finished_at {
+                return None;
+            }
+            let mut dec = PlainDecision::new(self.player);
+            dec.add_effect(|data: &mut CountingGame| {
+                data.add(1);
+                None
+            });
+            let player = self.player;
+            dec.add_follow_up(move |_| {
+                let mut follow_up = PlainDecision::new(player);
+                follow_up.add_effect(|data: &mut CountingGame| {
+                    data.add(2);
+                    None
+                });
+                follow_up.add_effect(|data: &mut CountingGame| {
+                    data.add(3);
+                    None
+                });
+                follow_up
+            });
+            Some(Box::new(dec))
+        }
+
+        /// The player who reaches the target sum wins.
+        fn result(&self) -> Option<GameResult> {
+            Some(GameResult::win((self.player + 1) % 2).with_scores(vec![self.sum as i64; 2]))
+        }
+    }
+
+    impl StateHash for CountingGame {
+        fn state_hash(&self) -> u64 {
+            self.history
+                .iter()
+                .fold(17, |hash, &val| 31 * hash + val as u64)
+        }
+    }
+
+    /// Each player only sees the values they added.
+    impl ObservableGameData for CountingGame {
+        type View = Vec<u32>;
+
+        fn view(&self, player: usize) -> Vec<u32> {
+            self.history
+                .iter()
+                .skip(player)
+                .step_by(2)
+                .copied()
+                .collect()
+        }
+    }
+
+    /// Same as `CountingGame`, but with reversible effects.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub(crate) struct RevCountingGame(pub CountingGame);
+
+    impl RevCountingGame {
+        pub(crate) fn new(finished_at: u32) -> Self {
+            Self(CountingGame::new(finished_at))
+        }
+    }
+
+    impl StateHash for RevCountingGame {
+        fn state_hash(&self) -> u64 {
+            self.0.state_hash()
+        }
+    }
+
+    impl RevCountingGame {
+        fn send_decision(&self) -> Option<PlainDecision<Self, dyn SendDecision<Self>>> {
+            if self.0.sum >= self.0.finished_at {
+                return None;
+            }
+            let mut dec = PlainDecision::new_send(self.0.player);
+            dec.add_rev_effect(
+                |data: &mut RevCountingGame| {
+                    data.0.add(1);
+                    None
+                },
+                |data: &mut RevCountingGame| data.0.remove(),
+            );
+            let player = self.0.player;
+            dec.add_follow_up(move |_| {
+                let mut follow_up = PlainDecision::new_send(player);
+                for val in [2, 3] {
+                    follow_up.add_rev_effect(
+                        move |data: &mut RevCountingGame| {
+                            data.0.add(val);
+                            None
+                        },
+                        |data: &mut RevCountingGame| data.0.remove(),
+                    );
+                }
+                follow_up
+            });
+            Some(dec)
+        }
+    }
+
+    impl GameData for RevCountingGame {
+        type Context = ();
+        type EffectType = dyn RevEffect<Self>;
+
+        fn next_decision(&self) -> Option<Box<dyn Decision<Self>>> {
+            self.send_decision()
+                .map(|dec| Box::new(dec) as Box<dyn Decision<Self>>)
+        }
+
+        fn result(&self) -> Option<GameResult> {
+            self.0.result()
+        }
+    }
+
+    impl SendGameData for RevCountingGame {
+        fn next_send_decision(&self) -> Option<Box<dyn SendDecision<Self>>> {
+            self.send_decision()
+                .map(|dec| Box::new(dec) as Box<dyn SendDecision<Self>>)
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub(crate) struct Value(pub u64);
+
+    impl IdentifiableOption for Value {
+        fn option_id(&self) -> OptionId {
+            OptionId(self.0)
+        }
+    }
+
+    /// Each player takes a value, which are enumerated in ascending or descending order.
+    pub(crate) struct OrderedGame {
+        pub descending: bool,
+        pub taken: Vec<u64>,
+    }
+
+    impl GameData for OrderedGame {
+        type Context = Vec<Value>;
+        type EffectType = dyn Effect<Self>;
+
+        fn next_decision(&self) -> Option<Box<dyn Decision<Self>>> {
+            let mut values = vec![1, 2, 3];
+            if self.descending {
+                values.reverse();
+            }
+            match self.taken.len() {
+                0 => {
+                    let mut dec = MappedDecision::new(0);
+                    for value in values {
+                        dec.add_option(Value(value));
+                    }
+                    dec.identify_options();
+                    Some(dec.spawn_by_effect(|_: &(), &Value(value): &Value| {
+                        move |data: &mut OrderedGame| {
+                            data.taken.push(value);
+                            None
+                        }
+                    }))
+                }
+                1 => {
+                    let mut dec: VecDecision<OrderedGame, Value> = VecDecision::new(1);
+                    for value in values {
+                        dec.add_effect(
+                            move |data: &mut OrderedGame| {
+                                data.taken.push(value);
+                                None
+                            },
+                            Value(value),
+                        );
+                    }
+                    dec.identify_options();
+                    Some(Box::new(dec))
+                }
+                _ => None,
+            }
+        }
+    }
+}
@@ -15,12 +15,15 @@ pub trait RevEffect<T: GameData>: Effect<T> {
 }
 
 /// Outcome of a decision - either an effect or a follow-up decision.
-pub enum Outcome<T: GameData> {
+///
+/// The follow-up decision is a `dyn Decision<T>`, or a `dyn SendDecision<T>` for the
+/// outcomes of a `SendDecision` (see `SendOutcome`).
+pub enum Outcome<T: GameData, D: ?Sized = dyn Decision<T>> {
     Effect(Box<T::EffectType>),
-    FollowUp(Box<dyn Decision<T>>),
+    FollowUp(Box<D>),
 }
 
-impl<T: GameData> Debug for Outcome<T> {
+impl<T: GameData, D: ?Sized> Debug for Outcome<T, D> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Outcome::Effect(_) => {
@@ -33,6 +36,24 @@ impl<T: GameData> Debug for Outcome<T> {
     }
 }
 
+/// Boxed function that maps the game data to the outcome of an option.
+pub type OutcomeFn<T> = Box<dyn Fn(&T) -> Outcome<T>>;
+
+/// Outcome of a `SendDecision`, whose follow-up decision can be sent to another thread.
+pub type SendOutcome<T> = Outcome<T, dyn SendDecision<T>>;
+
+/// Boxed function that maps the game data to the outcome of an option of a `SendDecision`.
+pub type SendOutcomeFn<T> = Box<dyn Fn(&T) -> SendOutcome<T> + Send>;
+
+impl<T: GameData> SendOutcome<T> {
+    pub fn into_outcome(self) -> Outcome<T> {
+        match self {
+            Outcome::Effect(effect) => Outcome::Effect(effect),
+            Outcome::FollowUp(decision) => Outcome::FollowUp(decision),
+        }
+    }
+}
+
 /// A game decision.
 /// `T`: GameData
 /// `C`: Context
@@ -46,6 +67,112 @@ pub trait Decision<T: GameData> {
 
     // seems like the best/most simple we can do in absence of GATs
     fn context(&self, data: &T) -> T::Context;
+
+    /// Weights of the options if this is a chance decision (e.g. a dice roll),
+    /// which is resolved randomly instead of by a player. The player of a chance decision
+    /// does not need to be a player of the game (see `GameRunner::with_chance_player`).
+    fn chance_weights(&self) -> Option<Vec<u32>> {
+        None
+    }
+
+    /// Identifier of the option that is independent of the order of the options, e.g. for
+    /// opening books or network messages. `None` if the decision doesn't identify its options.
+    fn option_id(&self, _data: &T, _index: usize) -> Option<OptionId> {
+        None
+    }
+
+    /// Index of the option with the given identifier, see `option_id`.
+    /// The identifiers of the options must be unique, which is checked in debug builds.
+    fn option_index(&self, data: &T, id: OptionId) -> Option<usize> {
+        let mut indices =
+            (0..self.option_count()).filter(|&index| self.option_id(data, index) == Some(id));
+        let index = indices.next();
+        debug_assert!(indices.next().is_none(), "Option id {id:?} is not unique!");
+        index
+    }
+}
+
+/// Stable identifier of an option, see `Decision::option_id`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OptionId(pub u64);
+
+/// A context element that identifies its option (e.g. a move from one field to another),
+/// which allows decisions to provide `Decision::option_id`.
+pub trait IdentifiableOption {
+    fn option_id(&self) -> OptionId;
+}
+
+/// A decision that can be sent to another thread, including its follow-up decisions
+/// (see `SendGameData`).
+pub trait SendDecision<T: GameData>: Decision<T> + Send {
+    /// Same as `Decision::select_option`, but the follow-up decision is a `SendDecision`.
+    fn select_send_option(&self, data: &T, index: usize) -> SendOutcome<T>;
+}
+
+/// Trait object of the decisions that are stored by the engine,
+/// i.e. `dyn Decision<T>` or `dyn SendDecision<T>`.
+pub trait DecisionObject<T: GameData>: Decision<T> {
+    /// Function that creates the outcome of an option.
+    type OutcomeFn: Fn(&T) -> Outcome<T, Self> + ?Sized;
+
+    fn next_decision(data: &T) -> Option<Box<Self>>;
+
+    fn select_outcome(&self, data: &T, index: usize) -> Outcome<T, Self>;
+
+    /// Converts the outcome to the outcome of a `dyn Decision<T>`.
+    fn into_outcome(outcome: Outcome<T, Self>) -> Outcome<T>;
+
+    /// Outcome function of an option that results in the effect created by `effect_fn`.
+    fn effect_fn<F>(effect_fn: F) -> Box<Self::OutcomeFn>
+    where
+        F: Fn(&T) -> Box<T::EffectType> + Send + 'static;
+}
+
+impl<T: GameData> DecisionObject<T> for dyn Decision<T> {
+    type OutcomeFn = dyn Fn(&T) -> Outcome<T>;
+
+    fn next_decision(data: &T) -> Option<Box<Self>> {
+        data.next_decision()
+    }
+
+    fn select_outcome(&self, data: &T, index: usize) -> Outcome<T> {
+        self.select_option(data, index)
+    }
+
+    fn into_outcome(outcome: Outcome<T>) -> Outcome<T> {
+        outcome
+    }
+
+    fn effect_fn<F>(effect_fn: F) -> Box<Self::OutcomeFn>
+    where
+        F: Fn(&T) -> Box<T::EffectType> + Send + 'static,
+    {
+        Box::new(move |data| Outcome::Effect(effect_fn(data)))
+    }
+}
+
+impl<T: SendGameData> DecisionObject<T> for dyn SendDecision<T> {
+    type OutcomeFn = dyn Fn(&T) -> SendOutcome<T> + Send;
+
+    fn next_decision(data: &T) -> Option<Box<Self>> {
+        data.next_send_decision()
+    }
+
+    fn select_outcome(&self, data: &T, index: usize) -> SendOutcome<T> {
+        self.select_send_option(data, index)
+    }
+
+    fn into_outcome(outcome: SendOutcome<T>) -> Outcome<T> {
+        outcome.into_outcome()
+    }
+
+    fn effect_fn<F>(effect_fn: F) -> Box<Self::OutcomeFn>
+    where
+        F: Fn(&T) -> Box<T::EffectType> + Send + 'static,
+    {
+        Box::new(move |data| Outcome::Effect(effect_fn(data)))
+    }
 }
 
 /// A context that where a specific element is provided for each
@@ -56,6 +183,110 @@ pub trait IndexableContext {
     fn select(&self, index: usize) -> Self::ContextElement;
 }
 
+/// An indexable context that consists of exactly one element per option (e.g. `VecContext`),
+/// which allows the combinators of `DecisionExt` to keep it consistent with the options.
+pub trait OptionContext: IndexableContext {
+    fn elements_mut(&mut self) -> &mut Vec<Self::ContextElement>;
+}
+
+impl<C: Clone> IndexableContext for Vec<C> {
+    type ContextElement = C;
+
+    fn select(&self, index: usize) -> C {
+        self[index].clone()
+    }
+}
+
+impl<C: Clone> OptionContext for Vec<C> {
+    fn elements_mut(&mut self) -> &mut Vec<C> {
+        self
+    }
+}
+
+/// Fingerprint of the game state, used to detect saved games that diverge when replayed.
+pub trait StateHash {
+    fn state_hash(&self) -> u64;
+}
+
+/// Game data with chance decisions, which are resolved by a seeded random number generator.
+///
+/// The random choice only depends on the seed, the state hash and the number of completed
+/// decisions, so it is the same after replaying, undoing or cloning. A state that repeats
+/// later in the game gets a new random choice.
+pub trait ChanceData: GameData + StateHash {
+    /// Seed of the random number generator, usually stored in the initial state.
+    fn chance_seed(&self) -> u64;
+}
+
+/// Game data with hidden information (e.g. the hands of a card game), where each
+/// player must only see a redacted view of the data.
+///
+/// Note that the context of a decision is visible to the deciding player.
+pub trait ObservableGameData: GameData {
+    type View;
+
+    /// The part of the data that is visible to the player.
+    fn view(&self, player: usize) -> Self::View;
+}
+
+/// Reason why a game ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Termination {
+    /// The game ended according to its rules.
+    Regular,
+    Resignation,
+    Timeout,
+    /// The players agreed on the result.
+    Agreement,
+    Other(String),
+}
+
+/// Structured outcome of a finished game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameResult {
+    /// Players who won the game (empty for a draw).
+    pub winners: Vec<usize>,
+    pub draw: bool,
+    /// Score of each player (empty if the game has no scores).
+    pub scores: Vec<i64>,
+    pub termination: Termination,
+}
+
+impl GameResult {
+    pub fn win(player: usize) -> Self {
+        Self {
+            winners: vec![player],
+            draw: false,
+            scores: Vec::new(),
+            termination: Termination::Regular,
+        }
+    }
+
+    pub fn draw() -> Self {
+        Self {
+            winners: Vec::new(),
+            draw: true,
+            scores: Vec::new(),
+            termination: Termination::Regular,
+        }
+    }
+
+    pub fn with_scores(mut self, scores: Vec<i64>) -> Self {
+        self.scores = scores;
+        self
+    }
+
+    pub fn with_termination(mut self, termination: Termination) -> Self {
+        self.termination = termination;
+        self
+    }
+
+    pub fn is_winner(&self, player: usize) -> bool {
+        self.winners.contains(&player)
+    }
+}
+
 /// Interface between the data and the GameEngine.
 pub trait GameData: Sized + 'static {
     /// Context that is added to each decision.
@@ -65,4 +296,35 @@ pub trait GameData: Sized + 'static {
     type EffectType: Effect<Self> + ?Sized;
 
     fn next_decision(&self) -> Option<Box<dyn Decision<Self>>>;
+
+    /// Outcome of the game, only called if there is no next decision.
+    fn result(&self) -> Option<GameResult> {
+        None
+    }
+}
+
+/// Game data whose decisions can be sent to another thread, which allows to send
+/// the engine as well (see `SendEngine`).
+///
+/// The decisions must be the same as the ones returned by `GameData::next_decision`,
+/// which is still used e.g. for replaying the game.
+pub trait SendGameData: GameData {
+    fn next_send_decision(&self) -> Option<Box<dyn SendDecision<Self>>>;
+}
+
+/// Human-readable notation for moves, i.e. complete decision chains (similar to algebraic
+/// chess notation). Each decision of a chain is described by its context and the selected index.
+///
+/// Tokens are computed on the game state before the move is applied.
+pub trait MoveNotation: GameData {
+    fn write_notation(&self, chain: &[(Self::Context, usize)]) -> String;
+
+    /// Resolves the index for the pending decision with the given context, where `chain`
+    /// contains the already resolved decisions of the move.
+    fn read_notation(
+        &self,
+        token: &str,
+        chain: &[(Self::Context, usize)],
+        context: &Self::Context,
+    ) -> Result<usize, String>;
 }